@@ -0,0 +1,18 @@
+pub mod bdd;
+pub mod equivalence;
+pub mod fault;
+pub(crate) mod gate_network;
+pub mod minimize;
+pub mod parallel;
+pub mod random;
+pub mod sat;
+pub mod truth_table;
+
+pub use bdd::{Bdd, Node, SymbolicCircuit, VariableOrder};
+pub use equivalence::{check_equivalence, check_equivalence_with, Counterexample, Method};
+pub use fault::{fault_list, generate_tests, simulate_faults, Fault, FaultReport, TestSet};
+pub use gate_network::logic_depth;
+pub use minimize::{cover, minimize, minimize_table, prime_implicants, Implicant};
+pub use parallel::ParallelCircuit;
+pub use random::RandomVectors;
+pub use truth_table::{Mismatch, TruthTable, MAX_TRUTH_TABLE_INPUTS};
@@ -0,0 +1,452 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::analysis::gate_network::GateNetwork;
+use crate::components::gates::GateKind;
+use crate::simulation::Circuit;
+
+// A boolean function stored in a `Bdd`. Equal functions are the same node.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Node(u32);
+
+impl Node {
+    pub const FALSE: Node = Node(0);
+    pub const TRUE: Node = Node(1);
+
+    pub fn is_constant(self) -> bool {
+        self.0 < 2
+    }
+}
+
+// How circuit inputs are ordered as BDD variables. The order decides the size of the
+// diagrams: an adder needs nodes linear in its width if the bits of both operands are
+// interleaved, but exponentially many if all bits of one operand come first.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum VariableOrder {
+    // The order the input ports are declared in
+    Declaration,
+    // The order a depth-first walk from the outputs reaches the inputs in, which keeps
+    // inputs that feed the same gates close together
+    DepthFirst,
+    Custom(Vec<String>),
+}
+
+// Reduced ordered binary decision diagrams over a fixed number of variables, variable 0
+// being tested first. Nodes are shared between all functions of the same `Bdd`.
+#[derive(Debug)]
+pub struct Bdd {
+    variables: usize,
+    // Variable, low (variable is 0) and high child of every node, the terminals first
+    nodes: Vec<(usize, Node, Node)>,
+    unique: HashMap<(usize, Node, Node), Node>,
+    // Results of if-then-else
+    computed: HashMap<(Node, Node, Node), Node>,
+}
+
+impl Bdd {
+    pub fn new(variables: usize) -> Bdd {
+        Bdd {
+            variables,
+            // Terminals test a variable past the last one so they sort below every node
+            nodes: vec![(variables, Node::FALSE, Node::FALSE), (variables, Node::TRUE, Node::TRUE)],
+            unique: HashMap::new(),
+            computed: HashMap::new(),
+        }
+    }
+
+    pub fn variable_count(&self) -> usize {
+        self.variables
+    }
+
+    pub fn constant(&self, value: bool) -> Node {
+        if value { Node::TRUE } else { Node::FALSE }
+    }
+
+    pub fn variable(&mut self, variable: usize) -> Node {
+        assert!(variable < self.variables, "variable {} out of range", variable);
+        self.make(variable, Node::FALSE, Node::TRUE)
+    }
+
+    pub fn not(&mut self, f: Node) -> Node {
+        self.ite(f, Node::FALSE, Node::TRUE)
+    }
+
+    pub fn and(&mut self, f: Node, g: Node) -> Node {
+        self.ite(f, g, Node::FALSE)
+    }
+
+    pub fn or(&mut self, f: Node, g: Node) -> Node {
+        self.ite(f, Node::TRUE, g)
+    }
+
+    pub fn xor(&mut self, f: Node, g: Node) -> Node {
+        let not_g = self.not(g);
+        self.ite(f, not_g, g)
+    }
+
+    // If f then g else h, from which all other operations are built
+    pub fn ite(&mut self, f: Node, g: Node, h: Node) -> Node {
+        if f == Node::TRUE || g == h {
+            return g;
+        }
+        if f == Node::FALSE {
+            return h;
+        }
+        if g == Node::TRUE && h == Node::FALSE {
+            return f;
+        }
+        if let Some(&result) = self.computed.get(&(f, g, h)) {
+            return result;
+        }
+
+        let variable = self.top(f).min(self.top(g)).min(self.top(h));
+        let (f0, f1) = self.cofactors(f, variable);
+        let (g0, g1) = self.cofactors(g, variable);
+        let (h0, h1) = self.cofactors(h, variable);
+        let low = self.ite(f0, g0, h0);
+        let high = self.ite(f1, g1, h1);
+        let result = self.make(variable, low, high);
+        self.computed.insert((f, g, h), result);
+        result
+    }
+
+    // Value of the function for the given variable values
+    pub fn evaluate(&self, f: Node, values: &[bool]) -> bool {
+        let mut node = f;
+        while !node.is_constant() {
+            let (variable, low, high) = self.nodes[node.0 as usize];
+            node = if values[variable] { high } else { low };
+        }
+        node == Node::TRUE
+    }
+
+    pub fn is_satisfiable(&self, f: Node) -> bool {
+        f != Node::FALSE
+    }
+
+    // Variable values for which the function is 1, variables it does not depend on being 0
+    pub fn satisfying_assignment(&self, f: Node) -> Option<Vec<bool>> {
+        if f == Node::FALSE {
+            return None;
+        }
+        let mut values = vec![false; self.variables];
+        let mut node = f;
+        // In a reduced diagram every path that avoids the 0 terminal ends at the 1 terminal
+        while !node.is_constant() {
+            let (variable, low, high) = self.nodes[node.0 as usize];
+            if high != Node::FALSE {
+                values[variable] = true;
+                node = high;
+            } else {
+                node = low;
+            }
+        }
+        Some(values)
+    }
+
+    // Number of variable assignments for which the function is 1, or None if it does not
+    // fit into a u128
+    pub fn count(&self, f: Node) -> Option<u128> {
+        let mut counts = HashMap::new();
+        let below = self.count_below(f, &mut counts);
+        scale(below, self.top(f))
+    }
+
+    // Assignments of the variables from the node's own variable on
+    fn count_below(&self, f: Node, counts: &mut HashMap<Node, Option<u128>>) -> Option<u128> {
+        if f.is_constant() {
+            return Some(if f == Node::TRUE { 1 } else { 0 });
+        }
+        if let Some(&count) = counts.get(&f) {
+            return count;
+        }
+        let (variable, low, high) = self.nodes[f.0 as usize];
+        // Variables skipped between a node and its child can take either value
+        let low_count = scale(self.count_below(low, counts), self.top(low) - variable - 1);
+        let high_count = scale(self.count_below(high, counts), self.top(high) - variable - 1);
+        let count = low_count.zip(high_count).and_then(|(low, high)| low.checked_add(high));
+        counts.insert(f, count);
+        count
+    }
+
+    // Nodes reachable from the function, terminals included
+    pub fn size(&self, f: Node) -> usize {
+        let mut seen = HashSet::new();
+        let mut stack = vec![f];
+        while let Some(node) = stack.pop() {
+            if seen.insert(node) && !node.is_constant() {
+                let (_, low, high) = self.nodes[node.0 as usize];
+                stack.push(low);
+                stack.push(high);
+            }
+        }
+        seen.len()
+    }
+
+    // Nodes created so far for all functions together
+    pub fn node_count(&self) -> usize {
+        self.nodes.len()
+    }
+
+    // Functions of the circuit's outputs, in output order. Every input is the variable
+    // at its position in `variables`, which has to name all inputs.
+    pub fn add_circuit(&mut self, circuit: &Circuit, variables: &[String]) -> Result<Vec<Node>, String> {
+        let network = GateNetwork::new(circuit)?;
+        let mut functions: Vec<Option<Node>> = network.constants.iter().map(|constant| constant.map(|value| self.constant(value))).collect();
+        for (name, &net) in circuit.inputs().iter().zip(&network.inputs) {
+            let Some(variable) = variables.iter().position(|variable| variable == name) else {
+                return Err(format!("Input '{}' of {} has no variable", name, circuit.name()));
+            };
+            functions[net] = Some(self.variable(variable));
+        }
+        for gate in &network.gates {
+            let inputs: Vec<Node> = gate.inputs.iter().map(|&net| functions[net].unwrap()).collect();
+            functions[gate.output] = Some(self.gate(gate.kind, &inputs));
+        }
+        Ok(network.outputs.iter().map(|&net| functions[net].unwrap()).collect())
+    }
+
+    fn gate(&mut self, kind: GateKind, inputs: &[Node]) -> Node {
+        match kind {
+            GateKind::Buffer | GateKind::TriState => inputs[0],
+            GateKind::Not => self.not(inputs[0]),
+            GateKind::And => self.fold(inputs, Node::TRUE, Bdd::and),
+            GateKind::Or => self.fold(inputs, Node::FALSE, Bdd::or),
+            GateKind::Xor => self.fold(inputs, Node::FALSE, Bdd::xor),
+            GateKind::Nand => {
+                let and = self.fold(inputs, Node::TRUE, Bdd::and);
+                self.not(and)
+            }
+            GateKind::Nor => {
+                let or = self.fold(inputs, Node::FALSE, Bdd::or);
+                self.not(or)
+            }
+            GateKind::Xnor => {
+                let xor = self.fold(inputs, Node::FALSE, Bdd::xor);
+                self.not(xor)
+            }
+        }
+    }
+
+    fn fold(&mut self, inputs: &[Node], start: Node, operation: fn(&mut Bdd, Node, Node) -> Node) -> Node {
+        inputs.iter().fold(start, |result, &input| operation(self, result, input))
+    }
+
+    fn make(&mut self, variable: usize, low: Node, high: Node) -> Node {
+        if low == high {
+            return low;
+        }
+        if let Some(&node) = self.unique.get(&(variable, low, high)) {
+            return node;
+        }
+        let node = Node(self.nodes.len() as u32);
+        self.nodes.push((variable, low, high));
+        self.unique.insert((variable, low, high), node);
+        node
+    }
+
+    fn top(&self, f: Node) -> usize {
+        self.nodes[f.0 as usize].0
+    }
+
+    // The function with the variable set to 0 and to 1, for a variable at or above its top
+    fn cofactors(&self, f: Node, variable: usize) -> (Node, Node) {
+        let (top, low, high) = self.nodes[f.0 as usize];
+        if top == variable { (low, high) } else { (f, f) }
+    }
+}
+
+// A count times 2^exponent, or None if it overflows
+fn scale(count: Option<u128>, exponent: usize) -> Option<u128> {
+    match count? {
+        0 => Some(0),
+        count => u32::try_from(exponent).ok().and_then(|exponent| 1u128.checked_shl(exponent))?.checked_mul(count),
+    }
+}
+
+// Input names of a circuit in the given order
+pub fn variable_order(circuit: &Circuit, order: &VariableOrder) -> Result<Vec<String>, String> {
+    match order {
+        VariableOrder::Declaration => Ok(circuit.inputs().to_vec()),
+        VariableOrder::Custom(variables) => {
+            let mut sorted = variables.clone();
+            let mut inputs = circuit.inputs().to_vec();
+            sorted.sort();
+            inputs.sort();
+            if sorted != inputs {
+                return Err(format!("The order has to name every input of {} once", circuit.name()));
+            }
+            Ok(variables.clone())
+        }
+        VariableOrder::DepthFirst => {
+            let network = GateNetwork::new(circuit)?;
+            let mut driver = vec![None; circuit.net_count()];
+            for gate in &network.gates {
+                driver[gate.output] = Some(gate);
+            }
+            let mut visited = vec![false; circuit.net_count()];
+            let mut variables = Vec::new();
+            // An explicit stack, as carry chains of wide adders are thousands of gates deep
+            let mut stack: Vec<usize> = network.outputs.iter().rev().copied().collect();
+            while let Some(net) = stack.pop() {
+                if visited[net] {
+                    continue;
+                }
+                visited[net] = true;
+                match driver[net] {
+                    Some(gate) => stack.extend(gate.inputs.iter().rev()),
+                    None => {
+                        if let Some(input) = network.inputs.iter().position(|&input| input == net) {
+                            variables.push(circuit.inputs()[input].clone());
+                        }
+                    }
+                }
+            }
+            // Inputs no output depends on go last
+            for input in circuit.inputs() {
+                if !variables.contains(input) {
+                    variables.push(input.clone());
+                }
+            }
+            Ok(variables)
+        }
+    }
+}
+
+// The output functions of a circuit as BDDs
+#[derive(Debug)]
+pub struct SymbolicCircuit {
+    pub bdd: Bdd,
+    // Circuit inputs in variable order
+    pub variables: Vec<String>,
+    pub outputs: Vec<(String, Node)>,
+}
+
+impl SymbolicCircuit {
+    pub fn new(circuit: &Circuit, order: &VariableOrder) -> Result<SymbolicCircuit, String> {
+        let variables = variable_order(circuit, order)?;
+        let mut bdd = Bdd::new(variables.len());
+        let functions = bdd.add_circuit(circuit, &variables)?;
+        let outputs = circuit.outputs().iter().cloned().zip(functions).collect();
+        Ok(SymbolicCircuit { bdd, variables, outputs })
+    }
+
+    pub fn output(&self, name: &str) -> Option<Node> {
+        self.outputs.iter().find(|(output, _)| output == name).map(|&(_, node)| node)
+    }
+
+    // Input values, by input name, for which the function is 1
+    pub fn satisfying_inputs(&self, f: Node) -> Option<Vec<(String, bool)>> {
+        let values = self.bdd.satisfying_assignment(f)?;
+        Some(self.variables.iter().cloned().zip(values).collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::digital_logic::arithmetic::RippleCarryAdder;
+    use crate::expression::{compile, parse};
+    use crate::netlist::load_builtin;
+
+    #[test]
+    fn test_canonical_form() {
+        let mut bdd = Bdd::new(3);
+        let [a, b, c] = [bdd.variable(0), bdd.variable(1), bdd.variable(2)];
+        // a & (b | c) and a & b | a & c are the same node
+        let b_or_c = bdd.or(b, c);
+        let first = bdd.and(a, b_or_c);
+        let a_and_b = bdd.and(a, b);
+        let a_and_c = bdd.and(a, c);
+        let second = bdd.or(a_and_b, a_and_c);
+        assert_eq!(first, second);
+
+        let not_a = bdd.not(a);
+        assert_eq!(bdd.and(a, not_a), Node::FALSE);
+        assert_eq!(bdd.or(a, not_a), Node::TRUE);
+        let twice = bdd.not(not_a);
+        assert_eq!(twice, a);
+        assert_eq!(bdd.size(first), 5);
+    }
+
+    #[test]
+    fn test_count_and_satisfy() {
+        let mut bdd = Bdd::new(4);
+        let [a, b, c] = [bdd.variable(0), bdd.variable(1), bdd.variable(3)];
+        let a_xor_b = bdd.xor(a, b);
+        let f = bdd.and(a_xor_b, c);
+        // Variable 2 is free, so 2 * 2 of the 16 assignments
+        assert_eq!(bdd.count(f), Some(4));
+        assert_eq!(bdd.count(Node::TRUE), Some(16));
+        assert_eq!(bdd.count(Node::FALSE), Some(0));
+        assert_eq!(bdd.count(c), Some(8));
+
+        let values = bdd.satisfying_assignment(f).unwrap();
+        assert!(bdd.evaluate(f, &values));
+        assert!(bdd.is_satisfiable(f));
+        assert_eq!(bdd.satisfying_assignment(Node::FALSE), None);
+
+        // 2^129 assignments do not fit, but none or 2^127 do
+        let mut bdd = Bdd::new(130);
+        let first = bdd.variable(0);
+        assert_eq!(bdd.count(first), None);
+        assert_eq!(bdd.count(Node::FALSE), Some(0));
+        let pair = [bdd.variable(1), bdd.variable(2)];
+        let both = bdd.and(first, pair[0]);
+        let all = bdd.and(both, pair[1]);
+        assert_eq!(bdd.count(all), Some(1 << 127));
+    }
+
+    #[test]
+    fn test_circuit_functions() {
+        let circuit = load_builtin("full_adder").unwrap();
+        let symbolic = SymbolicCircuit::new(&circuit, &VariableOrder::Declaration).unwrap();
+        assert_eq!(symbolic.variables, vec!["a", "b", "cin"]);
+        let sum = symbolic.output("sum").unwrap();
+        let cout = symbolic.output("cout").unwrap();
+        for row in 0..8usize {
+            let values: Vec<bool> = (0..3).map(|i| row >> (2 - i) & 1 == 1).collect();
+            let ones = row.count_ones();
+            assert_eq!(symbolic.bdd.evaluate(sum, &values), ones % 2 == 1);
+            assert_eq!(symbolic.bdd.evaluate(cout, &values), ones >= 2);
+        }
+        assert_eq!(symbolic.bdd.count(cout), Some(4));
+
+        let symbolic = SymbolicCircuit::new(&compile(&parse("a & !a | b & 0").unwrap()), &VariableOrder::Declaration).unwrap();
+        assert_eq!(symbolic.output("y"), Some(Node::FALSE));
+        assert_eq!(symbolic.satisfying_inputs(Node::FALSE), None);
+    }
+
+    #[test]
+    fn test_variable_orders() {
+        let circuit = RippleCarryAdder::new(3).into_circuit();
+        let order = variable_order(&circuit, &VariableOrder::DepthFirst).unwrap();
+        assert_eq!(order, vec!["a0", "b0", "a1", "b1", "a2", "b2"]);
+        let custom = VariableOrder::Custom(vec!["a0".to_string()]);
+        assert!(variable_order(&circuit, &custom).is_err());
+
+        // Interleaving keeps the carry small, declaring all a bits first does not
+        let circuit = RippleCarryAdder::new(10).into_circuit();
+        let size = |order: &VariableOrder| {
+            let symbolic = SymbolicCircuit::new(&circuit, order).unwrap();
+            symbolic.bdd.size(symbolic.output("carry9").unwrap())
+        };
+        assert!(size(&VariableOrder::DepthFirst) < 40);
+        assert!(size(&VariableOrder::Declaration) > 1000);
+    }
+
+    #[test]
+    fn test_wide_adder() {
+        // 64 inputs, far too many to enumerate
+        let circuit = RippleCarryAdder::new(32).into_circuit();
+        let symbolic = SymbolicCircuit::new(&circuit, &VariableOrder::DepthFirst).unwrap();
+        let carry = symbolic.output("carry31").unwrap();
+        // a + b overflows for (2^32 - 1) * 2^32 / 2 of the pairs
+        assert_eq!(symbolic.bdd.count(carry), Some(((1u128 << 32) - 1) << 31));
+
+        let inputs = symbolic.satisfying_inputs(carry).unwrap();
+        let value = |operand: &str| {
+            (0..32).filter(|i| inputs.iter().any(|(name, high)| *high && name == &format!("{}{}", operand, i))).map(|i| 1u64 << i).sum::<u64>()
+        };
+        assert!(value("a") + value("b") >= 1 << 32);
+    }
+}
@@ -0,0 +1,474 @@
+use core::fmt;
+use std::collections::HashMap;
+
+use crate::analysis::bdd::{variable_order, Bdd, VariableOrder};
+use crate::analysis::gate_network::GateNetwork;
+use crate::analysis::parallel::{row_words, ParallelCircuit};
+use crate::analysis::sat::{Literal, Solver};
+use crate::analysis::truth_table::row_count;
+use crate::components::gates::GateKind;
+use crate::components::Level;
+use crate::simulation::{Circuit, Simulator};
+
+// Circuits with up to this many inputs are compared by simulating every input combination
+pub const MAX_EXHAUSTIVE_INPUTS: usize = 12;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Method {
+    // Simulate both circuits for every input combination
+    Exhaustive,
+    // Ask a SAT solver for inputs on which the outputs differ (a miter of both circuits)
+    Sat,
+    // Build the outputs of both circuits as BDDs, which are equal exactly for equal functions
+    Bdd,
+}
+
+// Inputs on which two circuits differ
+#[derive(Debug, Clone, PartialEq)]
+pub struct Counterexample {
+    pub inputs: Vec<(String, Level)>,
+    // Outputs that differ, with their level in the first and in the second circuit
+    pub outputs: Vec<(String, Level, Level)>,
+}
+
+impl fmt::Display for Counterexample {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let inputs: Vec<String> = self.inputs.iter().map(|(name, level)| format!("{}={}", name, level.symbol())).collect();
+        let outputs: Vec<String> = self
+            .outputs
+            .iter()
+            .map(|(name, first, second)| format!("{} is {} and {}", name, first.symbol(), second.symbol()))
+            .collect();
+        write!(f, "{}: {}", inputs.join(" "), outputs.join(", "))
+    }
+}
+
+// Check that two circuits with the same port names compute the same outputs for all
+// inputs of 0 and 1. Small circuits are simulated exhaustively, larger ones go to the SAT solver.
+// Returns None if they are equivalent, otherwise inputs on which they differ.
+pub fn check_equivalence(first: Circuit, second: Circuit) -> Result<Option<Counterexample>, String> {
+    let method = if first.inputs().len() <= MAX_EXHAUSTIVE_INPUTS { Method::Exhaustive } else { Method::Sat };
+    check_equivalence_with(first, second, method)
+}
+
+pub fn check_equivalence_with(first: Circuit, second: Circuit, method: Method) -> Result<Option<Counterexample>, String> {
+    let mut sorted = [first.inputs().to_vec(), second.inputs().to_vec(), first.outputs().to_vec(), second.outputs().to_vec()];
+    for names in sorted.iter_mut() {
+        names.sort();
+    }
+    if sorted[0] != sorted[1] || sorted[2] != sorted[3] {
+        return Err(format!(
+            "Ports differ: {} | {} and {} | {}",
+            first.inputs().join(" "),
+            first.outputs().join(" "),
+            second.inputs().join(" "),
+            second.outputs().join(" ")
+        ));
+    }
+    if method == Method::Exhaustive && first.inputs().len() > MAX_EXHAUSTIVE_INPUTS {
+        return Err(format!(
+            "{} inputs are too many to simulate exhaustively, the limit is {}",
+            first.inputs().len(),
+            MAX_EXHAUSTIVE_INPUTS
+        ));
+    }
+
+    let assignment = match method {
+        Method::Exhaustive => None,
+        Method::Sat => match find_difference(&first, &second)? {
+            Some(assignment) => Some(assignment),
+            None => return Ok(None),
+        },
+        Method::Bdd => match bdd_difference(&first, &second)? {
+            Some(assignment) => Some(assignment),
+            None => return Ok(None),
+        },
+    };
+
+    let inputs = first.inputs().to_vec();
+    let assignment = match assignment {
+        None if method == Method::Exhaustive => match parallel_difference(&first, &second) {
+            Ok(None) => return Ok(None),
+            Ok(assignment) => assignment,
+            Err(_) => None,
+        },
+        assignment => assignment,
+    };
+    let mut simulators = [Simulator::new(first), Simulator::new(second)];
+    if let Some(assignment) = assignment {
+        return compare(&mut simulators, &inputs, &assignment);
+    }
+    // The first input is the most significant, like in truth tables
+    for row in 0..1u64 << inputs.len() {
+        let levels: Vec<Level> = (0..inputs.len())
+            .map(|i| if row >> (inputs.len() - 1 - i) & 1 == 1 { Level::High } else { Level::Low })
+            .collect();
+        if let Some(counterexample) = compare(&mut simulators, &inputs, &levels)? {
+            return Ok(Some(counterexample));
+        }
+    }
+    Ok(None)
+}
+
+// Simulate both circuits with the same inputs and report the outputs that differ
+fn compare(simulators: &mut [Simulator; 2], inputs: &[String], levels: &[Level]) -> Result<Option<Counterexample>, String> {
+    for simulator in simulators.iter_mut() {
+        for (name, &level) in inputs.iter().zip(levels) {
+            simulator.set_level(name, level).map_err(|e| e.to_string())?;
+        }
+        simulator.settle().map_err(|e| e.to_string())?;
+    }
+
+    let mut outputs = Vec::new();
+    for name in simulators[0].circuit().outputs() {
+        let first = simulators[0].level(name).map_err(|e| e.to_string())?;
+        let second = simulators[1].level(name).map_err(|e| e.to_string())?;
+        if first != second {
+            outputs.push((name.clone(), first, second));
+        }
+    }
+    if outputs.is_empty() {
+        return Ok(None);
+    }
+    let inputs = inputs.iter().cloned().zip(levels.iter().copied()).collect();
+    Ok(Some(Counterexample { inputs, outputs }))
+}
+
+// Sweep all inputs 64 rows at a time, for circuits a ParallelCircuit can evaluate
+fn parallel_difference(first: &Circuit, second: &Circuit) -> Result<Option<Vec<Level>>, String> {
+    let mut circuits = [ParallelCircuit::new(first)?, ParallelCircuit::new(second)?];
+    let inputs = first.inputs();
+    // Where the first circuit's inputs and outputs are among the second's ports
+    let position = |names: &[String], name: &String| names.iter().position(|other| other == name).unwrap();
+    let input_order: Vec<usize> = second.inputs().iter().map(|name| position(inputs, name)).collect();
+    let output_order: Vec<usize> = first.outputs().iter().map(|name| position(second.outputs(), name)).collect();
+
+    let count = row_count(inputs.len(), 2).map_err(|e| e.to_string())? as u64;
+    for first_row in (0..count).step_by(64) {
+        let words = row_words(inputs.len(), first_row).map_err(|e| e.to_string())?;
+        let expected = circuits[0].evaluate(&words);
+        let actual = circuits[1].evaluate(&input_order.iter().map(|&i| words[i]).collect::<Vec<u64>>());
+        let mut differences = expected.iter().zip(&output_order).fold(0, |all, (word, &i)| all | (word ^ actual[i]));
+        if count - first_row < 64 {
+            differences &= (1 << (count - first_row)) - 1;
+        }
+        if differences != 0 {
+            let row = first_row + differences.trailing_zeros() as u64;
+            let levels = (0..inputs.len())
+                .map(|i| if (row >> (inputs.len() - 1 - i)) & 1 == 1 { Level::High } else { Level::Low })
+                .collect();
+            return Ok(Some(levels));
+        }
+    }
+    Ok(None)
+}
+
+// Input levels, in the order of the first circuit's inputs, for which some output differs
+fn find_difference(first: &Circuit, second: &Circuit) -> Result<Option<Vec<Level>>, String> {
+    let mut encoder = Encoder::new();
+    for name in first.inputs() {
+        let literal = encoder.solver.new_variable();
+        encoder.inputs.insert(name.clone(), literal);
+    }
+    let first_outputs = encoder.encode(first)?;
+    let second_outputs = encoder.encode(second)?;
+
+    // The miter: at least one pair of outputs with the same name differs
+    let mut differences = Vec::new();
+    for (name, &output) in &first_outputs {
+        let other = second_outputs[name];
+        if output != other {
+            differences.push(encoder.gate(GateKind::Xor, vec![output, other]));
+        }
+    }
+    encoder.solver.add_clause(&differences);
+
+    let Some(model) = encoder.solver.solve() else {
+        return Ok(None);
+    };
+    let levels = first
+        .inputs()
+        .iter()
+        .map(|name| if model[encoder.inputs[name].variable()] { Level::High } else { Level::Low })
+        .collect();
+    Ok(Some(levels))
+}
+
+// Inputs, in port order, for which a net stuck at 0 or 1 changes some output, or None if the
+// fault cannot be detected. This is the miter of the circuit and its faulty copy, which share
+// every gate that does not depend on the faulty net.
+pub(crate) fn detecting_inputs(circuit: &Circuit, net: usize, stuck_at: bool) -> Result<Option<Vec<Level>>, String> {
+    let mut encoder = Encoder::new();
+    for name in circuit.inputs() {
+        let literal = encoder.solver.new_variable();
+        encoder.inputs.insert(name.clone(), literal);
+    }
+    let good = encoder.encode(circuit)?;
+    let faulty = encoder.encode_with_fault(circuit, Some((net, stuck_at)))?;
+
+    let mut differences = Vec::new();
+    for (name, &output) in &good {
+        if output != faulty[name] {
+            differences.push(encoder.gate(GateKind::Xor, vec![output, faulty[name]]));
+        }
+    }
+    encoder.solver.add_clause(&differences);
+
+    let Some(model) = encoder.solver.solve() else {
+        return Ok(None);
+    };
+    let levels = circuit
+        .inputs()
+        .iter()
+        .map(|name| if model[encoder.inputs[name].variable()] { Level::High } else { Level::Low })
+        .collect();
+    Ok(Some(levels))
+}
+
+// Input levels, in the order of the first circuit's inputs, for which some output differs
+fn bdd_difference(first: &Circuit, second: &Circuit) -> Result<Option<Vec<Level>>, String> {
+    let variables = variable_order(first, &VariableOrder::DepthFirst)?;
+    let mut bdd = Bdd::new(variables.len());
+    let first_outputs = bdd.add_circuit(first, &variables)?;
+    let second_outputs = bdd.add_circuit(second, &variables)?;
+    for (name, &output) in first.outputs().iter().zip(&first_outputs) {
+        let position = second.outputs().iter().position(|other| other == name).unwrap();
+        let difference = bdd.xor(output, second_outputs[position]);
+        if let Some(values) = bdd.satisfying_assignment(difference) {
+            let levels = first
+                .inputs()
+                .iter()
+                .map(|input| values[variables.iter().position(|variable| variable == input).unwrap()])
+                .map(|high| if high { Level::High } else { Level::Low })
+                .collect();
+            return Ok(Some(levels));
+        }
+    }
+    Ok(None)
+}
+
+// Clauses for the gates of both circuits. Gates of the same kind on the same literals
+// share one variable, so the parts the circuits have in common are only encoded once.
+struct Encoder {
+    solver: Solver,
+    inputs: HashMap<String, Literal>,
+    gates: HashMap<(GateKind, Vec<Literal>), Literal>,
+    high: Literal,
+}
+
+impl Encoder {
+    fn new() -> Encoder {
+        let mut solver = Solver::new();
+        let high = solver.new_variable();
+        solver.add_clause(&[high]);
+        Encoder { solver, inputs: HashMap::new(), gates: HashMap::new(), high }
+    }
+
+    // Literals of the circuit's outputs by name
+    fn encode(&mut self, circuit: &Circuit) -> Result<HashMap<String, Literal>, String> {
+        self.encode_with_fault(circuit, None)
+    }
+
+    // Same with a net stuck at 0 or 1
+    fn encode_with_fault(&mut self, circuit: &Circuit, fault: Option<(usize, bool)>) -> Result<HashMap<String, Literal>, String> {
+        let network = GateNetwork::new(circuit)?;
+        let true_literal = self.high;
+        let constant = move |high: bool| if high { true_literal } else { !true_literal };
+        let mut literals: Vec<Option<Literal>> =
+            network.constants.iter().map(|constant_net| constant_net.map(constant)).collect();
+        for (name, &net) in circuit.inputs().iter().zip(&network.inputs) {
+            literals[net] = Some(self.inputs[name]);
+        }
+        if let Some((net, high)) = fault {
+            literals[net] = Some(constant(high));
+        }
+        for gate in &network.gates {
+            let inputs = gate.inputs.iter().map(|&net| literals[net].unwrap()).collect();
+            literals[gate.output] = match fault {
+                Some((net, high)) if net == gate.output => Some(constant(high)),
+                _ => Some(self.gate(gate.kind, inputs)),
+            };
+        }
+        Ok(circuit.outputs().iter().cloned().zip(network.outputs.iter().map(|&net| literals[net].unwrap())).collect())
+    }
+
+    // Literal for the output of a gate. Inverting gates are the negation of their
+    // non-inverting counterpart, so only AND, OR and XOR need clauses.
+    fn gate(&mut self, kind: GateKind, mut inputs: Vec<Literal>) -> Literal {
+        match kind {
+            GateKind::Buffer | GateKind::TriState => inputs[0],
+            GateKind::Not => !inputs[0],
+            GateKind::Nand => !self.gate(GateKind::And, inputs),
+            GateKind::Nor => !self.gate(GateKind::Or, inputs),
+            GateKind::Xnor => !self.gate(GateKind::Xor, inputs),
+            // The netlist allows a single input, which XOR passes on unchanged
+            GateKind::Xor if inputs.len() < 2 => inputs.first().copied().unwrap_or(!self.high),
+            GateKind::Xor if inputs.len() > 2 => {
+                let last = inputs.pop().unwrap();
+                let rest = self.gate(GateKind::Xor, inputs);
+                self.gate(GateKind::Xor, vec![rest, last])
+            }
+            GateKind::And | GateKind::Or | GateKind::Xor => {
+                inputs.sort();
+                if let Some(&output) = self.gates.get(&(kind, inputs.clone())) {
+                    return output;
+                }
+                let output = self.solver.new_variable();
+                match kind {
+                    GateKind::And => self.and(output, &inputs),
+                    // a | b is !(!a & !b)
+                    GateKind::Or => self.and(!output, &inputs.iter().map(|&input| !input).collect::<Vec<_>>()),
+                    _ => {
+                        let (a, b) = (inputs[0], inputs[1]);
+                        self.solver.add_clause(&[!output, a, b]);
+                        self.solver.add_clause(&[!output, !a, !b]);
+                        self.solver.add_clause(&[output, !a, b]);
+                        self.solver.add_clause(&[output, a, !b]);
+                    }
+                }
+                self.gates.insert((kind, inputs), output);
+                output
+            }
+        }
+    }
+
+    // Clauses for output = inputs[0] & inputs[1] & ...
+    fn and(&mut self, output: Literal, inputs: &[Literal]) {
+        for &input in inputs {
+            self.solver.add_clause(&[!output, input]);
+        }
+        let mut clause: Vec<Literal> = inputs.iter().map(|&input| !input).collect();
+        clause.push(output);
+        self.solver.add_clause(&clause);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::digital_logic::arithmetic::RippleCarryAdder;
+    use crate::expression::{compile, compile_outputs, parse, Expr};
+    use crate::netlist::{load, load_builtin};
+
+    // Adder computing the carries as majority functions instead of through half adders.
+    // With `bug`, the carry out of that bit ignores the carry in.
+    fn majority_adder(bits: usize, bug: Option<usize>) -> Circuit {
+        let mut inputs: Vec<String> = (0..bits).map(|i| format!("a{}", i)).collect();
+        inputs.extend((0..bits).map(|i| format!("b{}", i)));
+        let mut outputs = Vec::new();
+        let mut carry: Option<Expr> = None;
+        for i in 0..bits {
+            let (a, b) = (Expr::var(&format!("a{}", i)), Expr::var(&format!("b{}", i)));
+            let sum = match &carry {
+                Some(carry) => Expr::xor(Expr::xor(a.clone(), b.clone()), carry.clone()),
+                None => Expr::xor(a.clone(), b.clone()),
+            };
+            outputs.push((format!("sum{}", i), sum));
+            carry = Some(match carry {
+                Some(carry) if bug != Some(i) => Expr::or(
+                    Expr::or(Expr::and(a.clone(), b.clone()), Expr::and(a, carry.clone())),
+                    Expr::and(b, carry),
+                ),
+                _ => Expr::and(a, b),
+            });
+        }
+        outputs.push((format!("carry{}", bits - 1), carry.unwrap()));
+        compile_outputs("majority_adder", &inputs, &outputs).unwrap()
+    }
+
+    #[test]
+    fn test_full_adder_from_half_adders() {
+        let netlist = load(include_str!("../../netlists/full_adder.net"), Some("adder")).unwrap();
+        let builtin = load_builtin("full_adder").unwrap();
+        assert_eq!(check_equivalence(netlist, builtin), Ok(None));
+
+        let netlist = load(include_str!("../../netlists/full_adder.net"), Some("adder")).unwrap();
+        let builtin = load_builtin("full_adder").unwrap();
+        assert_eq!(check_equivalence_with(netlist, builtin, Method::Sat), Ok(None));
+    }
+
+    #[test]
+    fn test_counterexample() {
+        let and = compile(&parse("a & b").unwrap());
+        let or = compile(&parse("a | b").unwrap());
+        let counterexample = check_equivalence(and, or).unwrap().unwrap();
+        assert_eq!(counterexample.inputs, vec![("a".to_string(), Level::Low), ("b".to_string(), Level::High)]);
+        assert_eq!(counterexample.outputs, vec![("y".to_string(), Level::Low, Level::High)]);
+        assert_eq!(counterexample.to_string(), "a=0 b=1: y is 0 and 1");
+
+        for method in [Method::Exhaustive, Method::Sat, Method::Bdd] {
+            let first = compile(&parse("a & (b | c)").unwrap());
+            let second = compile(&parse("a & b | a & c").unwrap());
+            assert_eq!(check_equivalence_with(first, second, method), Ok(None));
+            let first = compile(&parse("a & (b | c)").unwrap());
+            let second = compile(&parse("a & b | c").unwrap());
+            assert!(check_equivalence_with(first, second, method).unwrap().is_some());
+        }
+    }
+
+    #[test]
+    fn test_ports_must_match() {
+        let first = compile(&parse("a & b").unwrap());
+        let second = compile(&parse("a & c").unwrap());
+        assert_eq!(check_equivalence(first, second), Err("Ports differ: a b | y and a c | y".to_string()));
+    }
+
+    #[test]
+    fn test_large_adders_with_sat() {
+        // 32 inputs, too many to simulate every combination
+        let ripple = RippleCarryAdder::new(16).into_circuit();
+        assert_eq!(check_equivalence(ripple, majority_adder(16, None)), Ok(None));
+
+        let ripple = RippleCarryAdder::new(16).into_circuit();
+        assert_eq!(check_equivalence_with(ripple, majority_adder(16, None), Method::Bdd), Ok(None));
+
+        let ripple = RippleCarryAdder::new(16).into_circuit();
+        assert_eq!(
+            check_equivalence_with(ripple, majority_adder(16, None), Method::Exhaustive),
+            Err("32 inputs are too many to simulate exhaustively, the limit is 12".to_string())
+        );
+
+        for method in [Method::Sat, Method::Bdd] {
+            let ripple = RippleCarryAdder::new(16).into_circuit();
+            let counterexample = check_equivalence_with(ripple, majority_adder(16, Some(9)), method).unwrap().unwrap();
+            // The carry out of bit 9 is lost, so a carry comes into bit 9 and exactly one of a9 and b9 is set
+            let level = |name: &str| counterexample.inputs.iter().find(|(input, _)| input == name).unwrap().1;
+            assert_ne!(level("a9"), level("b9"));
+            assert!(!counterexample.outputs.is_empty());
+        }
+    }
+
+    #[test]
+    fn test_parallel_sweep_limit() {
+        // 128 inputs are refused instead of overflowing the row count
+        let ripple = RippleCarryAdder::new(64).into_circuit();
+        let error = parallel_difference(&ripple, &RippleCarryAdder::new(64).into_circuit()).unwrap_err();
+        assert_eq!(error, "128 inputs are too many to enumerate, the limit is 20");
+    }
+
+    #[test]
+    fn test_gate_reading_a_net_twice() {
+        let first = load("circuit c(a) -> (y) { and g(a, a) -> (y); }", None).unwrap();
+        let second = load("circuit c(a) -> (y) { wire t; not n(a) -> (t); not m(t) -> (y); }", None).unwrap();
+        assert_eq!(check_equivalence_with(first, second, Method::Sat), Ok(None));
+    }
+
+    #[test]
+    fn test_single_input_xor() {
+        // XOR of one input is a buffer and XNOR of one input an inverter
+        for (gate, equivalent) in [("xor", "buf"), ("xnor", "not")] {
+            let single = || load(&format!("circuit c(a) -> (y) {{ {} g(a) -> (y); }}", gate), None).unwrap();
+            let other = || load(&format!("circuit c(a) -> (y) {{ {} g(a) -> (y); }}", equivalent), None).unwrap();
+            for method in [Method::Exhaustive, Method::Sat, Method::Bdd] {
+                assert_eq!(check_equivalence_with(single(), other(), method), Ok(None), "{} {:?}", gate, method);
+            }
+        }
+    }
+
+    #[test]
+    fn test_sequential_circuits_are_rejected() {
+        let first = load_builtin("dlatch").unwrap();
+        let second = load_builtin("dlatch").unwrap();
+        assert!(check_equivalence_with(first, second, Method::Sat).is_err());
+    }
+}
@@ -0,0 +1,309 @@
+use core::fmt;
+
+use crate::analysis::equivalence::detecting_inputs;
+use crate::analysis::{ParallelCircuit, RandomVectors};
+use crate::components::Level;
+use crate::simulation::Circuit;
+
+// Batches of 64 random vectors tried before faults are targeted one at a time
+const RANDOM_BATCHES: usize = 16;
+
+// A net that is stuck at 0 or 1 whatever drives it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Fault {
+    pub net: String,
+    pub stuck_at: bool,
+}
+
+impl fmt::Display for Fault {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} stuck-at-{}", self.net, self.stuck_at as u8)
+    }
+}
+
+// Which faults a set of input vectors detects, i.e. makes some output differ from the
+// fault-free circuit
+#[derive(Debug, Clone, PartialEq)]
+pub struct FaultReport {
+    pub faults: Vec<Fault>,
+    // Index of the first vector detecting each fault
+    pub detected_by: Vec<Option<usize>>,
+    pub vectors: usize,
+}
+
+impl FaultReport {
+    pub fn detected(&self) -> usize {
+        self.detected_by.iter().filter(|vector| vector.is_some()).count()
+    }
+
+    pub fn undetected(&self) -> Vec<&Fault> {
+        self.faults
+            .iter()
+            .zip(&self.detected_by)
+            .filter(|(_, vector)| vector.is_none())
+            .map(|(fault, _)| fault)
+            .collect()
+    }
+
+    // Share of detected faults, between 0 and 1
+    pub fn coverage(&self) -> f64 {
+        if self.faults.is_empty() {
+            return 1.0;
+        }
+        self.detected() as f64 / self.faults.len() as f64
+    }
+}
+
+impl fmt::Display for FaultReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} of {} faults detected by {} vectors ({:.1}% coverage)",
+            self.detected(),
+            self.faults.len(),
+            self.vectors,
+            self.coverage() * 100.0
+        )?;
+        let undetected: Vec<String> = self.undetected().iter().map(|fault| fault.to_string()).collect();
+        if !undetected.is_empty() {
+            write!(f, "\nUndetected: {}", undetected.join(", "))?;
+        }
+        Ok(())
+    }
+}
+
+// Vectors found by generate_tests and the faults they detect
+#[derive(Debug, Clone, PartialEq)]
+pub struct TestSet {
+    pub vectors: Vec<Vec<Level>>,
+    pub report: FaultReport,
+    // Faults no input vector can detect, found while targeting them
+    pub redundant: Vec<Fault>,
+}
+
+// Every net stuck at 0 and at 1, in the order of the circuit's nets
+pub fn fault_list(circuit: &Circuit) -> Vec<Fault> {
+    circuit
+        .net_names()
+        .iter()
+        .flat_map(|net| [false, true].map(|stuck_at| Fault { net: net.clone(), stuck_at }))
+        .collect()
+}
+
+// Simulate every stuck-at fault of a combinational gate circuit with the given input vectors,
+// in input port order and made of 0 and 1. Vectors are simulated 64 at a time.
+pub fn simulate_faults(circuit: &Circuit, vectors: &[Vec<Level>]) -> Result<FaultReport, String> {
+    let inputs = circuit.inputs().len();
+    for (i, vector) in vectors.iter().enumerate() {
+        if vector.len() != inputs {
+            return Err(format!("Vector {} has {} levels, expected {}", i + 1, vector.len(), inputs));
+        }
+        if vector.iter().any(|&level| level != Level::High && level != Level::Low) {
+            return Err(format!("Vector {} is not made of 0 and 1", i + 1));
+        }
+    }
+
+    let mut simulator = FaultSimulator::new(circuit)?;
+    let mut detected_by = vec![None; simulator.faults.len()];
+    for (block, chunk) in vectors.chunks(64).enumerate() {
+        let (words, good) = simulator.good(chunk);
+        for (fault, detected) in detected_by.iter_mut().enumerate().filter(|(_, detected)| detected.is_none()) {
+            let patterns = simulator.detecting(&words, &good, fault) & mask(chunk.len());
+            if patterns != 0 {
+                *detected = Some(block * 64 + patterns.trailing_zeros() as usize);
+            }
+        }
+    }
+    Ok(FaultReport { faults: simulator.faults, detected_by, vectors: vectors.len() })
+}
+
+// Find input vectors detecting at least `target` (between 0 and 1) of the stuck-at faults.
+// Random vectors come first, keeping only those that detect a fault no earlier vector did.
+// Faults still undetected are then targeted one at a time with a SAT solver, which either
+// finds a vector or proves that the fault cannot be detected.
+pub fn generate_tests(circuit: &Circuit, target: f64) -> Result<TestSet, String> {
+    let mut simulator = FaultSimulator::new(circuit)?;
+    let total = simulator.faults.len();
+    let mut remaining: Vec<usize> = (0..total).collect();
+    let mut redundant = Vec::new();
+    let reached = |remaining: usize, redundant: usize| {
+        total == 0 || (total - remaining - redundant) as f64 / total as f64 >= target
+    };
+
+    let mut vectors: Vec<Vec<Level>> = Vec::new();
+    let mut random = RandomVectors::new();
+    for _ in 0..RANDOM_BATCHES {
+        if reached(remaining.len(), 0) {
+            break;
+        }
+        let batch = random.vectors(64, circuit.inputs().len());
+        let useful = simulator.drop_detected(&batch, &mut remaining);
+        if useful.is_empty() {
+            break;
+        }
+        vectors.extend(useful.into_iter().map(|vector| batch[vector].clone()));
+    }
+
+    while !remaining.is_empty() && !reached(remaining.len(), redundant.len()) {
+        let fault = remaining[0];
+        let (net, stuck_at) = (simulator.nets[fault], simulator.faults[fault].stuck_at);
+        match detecting_inputs(circuit, net, stuck_at)? {
+            Some(vector) => {
+                simulator.drop_detected(std::slice::from_ref(&vector), &mut remaining);
+                vectors.push(vector);
+            }
+            None => {
+                redundant.push(simulator.faults[fault].clone());
+                remaining.remove(0);
+            }
+        }
+    }
+
+    let report = simulate_faults(circuit, &vectors)?;
+    Ok(TestSet { vectors, report, redundant })
+}
+
+// Bit-parallel fault simulation: the fault-free circuit and every faulty one see the same
+// 64 vectors, and a fault is detected by the vectors whose outputs differ
+struct FaultSimulator {
+    circuit: ParallelCircuit,
+    faults: Vec<Fault>,
+    // Net index of every fault
+    nets: Vec<usize>,
+}
+
+impl FaultSimulator {
+    fn new(circuit: &Circuit) -> Result<FaultSimulator, String> {
+        let faults = fault_list(circuit);
+        let nets = (0..circuit.net_count()).flat_map(|net| [net, net]).collect();
+        Ok(FaultSimulator { circuit: ParallelCircuit::new(circuit)?, faults, nets })
+    }
+
+    // Input words of up to 64 vectors and the fault-free output words
+    fn good(&mut self, vectors: &[Vec<Level>]) -> (Vec<u64>, Vec<u64>) {
+        let words: Vec<u64> = (0..self.circuit.inputs().len())
+            .map(|input| {
+                vectors
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, vector)| vector[input] == Level::High)
+                    .fold(0, |word, (k, _)| word | (1 << k))
+            })
+            .collect();
+        let good = self.circuit.evaluate(&words);
+        (words, good)
+    }
+
+    // Vectors, as bits, for which the fault changes some output
+    fn detecting(&mut self, words: &[u64], good: &[u64], fault: usize) -> u64 {
+        let word = if self.faults[fault].stuck_at { u64::MAX } else { 0 };
+        let faulty = self.circuit.evaluate_with_fault(words, Some((self.nets[fault], word)));
+        good.iter().zip(&faulty).fold(0, |all, (good, faulty)| all | (good ^ faulty))
+    }
+
+    // Remove the faults the vectors (at most 64) detect from `remaining` and return the
+    // vectors that are the first to detect one of them, in order
+    fn drop_detected(&mut self, vectors: &[Vec<Level>], remaining: &mut Vec<usize>) -> Vec<usize> {
+        let (words, good) = self.good(vectors);
+        let mut useful = Vec::new();
+        let mut undetected = Vec::new();
+        for &fault in remaining.iter() {
+            let patterns = self.detecting(&words, &good, fault) & mask(vectors.len());
+            if patterns == 0 {
+                undetected.push(fault);
+            } else {
+                useful.push(patterns.trailing_zeros() as usize);
+            }
+        }
+        *remaining = undetected;
+        useful.sort();
+        useful.dedup();
+        useful
+    }
+}
+
+// Bits of the first `count` of 64 patterns
+fn mask(count: usize) -> u64 {
+    if count >= 64 { u64::MAX } else { (1 << count) - 1 }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::digital_logic::arithmetic::RippleCarryAdder;
+    use crate::netlist::{load, load_builtin};
+
+    fn vector(bits: &str) -> Vec<Level> {
+        bits.chars().map(|c| Level::from_symbol(c).unwrap()).collect()
+    }
+
+    #[test]
+    fn test_single_vector() {
+        let circuit = load_builtin("full_adder").unwrap();
+        let report = simulate_faults(&circuit, &[vector("000")]).unwrap();
+        assert_eq!(report.faults.len(), 2 * circuit.net_count());
+        // With all inputs 0 only faults pulling a net to 1 can show
+        for (fault, detected) in report.faults.iter().zip(&report.detected_by) {
+            if !fault.stuck_at {
+                assert_eq!(*detected, None, "{}", fault);
+            }
+        }
+        let detected = |net: &str, stuck_at: bool| {
+            let position = report.faults.iter().position(|fault| fault.net == net && fault.stuck_at == stuck_at);
+            report.detected_by[position.unwrap()]
+        };
+        assert_eq!(detected("sum", true), Some(0));
+        assert_eq!(detected("cout", true), Some(0));
+    }
+
+    #[test]
+    fn test_exhaustive_full_adder() {
+        let circuit = load_builtin("full_adder").unwrap();
+        let vectors: Vec<Vec<Level>> = (0..8).map(|row| vector(&format!("{:03b}", row))).collect();
+        let report = simulate_faults(&circuit, &vectors).unwrap();
+        assert_eq!(report.coverage(), 1.0);
+        assert!(report.to_string().starts_with("16 of 16 faults detected by 8 vectors (100.0% coverage)"));
+    }
+
+    #[test]
+    fn test_invalid_vectors() {
+        let circuit = load_builtin("full_adder").unwrap();
+        assert_eq!(simulate_faults(&circuit, &[vector("01")]).err(), Some("Vector 1 has 2 levels, expected 3".to_string()));
+        assert!(simulate_faults(&circuit, &[vector("0x1")]).is_err());
+        assert!(simulate_faults(&load_builtin("dff").unwrap(), &[]).is_err());
+    }
+
+    #[test]
+    fn test_generate_tests() {
+        for circuit in [load_builtin("full_adder").unwrap(), RippleCarryAdder::new(16).into_circuit()] {
+            let tests = generate_tests(&circuit, 1.0).unwrap();
+            assert_eq!(tests.report.coverage(), 1.0, "{}", circuit.name());
+            assert!(tests.redundant.is_empty());
+            assert!(tests.vectors.len() < 64, "{} vectors for {}", tests.vectors.len(), circuit.name());
+        }
+
+        // A lower target stops earlier
+        let circuit = RippleCarryAdder::new(16).into_circuit();
+        let tests = generate_tests(&circuit, 0.5).unwrap();
+        assert!(tests.report.coverage() >= 0.5);
+    }
+
+    #[test]
+    fn test_single_input_xor() {
+        let circuit = load("circuit c(a, b) -> (y, z) { xor g1(a) -> (y); xnor g2(b) -> (z); }", None).unwrap();
+        let tests = generate_tests(&circuit, 1.0).unwrap();
+        assert_eq!(tests.report.coverage(), 1.0);
+        assert_eq!(tests.vectors.len(), 2);
+    }
+
+    #[test]
+    fn test_redundant_fault() {
+        // y = a | (a & b) = a, so neither b nor t stuck at 0 changes anything
+        let circuit = load("circuit c(a, b) -> (y) { wire t; and g1(a, b) -> (t); or g2(a, t) -> (y); }", None).unwrap();
+        let tests = generate_tests(&circuit, 1.0).unwrap();
+        let fault = |net: &str, stuck_at: bool| Fault { net: net.to_string(), stuck_at };
+        assert_eq!(tests.redundant, vec![fault("b", false), fault("b", true), fault("t", false)]);
+        assert_eq!(tests.report.undetected(), tests.redundant.iter().collect::<Vec<&Fault>>());
+        assert_eq!(tests.report.detected(), 5);
+    }
+}
@@ -0,0 +1,149 @@
+use crate::components::gates::GateKind;
+use crate::components::nets::net_id;
+use crate::components::{ComponentId, Level, NetId};
+use crate::simulation::Circuit;
+
+// A circuit seen as logic gates without feedback, as needed to analyse it symbolically.
+// Nets are the circuit's net indices.
+pub(crate) struct GateNetwork {
+    // Nets of the input ports, in port order
+    pub inputs: Vec<usize>,
+    // Value of every net nobody drives but that is forced to 0 or 1
+    pub constants: Vec<Option<bool>>,
+    // Gates in topological order, so every gate comes after the gates it reads
+    pub gates: Vec<Gate>,
+    // Nets of the output ports, in port order
+    pub outputs: Vec<usize>,
+}
+
+pub(crate) struct Gate {
+    pub kind: GateKind,
+    pub inputs: Vec<usize>,
+    pub output: usize,
+}
+
+impl GateNetwork {
+    // Fails for components that are not logic gates (tri-state buffers included), nets with
+    // several drivers, feedback loops and for nets that are read but neither driven nor forced
+    pub fn new(circuit: &Circuit) -> Result<GateNetwork, String> {
+        let name = circuit.name();
+        let inputs: Vec<usize> = circuit.inputs().iter().map(|input| circuit.net(input).unwrap().index()).collect();
+        // Nets driven by an input port or a component, and those driven by a component
+        let mut driven = vec![false; circuit.net_count()];
+        let mut produced = vec![false; circuit.net_count()];
+        for &net in &inputs {
+            driven[net] = true;
+        }
+        for component in circuit.component_ids() {
+            for &net in circuit.component_outputs(component) {
+                if driven[net.index()] {
+                    return Err(format!("Net '{}' of {} has several drivers", circuit.net_name(net), name));
+                }
+                driven[net.index()] = true;
+                produced[net.index()] = true;
+            }
+        }
+        let constants: Vec<Option<bool>> = circuit
+            .net_ids()
+            .map(|net| match circuit.net_at(net).level() {
+                Level::High if !driven[net.index()] => Some(true),
+                Level::Low if !driven[net.index()] => Some(false),
+                _ => None,
+            })
+            .collect();
+        let has_value = |net: usize| driven[net] || constants[net].is_some();
+
+        // Kahn's algorithm, starting with the gates fed by inputs and constants only
+        let mut waiting: Vec<usize> = circuit
+            .component_ids()
+            .map(|component| {
+                let mut nets: Vec<NetId> = circuit
+                    .component_inputs(component)
+                    .iter()
+                    .copied()
+                    .filter(|&net| produced[net.index()])
+                    .collect();
+                // The fanout lists every reader once, however many of its inputs a net feeds
+                nets.sort();
+                nets.dedup();
+                nets.len()
+            })
+            .collect();
+        let mut ready: Vec<ComponentId> =
+            circuit.component_ids().filter(|&component| waiting[component.index()] == 0).collect();
+        let mut gates = Vec::new();
+        while let Some(component) = ready.pop() {
+            let outputs = circuit.component_outputs(component);
+            let kind = circuit.component(component).gate_kind().filter(|&kind| kind != GateKind::TriState);
+            let (Some(kind), [output]) = (kind, outputs) else {
+                let outputs: Vec<&str> = outputs.iter().map(|&net| circuit.net_name(net)).collect();
+                return Err(format!("{} drives {} with something other than a logic gate", name, outputs.join(", ")));
+            };
+            let inputs: Vec<usize> = circuit.component_inputs(component).iter().map(|net| net.index()).collect();
+            if let Some(&net) = inputs.iter().find(|&&net| !has_value(net)) {
+                return Err(format!("Net '{}' of {} is not driven", circuit.net_name(net_id(net)), name));
+            }
+
+            for &reader in circuit.fanout(*output) {
+                waiting[reader.index()] -= 1;
+                if waiting[reader.index()] == 0 {
+                    ready.push(reader);
+                }
+            }
+            gates.push(Gate { kind, inputs, output: output.index() });
+        }
+        if gates.len() < circuit.component_count() {
+            return Err(format!("{} contains a feedback loop", name));
+        }
+
+        let outputs: Vec<usize> = circuit.outputs().iter().map(|output| circuit.net(output).unwrap().index()).collect();
+        if let Some(&net) = outputs.iter().find(|&&net| !has_value(net)) {
+            return Err(format!("Net '{}' of {} is not driven", circuit.net_name(net_id(net)), name));
+        }
+        Ok(GateNetwork { inputs, constants, gates, outputs })
+    }
+}
+
+// Gates on the longest path from an input or constant to an output
+pub fn logic_depth(circuit: &Circuit) -> Result<usize, String> {
+    let network = GateNetwork::new(circuit)?;
+    let mut depth = vec![0; circuit.net_count()];
+    for gate in &network.gates {
+        depth[gate.output] = gate.inputs.iter().map(|&net| depth[net]).max().unwrap_or(0) + 1;
+    }
+    Ok(network.outputs.iter().map(|&net| depth[net]).max().unwrap_or(0))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::expression::{compile, parse};
+    use crate::netlist::{load, load_builtin};
+
+    #[test]
+    fn test_topological_order() {
+        let circuit = load_builtin("full_adder").unwrap();
+        let network = GateNetwork::new(&circuit).unwrap();
+        assert_eq!(network.gates.len(), 5);
+        let mut known: Vec<usize> = network.inputs.clone();
+        for gate in &network.gates {
+            assert!(gate.inputs.iter().all(|net| known.contains(net)));
+            known.push(gate.output);
+        }
+    }
+
+    #[test]
+    fn test_rejected_circuits() {
+        let latch = load_builtin("srlatch").unwrap();
+        assert!(GateNetwork::new(&latch).is_err());
+        let floating = load("circuit c(a) -> (y) { wire w; and g(a, w) -> (y); }", None).unwrap();
+        assert_eq!(GateNetwork::new(&floating).err(), Some("Net 'w' of c is not driven".to_string()));
+    }
+
+    #[test]
+    fn test_logic_depth() {
+        assert_eq!(logic_depth(&load_builtin("full_adder").unwrap()), Ok(3));
+        assert_eq!(logic_depth(&compile(&parse("!(a & b) | c").unwrap())), Ok(2));
+        assert_eq!(logic_depth(&compile(&parse("a & 0").unwrap())), Ok(0));
+    }
+}
@@ -0,0 +1,290 @@
+use std::cmp::Reverse;
+use std::collections::{HashMap, HashSet};
+
+use crate::analysis::TruthTable;
+use crate::components::Level;
+use crate::expression::Expr;
+
+// Upper bound on the cover search, after which the best cover found so far is used,
+// or a greedy one if none was complete yet
+const MAX_SEARCH_STEPS: usize = 100_000;
+
+// A product term: the bits set in `mask` are the variables it does not depend on.
+// Bit `n - 1 - i` belongs to variable `i`, so the first variable is the most significant.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Implicant {
+    pub value: u32,
+    pub mask: u32,
+}
+
+impl Implicant {
+    pub fn covers(&self, minterm: u32) -> bool {
+        minterm & !self.mask == self.value
+    }
+
+    // Number of variables in the product
+    pub fn literals(&self, variables: usize) -> usize {
+        variables - (self.mask & ((1 << variables) - 1)).count_ones() as usize
+    }
+
+    pub fn to_expr(&self, variables: &[String]) -> Expr {
+        let n = variables.len();
+        let literals: Vec<Expr> = variables
+            .iter()
+            .enumerate()
+            .filter(|&(i, _)| self.mask >> (n - 1 - i) & 1 == 0)
+            .map(|(i, name)| {
+                if self.value >> (n - 1 - i) & 1 == 1 {
+                    Expr::var(name)
+                } else {
+                    !Expr::var(name)
+                }
+            })
+            .collect();
+        literals.into_iter().reduce(Expr::and).unwrap_or(Expr::Const(true))
+    }
+}
+
+// All prime implicants of the function, by repeatedly merging terms that differ in one variable
+pub fn prime_implicants(minterms: &[u32], dont_cares: &[u32]) -> Vec<Implicant> {
+    let mut current: HashSet<Implicant> = minterms
+        .iter()
+        .chain(dont_cares)
+        .map(|&value| Implicant { value, mask: 0 })
+        .collect();
+    let mut primes = Vec::new();
+
+    while !current.is_empty() {
+        // Only terms with the same free variables can be merged
+        let mut groups: HashMap<u32, Vec<Implicant>> = HashMap::new();
+        for implicant in &current {
+            groups.entry(implicant.mask).or_default().push(*implicant);
+        }
+        let mut next = HashSet::new();
+        let mut merged = HashSet::new();
+        for group in groups.values() {
+            for (i, a) in group.iter().enumerate() {
+                for b in &group[i + 1..] {
+                    let difference = a.value ^ b.value;
+                    if difference.count_ones() == 1 {
+                        next.insert(Implicant { value: a.value & !difference, mask: a.mask | difference });
+                        merged.insert(*a);
+                        merged.insert(*b);
+                    }
+                }
+            }
+        }
+        primes.extend(current.into_iter().filter(|implicant| !merged.contains(implicant)));
+        current = next;
+    }
+
+    // Terms made of don't-cares only are not needed
+    primes.retain(|prime| minterms.iter().any(|&minterm| prime.covers(minterm)));
+    primes.sort();
+    primes
+}
+
+// Minimal sum of products over the given variables, the first being the most significant bit.
+// Picks the essential prime implicants, then searches for the cover with the fewest terms
+// and literals among the rest (Petrick's method as a branch and bound search).
+pub fn minimize(minterms: &[u32], dont_cares: &[u32], variables: &[String]) -> Expr {
+    cover(minterms, dont_cares)
+        .iter()
+        .map(|implicant| implicant.to_expr(variables))
+        .reduce(Expr::or)
+        .unwrap_or(Expr::Const(false))
+}
+
+// Prime implicants covering all minterms
+pub fn cover(minterms: &[u32], dont_cares: &[u32]) -> Vec<Implicant> {
+    cover_within(minterms, dont_cares, MAX_SEARCH_STEPS)
+}
+
+fn cover_within(minterms: &[u32], dont_cares: &[u32], max_steps: usize) -> Vec<Implicant> {
+    let primes = prime_implicants(minterms, dont_cares);
+    let mut chosen: Vec<usize> = Vec::new();
+    for &minterm in minterms {
+        let covering: Vec<usize> = (0..primes.len()).filter(|&p| primes[p].covers(minterm)).collect();
+        if covering.len() == 1 && !chosen.contains(&covering[0]) {
+            chosen.push(covering[0]);
+        }
+    }
+    let remaining: Vec<u32> = minterms
+        .iter()
+        .copied()
+        .filter(|&minterm| !chosen.iter().any(|&p| primes[p].covers(minterm)))
+        .collect();
+
+    let mut search = Search { primes: &primes, best: None, steps: 0, max_steps };
+    search.run(&remaining, &mut Vec::new());
+    chosen.extend(search.best.unwrap_or_else(|| greedy_cover(&primes, &remaining)));
+    chosen.sort();
+    chosen.into_iter().map(|p| primes[p]).collect()
+}
+
+// Repeatedly the prime covering the most minterms left, preferring fewer literals
+fn greedy_cover(primes: &[Implicant], minterms: &[u32]) -> Vec<usize> {
+    let mut remaining = minterms.to_vec();
+    let mut selection = Vec::new();
+    while !remaining.is_empty() {
+        let covered = |p: usize| remaining.iter().filter(|&&minterm| primes[p].covers(minterm)).count();
+        let best = (0..primes.len()).max_by_key(|&p| (covered(p), primes[p].mask.count_ones())).unwrap();
+        remaining.retain(|&minterm| !primes[best].covers(minterm));
+        selection.push(best);
+    }
+    selection
+}
+
+struct Search<'a> {
+    primes: &'a [Implicant],
+    best: Option<Vec<usize>>,
+    steps: usize,
+    max_steps: usize,
+}
+
+impl Search<'_> {
+    // Fewer terms first, then more free variables, i.e. fewer literals
+    fn cost(&self, selection: &[usize]) -> (usize, Reverse<u32>) {
+        let free = selection.iter().map(|&p| self.primes[p].mask.count_ones()).sum();
+        (selection.len(), Reverse(free))
+    }
+
+    fn candidates(&self, minterm: u32) -> Vec<usize> {
+        (0..self.primes.len()).filter(|&p| self.primes[p].covers(minterm)).collect()
+    }
+
+    fn run(&mut self, remaining: &[u32], selection: &mut Vec<usize>) {
+        self.steps += 1;
+        if remaining.is_empty() {
+            if self.best.as_ref().is_none_or(|best| self.cost(selection) < self.cost(best)) {
+                self.best = Some(selection.clone());
+            }
+            return;
+        }
+        let bound = self.best.as_ref().map_or(usize::MAX, |best| best.len());
+        if selection.len() >= bound || self.steps > self.max_steps {
+            return;
+        }
+
+        // Branch on the minterm with the fewest candidates, trying the largest terms first
+        let minterm = *remaining.iter().min_by_key(|&&minterm| self.candidates(minterm).len()).unwrap();
+        let mut options = self.candidates(minterm);
+        options.sort_by_key(|&p| Reverse(remaining.iter().filter(|&&m| self.primes[p].covers(m)).count()));
+
+        for p in options {
+            let rest: Vec<u32> = remaining.iter().copied().filter(|&m| !self.primes[p].covers(m)).collect();
+            selection.push(p);
+            self.run(&rest, selection);
+            selection.pop();
+        }
+    }
+}
+
+// Minimal sum of products for every output of a table. Only rows whose inputs are all 0 or 1
+// are used; outputs that are undefined or floating in those rows are treated as don't-cares.
+pub fn minimize_table(table: &TruthTable) -> Vec<(String, Expr)> {
+    let mut minterms = vec![Vec::new(); table.outputs().len()];
+    let mut dont_cares = vec![Vec::new(); table.outputs().len()];
+    for (inputs, outputs) in table.rows() {
+        let Some(index) = row_index(inputs) else {
+            continue;
+        };
+        for (output, level) in outputs.iter().enumerate() {
+            match level {
+                Level::High => minterms[output].push(index),
+                Level::Low => {}
+                Level::Undefined | Level::HighZ => dont_cares[output].push(index),
+            }
+        }
+    }
+
+    table
+        .outputs()
+        .iter()
+        .enumerate()
+        .map(|(output, name)| (name.clone(), minimize(&minterms[output], &dont_cares[output], table.inputs())))
+        .collect()
+}
+
+// Binary value of the input levels, None if one of them is not 0 or 1
+fn row_index(inputs: &[Level]) -> Option<u32> {
+    inputs.iter().try_fold(0, |index, level| match level {
+        Level::Low => Some(index << 1),
+        Level::High => Some(index << 1 | 1),
+        _ => None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::expression::{compile, compile_outputs, parse};
+    use crate::netlist::load_builtin;
+
+    fn names(variables: &[&str]) -> Vec<String> {
+        variables.iter().map(|name| name.to_string()).collect()
+    }
+
+    #[test]
+    fn test_prime_implicants() {
+        // f(a, b) = a | b
+        let primes = prime_implicants(&[1, 2, 3], &[]);
+        assert_eq!(primes, vec![Implicant { value: 0b01, mask: 0b10 }, Implicant { value: 0b10, mask: 0b01 }]);
+    }
+
+    #[test]
+    fn test_minimize() {
+        let variables = names(&["a", "b", "c"]);
+        assert_eq!(minimize(&[], &[], &variables), Expr::Const(false));
+        assert_eq!(minimize(&[0, 1, 2, 3, 4, 5, 6, 7], &[], &variables), Expr::Const(true));
+        assert_eq!(minimize(&[1, 3], &[5, 7], &variables).to_string(), "c");
+        assert_eq!(minimize(&[3, 5, 6, 7], &[], &variables).to_string(), "b & c | a & c | a & b");
+    }
+
+    #[test]
+    fn test_cyclic_cover() {
+        // Every minterm is covered by two primes, so there are no essential ones
+        let variables = names(&["a", "b", "c"]);
+        let minterms = [0, 1, 2, 5, 6, 7];
+        let expr = minimize(&minterms, &[], &variables);
+        assert_eq!(cover(&minterms, &[]).len(), 3);
+        for row in 0..8u32 {
+            let value = |name: &str| row >> (2 - variables.iter().position(|v| v == name).unwrap()) & 1 == 1;
+            assert_eq!(expr.evaluate(&value), minterms.contains(&row));
+        }
+    }
+
+    #[test]
+    fn test_step_limit() {
+        // Stopped before any cover is complete, the rest is covered greedily
+        let minterms = [0, 1, 2, 5, 6, 7, 8, 13, 15];
+        let covering = cover_within(&minterms, &[4], 0);
+        for minterm in minterms {
+            assert!(covering.iter().any(|implicant| implicant.covers(minterm)), "{} is not covered", minterm);
+        }
+        assert!(covering.len() <= minterms.len());
+        assert_eq!(cover_within(&[0, 1, 2, 5, 6, 7], &[], 0).len(), 3);
+    }
+
+    #[test]
+    fn test_minimize_full_adder_table() {
+        let table = TruthTable::from_circuit(load_builtin("full_adder").unwrap(), true).unwrap();
+        let expressions = minimize_table(&table);
+        assert_eq!(expressions[0].0, "sum");
+        assert_eq!(
+            expressions[0].1.to_string(),
+            "!a & !b & cin | !a & b & !cin | a & !b & !cin | a & b & cin"
+        );
+        assert_eq!(expressions[1].1.to_string(), "b & cin | a & cin | a & b");
+    }
+
+    #[test]
+    fn test_minimized_circuit_is_equivalent() {
+        let expr = parse("a & b | a & !b & c | !a & b & c").unwrap();
+        let before = TruthTable::from_circuit(compile(&expr), false).unwrap();
+        let minimized = minimize_table(&before);
+        assert_eq!(minimized[0].1.to_string(), "b & c | a & c | a & b");
+        let after = TruthTable::from_circuit(compile_outputs("minimized", before.inputs(), &minimized).unwrap(), false).unwrap();
+        assert_eq!(after.compare(&before).unwrap(), vec![]);
+    }
+}
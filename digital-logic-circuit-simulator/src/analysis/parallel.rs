@@ -0,0 +1,190 @@
+use crate::analysis::gate_network::GateNetwork;
+use crate::analysis::truth_table::row_count;
+use crate::components::gates::GateKind;
+use crate::components::nets::net_id;
+use crate::simulation::{Circuit, SimulationError};
+
+// Two-valued simulation of 64 input patterns at once. Every net holds a u64 whose bit k is
+// its value in pattern k, so a gate is a single bitwise operation for all patterns. Gates
+// are evaluated once each in topological order, which needs a circuit of logic gates without
+// feedback, where every net read is driven or a constant (see GateNetwork). Such a circuit
+// never has an undefined output for inputs of 0 and 1, so the results match the simulator.
+pub struct ParallelCircuit {
+    inputs: Vec<String>,
+    outputs: Vec<String>,
+    network: GateNetwork,
+    values: Vec<u64>,
+}
+
+impl ParallelCircuit {
+    pub fn new(circuit: &Circuit) -> Result<ParallelCircuit, String> {
+        let network = GateNetwork::new(circuit)?;
+        if let Some(gate) = network.gates.iter().find(|gate| gate.inputs.is_empty()) {
+            return Err(format!("Net '{}' of {} is driven by a gate without inputs", circuit.net_name(net_id(gate.output)), circuit.name()));
+        }
+        let values = network
+            .constants
+            .iter()
+            .map(|constant| if *constant == Some(true) { u64::MAX } else { 0 })
+            .collect();
+        Ok(ParallelCircuit {
+            inputs: circuit.inputs().to_vec(),
+            outputs: circuit.outputs().to_vec(),
+            network,
+            values,
+        })
+    }
+
+    pub fn inputs(&self) -> &[String] {
+        &self.inputs
+    }
+
+    pub fn outputs(&self) -> &[String] {
+        &self.outputs
+    }
+
+    // Evaluate 64 patterns given as one word per input port, returning one word per output port
+    pub fn evaluate(&mut self, inputs: &[u64]) -> Vec<u64> {
+        self.evaluate_with_fault(inputs, None)
+    }
+
+    // Evaluate with a net forced to a word, e.g. all zeros for a stuck-at-0 fault
+    pub(crate) fn evaluate_with_fault(&mut self, inputs: &[u64], fault: Option<(usize, u64)>) -> Vec<u64> {
+        assert_eq!(inputs.len(), self.network.inputs.len(), "Expected one word per input");
+        for (&net, &word) in self.network.inputs.iter().zip(inputs) {
+            self.values[net] = word;
+        }
+        let constant = fault.and_then(|(net, _)| self.network.constants[net].map(|high| (net, high)));
+        if let Some((net, word)) = fault {
+            self.values[net] = word;
+        }
+        for gate in &self.network.gates {
+            let mut operands = gate.inputs.iter().map(|&net| self.values[net]);
+            let first = operands.next().unwrap();
+            self.values[gate.output] = match gate.kind {
+                GateKind::And => operands.fold(first, |a, b| a & b),
+                GateKind::Or => operands.fold(first, |a, b| a | b),
+                GateKind::Xor => operands.fold(first, |a, b| a ^ b),
+                GateKind::Nand => !operands.fold(first, |a, b| a & b),
+                GateKind::Nor => !operands.fold(first, |a, b| a | b),
+                GateKind::Xnor => !operands.fold(first, |a, b| a ^ b),
+                GateKind::Not => !first,
+                GateKind::Buffer => first,
+                GateKind::TriState => unreachable!("GateNetwork rejects tri-state buffers"),
+            };
+            if let Some((net, word)) = fault.filter(|&(net, _)| net == gate.output) {
+                self.values[net] = word;
+            }
+        }
+        let outputs = self.network.outputs.iter().map(|&net| self.values[net]).collect();
+        // A faulty constant is restored for the next evaluation
+        if let Some((net, high)) = constant {
+            self.values[net] = if high { u64::MAX } else { 0 };
+        }
+        outputs
+    }
+
+    // Evaluate the 64 truth table rows starting at `first_row`, which is a multiple of 64.
+    // Bit k of every output word belongs to row first_row + k.
+    pub fn evaluate_rows(&mut self, first_row: u64) -> Result<Vec<u64>, SimulationError> {
+        let words = row_words(self.inputs.len(), first_row)?;
+        Ok(self.evaluate(&words))
+    }
+}
+
+// Input words for the 64 truth table rows starting at `first_row`, the first of `inputs`
+// inputs being the most significant bit of the row number as in truth tables.
+// There can be at most MAX_TRUTH_TABLE_INPUTS inputs, as for truth tables.
+pub fn row_words(inputs: usize, first_row: u64) -> Result<Vec<u64>, SimulationError> {
+    row_count(inputs, 2)?;
+    // Bit k of PATTERNS[b] is bit b of k
+    const PATTERNS: [u64; 6] = [
+        0xaaaa_aaaa_aaaa_aaaa,
+        0xcccc_cccc_cccc_cccc,
+        0xf0f0_f0f0_f0f0_f0f0,
+        0xff00_ff00_ff00_ff00,
+        0xffff_0000_ffff_0000,
+        0xffff_ffff_0000_0000,
+    ];
+    let words = (0..inputs)
+        .map(|i| {
+            let bit = inputs - 1 - i;
+            match PATTERNS.get(bit) {
+                Some(&pattern) => pattern,
+                None if (first_row >> bit) & 1 == 1 => u64::MAX,
+                None => 0,
+            }
+        })
+        .collect();
+    Ok(words)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analysis::{RandomVectors, TruthTable};
+    use crate::components::Level;
+    use crate::digital_logic::arithmetic::RippleCarryAdder;
+    use crate::netlist::{load, load_builtin};
+    use crate::simulation::Simulator;
+
+    #[test]
+    fn test_truth_table_rows() {
+        let mut adder = ParallelCircuit::new(&load_builtin("full_adder").unwrap()).unwrap();
+        let words = adder.evaluate_rows(0).unwrap();
+        let mut simulator = Simulator::new(load_builtin("full_adder").unwrap());
+        let table = TruthTable::generate(&mut simulator, false).unwrap();
+        for (row, (_, outputs)) in table.rows().iter().enumerate() {
+            for (word, &level) in words.iter().zip(outputs) {
+                assert_eq!((word >> row) & 1 == 1, level == Level::High, "row {}", row);
+            }
+        }
+    }
+
+    #[test]
+    fn test_row_words() {
+        // Seven inputs: the first one is bit 6 of the row, fixed within a block of 64 rows
+        let words = row_words(7, 64).unwrap();
+        assert_eq!(words[0], u64::MAX);
+        assert_eq!(words[6], 0xaaaa_aaaa_aaaa_aaaa);
+        assert_eq!(row_words(7, 0).unwrap()[0], 0);
+        assert_eq!(row_words(65, 0), Err(SimulationError::TooManyInputs { inputs: 65, limit: 20 }));
+    }
+
+    #[test]
+    fn test_random_additions() {
+        // 64 additions of 16-bit numbers in one pass
+        let bits = 16;
+        let mut adder = ParallelCircuit::new(&RippleCarryAdder::new(bits).into_circuit()).unwrap();
+        let mut random = RandomVectors::new();
+        let mut next = || random.next_word();
+        let pairs: Vec<(u64, u64)> = (0..64).map(|_| (next() & 0xffff, next() & 0xffff)).collect();
+
+        // Inputs are a0.., b0..; outputs sum0.. and the carry out
+        let mut inputs = Vec::new();
+        for operand in [0, 1] {
+            for i in 0..bits {
+                let bit = |k: usize| {
+                    let value = if operand == 0 { pairs[k].0 } else { pairs[k].1 };
+                    ((value >> i) & 1) << k
+                };
+                inputs.push((0..64).map(bit).fold(0, |word, bit| word | bit));
+            }
+        }
+        let outputs = adder.evaluate(&inputs);
+        for (k, &(a, b)) in pairs.iter().enumerate() {
+            let sum = outputs.iter().enumerate().map(|(i, word)| ((word >> k) & 1) << i).sum::<u64>();
+            assert_eq!(sum, a + b, "{} + {}", a, b);
+        }
+    }
+
+    #[test]
+    fn test_constants_and_rejected_circuits() {
+        let mut circuit = ParallelCircuit::new(&load("circuit c(a) -> (y) { wire one; or g(a, one) -> (y); }", None).unwrap());
+        assert!(circuit.is_err(), "an undriven net without a level is rejected");
+        let constant = crate::expression::compile(&crate::expression::parse("a | 1").unwrap());
+        circuit = ParallelCircuit::new(&constant);
+        assert_eq!(circuit.unwrap().evaluate(&[0]), vec![u64::MAX]);
+        assert!(ParallelCircuit::new(&load_builtin("srlatch").unwrap()).is_err());
+    }
+}
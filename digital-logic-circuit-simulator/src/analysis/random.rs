@@ -0,0 +1,57 @@
+use crate::components::Level;
+
+// Xorshift generator with a fixed seed, so pseudo-random runs repeat and can be compared
+pub struct RandomVectors {
+    state: u64,
+}
+
+impl RandomVectors {
+    pub fn new() -> Self {
+        RandomVectors::with_seed(0x2545_f491_4f6c_dd1d)
+    }
+
+    // The seed must not be 0, which xorshift never leaves
+    pub fn with_seed(seed: u64) -> Self {
+        assert_ne!(seed, 0, "Seed must not be 0");
+        RandomVectors { state: seed }
+    }
+
+    pub fn next_word(&mut self) -> u64 {
+        self.state ^= self.state << 13;
+        self.state ^= self.state >> 7;
+        self.state ^= self.state << 17;
+        self.state
+    }
+
+    // One input vector of `inputs` random levels, each taken from the lowest bit of a new word
+    pub fn vector(&mut self, inputs: usize) -> Vec<Level> {
+        (0..inputs)
+            .map(|_| if self.next_word() & 1 == 1 { Level::High } else { Level::Low })
+            .collect()
+    }
+
+    pub fn vectors(&mut self, count: usize, inputs: usize) -> Vec<Vec<Level>> {
+        (0..count).map(|_| self.vector(inputs)).collect()
+    }
+}
+
+impl Default for RandomVectors {
+    fn default() -> Self {
+        RandomVectors::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_repeatable() {
+        let vectors = RandomVectors::new().vectors(8, 5);
+        assert_eq!(vectors, RandomVectors::new().vectors(8, 5));
+        assert!(vectors.iter().all(|vector| vector.len() == 5));
+        assert!(vectors.iter().flatten().any(|&level| level == Level::High));
+        assert!(vectors.iter().flatten().any(|&level| level == Level::Low));
+        assert_ne!(RandomVectors::with_seed(1).next_word(), RandomVectors::new().next_word());
+    }
+}
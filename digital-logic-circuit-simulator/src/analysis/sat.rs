@@ -0,0 +1,376 @@
+use std::ops::Not;
+
+// Conflicts before the first restart, grown by half after every restart
+const FIRST_RESTART: usize = 100;
+
+// A variable or its negation
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Literal(u32);
+
+impl Literal {
+    pub fn positive(variable: usize) -> Literal {
+        Literal((variable as u32) << 1)
+    }
+
+    pub fn variable(self) -> usize {
+        (self.0 >> 1) as usize
+    }
+
+    pub fn is_negated(self) -> bool {
+        self.0 & 1 == 1
+    }
+
+    fn index(self) -> usize {
+        self.0 as usize
+    }
+}
+
+impl Not for Literal {
+    type Output = Literal;
+
+    fn not(self) -> Literal {
+        Literal(self.0 ^ 1)
+    }
+}
+
+// Conflict-driven clause learning SAT solver: two watched literals per clause,
+// first-UIP learning, activity-based branching and restarts
+#[derive(Debug, Default)]
+pub struct Solver {
+    clauses: Vec<Vec<Literal>>,
+    // Clauses watching each literal, i.e. to look at when it becomes false
+    watches: Vec<Vec<usize>>,
+    values: Vec<Option<bool>>,
+    levels: Vec<usize>,
+    reasons: Vec<Option<usize>>,
+    // Value a variable last had, tried first when branching on it again
+    phases: Vec<bool>,
+    activity: Vec<f64>,
+    increment: f64,
+    trail: Vec<Literal>,
+    // Trail length at the start of every decision level
+    trail_limits: Vec<usize>,
+    propagated: usize,
+    unsatisfiable: bool,
+}
+
+impl Solver {
+    pub fn new() -> Solver {
+        Solver { increment: 1.0, ..Default::default() }
+    }
+
+    pub fn new_variable(&mut self) -> Literal {
+        let variable = self.values.len();
+        self.values.push(None);
+        self.levels.push(0);
+        self.reasons.push(None);
+        self.phases.push(false);
+        self.activity.push(0.0);
+        self.watches.push(Vec::new());
+        self.watches.push(Vec::new());
+        Literal::positive(variable)
+    }
+
+    pub fn variable_count(&self) -> usize {
+        self.values.len()
+    }
+
+    // Require at least one of the literals to be true
+    pub fn add_clause(&mut self, literals: &[Literal]) {
+        let mut clause = literals.to_vec();
+        clause.sort();
+        clause.dedup();
+        if clause.windows(2).any(|pair| pair[0] == !pair[1]) {
+            return;
+        }
+        // Level 0 literals may have to be looked at again for the new clause
+        self.propagated = 0;
+        match clause.len() {
+            0 => self.unsatisfiable = true,
+            1 => match self.value(clause[0]) {
+                Some(true) => {}
+                Some(false) => self.unsatisfiable = true,
+                None => self.assign(clause[0], None),
+            },
+            _ => {
+                self.attach(clause);
+            }
+        }
+    }
+
+    // Value of every variable in a satisfying assignment, None if there is none
+    pub fn solve(&mut self) -> Option<Vec<bool>> {
+        let mut conflicts = 0;
+        let mut restart = FIRST_RESTART;
+        while !self.unsatisfiable {
+            if let Some(conflict) = self.propagate() {
+                if self.trail_limits.is_empty() {
+                    self.unsatisfiable = true;
+                    break;
+                }
+                let (learnt, level) = self.analyze(conflict);
+                self.backtrack(level);
+                if learnt.len() == 1 {
+                    self.assign(learnt[0], None);
+                } else {
+                    let asserting = learnt[0];
+                    let clause = self.attach(learnt);
+                    self.assign(asserting, Some(clause));
+                }
+                self.increment /= 0.95;
+
+                conflicts += 1;
+                if conflicts == restart {
+                    conflicts = 0;
+                    restart += restart / 2;
+                    self.backtrack(0);
+                }
+                continue;
+            }
+
+            match self.pick_branch() {
+                Some(variable) => {
+                    self.trail_limits.push(self.trail.len());
+                    let literal = Literal::positive(variable);
+                    self.assign(if self.phases[variable] { literal } else { !literal }, None);
+                }
+                None => {
+                    let model = self.values.iter().map(|value| value == &Some(true)).collect();
+                    self.backtrack(0);
+                    return Some(model);
+                }
+            }
+        }
+        None
+    }
+
+    fn value(&self, literal: Literal) -> Option<bool> {
+        self.values[literal.variable()].map(|value| value != literal.is_negated())
+    }
+
+    fn assign(&mut self, literal: Literal, reason: Option<usize>) {
+        let variable = literal.variable();
+        self.values[variable] = Some(!literal.is_negated());
+        self.levels[variable] = self.trail_limits.len();
+        self.reasons[variable] = reason;
+        self.trail.push(literal);
+    }
+
+    // Store a clause of at least two literals, watching the first two
+    fn attach(&mut self, clause: Vec<Literal>) -> usize {
+        let index = self.clauses.len();
+        self.watches[clause[0].index()].push(index);
+        self.watches[clause[1].index()].push(index);
+        self.clauses.push(clause);
+        index
+    }
+
+    // Assign the literals implied by unit clauses, returning a clause that became false.
+    // The implied literal of a clause is always moved to its front.
+    fn propagate(&mut self) -> Option<usize> {
+        while self.propagated < self.trail.len() {
+            let falsified = !self.trail[self.propagated];
+            self.propagated += 1;
+
+            let watching = std::mem::take(&mut self.watches[falsified.index()]);
+            let mut kept = Vec::with_capacity(watching.len());
+            let mut conflict = None;
+            for index in watching {
+                if conflict.is_some() {
+                    kept.push(index);
+                    continue;
+                }
+                if self.clauses[index][0] == falsified {
+                    self.clauses[index].swap(0, 1);
+                }
+                let first = self.clauses[index][0];
+                if self.value(first) == Some(true) {
+                    kept.push(index);
+                    continue;
+                }
+
+                let replacement = (2..self.clauses[index].len()).find(|&k| self.value(self.clauses[index][k]) != Some(false));
+                if let Some(k) = replacement {
+                    self.clauses[index].swap(1, k);
+                    let watch = self.clauses[index][1];
+                    self.watches[watch.index()].push(index);
+                    continue;
+                }
+
+                kept.push(index);
+                match self.value(first) {
+                    Some(false) => conflict = Some(index),
+                    _ => self.assign(first, Some(index)),
+                }
+            }
+            self.watches[falsified.index()] = kept;
+            if conflict.is_some() {
+                return conflict;
+            }
+        }
+        None
+    }
+
+    // Learn a clause from a conflict by resolving until one literal of the current level is left.
+    // Returns the clause, asserting literal first, and the level to go back to.
+    fn analyze(&mut self, conflict: usize) -> (Vec<Literal>, usize) {
+        let level = self.trail_limits.len();
+        let mut seen = vec![false; self.values.len()];
+        let mut learnt = vec![Literal(0)];
+        let mut open = 0;
+        let mut clause = conflict;
+        let mut position = self.trail.len();
+        let mut skip = 0;
+
+        let asserting = loop {
+            for k in skip..self.clauses[clause].len() {
+                let literal = self.clauses[clause][k];
+                let variable = literal.variable();
+                if seen[variable] || self.levels[variable] == 0 {
+                    continue;
+                }
+                seen[variable] = true;
+                self.bump(variable);
+                if self.levels[variable] == level {
+                    open += 1;
+                } else {
+                    learnt.push(literal);
+                }
+            }
+
+            loop {
+                position -= 1;
+                if seen[self.trail[position].variable()] {
+                    break;
+                }
+            }
+            let literal = self.trail[position];
+            open -= 1;
+            if open == 0 {
+                break literal;
+            }
+            clause = self.reasons[literal.variable()].unwrap();
+            // The first literal of a reason is the one it implied
+            skip = 1;
+        };
+        learnt[0] = !asserting;
+
+        // Watch the literal of the highest remaining level second, so the clause is unit after backtracking
+        let mut back = 0;
+        for k in 1..learnt.len() {
+            if self.levels[learnt[k].variable()] > back {
+                back = self.levels[learnt[k].variable()];
+                learnt.swap(1, k);
+            }
+        }
+        (learnt, back)
+    }
+
+    fn bump(&mut self, variable: usize) {
+        self.activity[variable] += self.increment;
+        if self.activity[variable] > 1e100 {
+            for activity in self.activity.iter_mut() {
+                *activity *= 1e-100;
+            }
+            self.increment *= 1e-100;
+        }
+    }
+
+    fn backtrack(&mut self, level: usize) {
+        if self.trail_limits.len() <= level {
+            return;
+        }
+        let start = self.trail_limits[level];
+        for literal in self.trail.drain(start..) {
+            let variable = literal.variable();
+            self.phases[variable] = !literal.is_negated();
+            self.values[variable] = None;
+            self.reasons[variable] = None;
+        }
+        self.trail_limits.truncate(level);
+        self.propagated = start;
+    }
+
+    // Unassigned variable with the highest activity
+    fn pick_branch(&self) -> Option<usize> {
+        (0..self.values.len())
+            .filter(|&variable| self.values[variable].is_none())
+            .max_by(|&a, &b| self.activity[a].total_cmp(&self.activity[b]).then(b.cmp(&a)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn satisfies(model: &[bool], clauses: &[Vec<Literal>]) -> bool {
+        clauses
+            .iter()
+            .all(|clause| clause.iter().any(|literal| model[literal.variable()] != literal.is_negated()))
+    }
+
+    #[test]
+    fn test_satisfiable() {
+        let mut solver = Solver::new();
+        let [a, b, c] = [solver.new_variable(), solver.new_variable(), solver.new_variable()];
+        let clauses = vec![vec![a, b], vec![!a, c], vec![!b, !c], vec![!c, !b, a]];
+        for clause in &clauses {
+            solver.add_clause(clause);
+        }
+        let model = solver.solve().unwrap();
+        assert!(satisfies(&model, &clauses));
+    }
+
+    #[test]
+    fn test_unsatisfiable() {
+        let mut solver = Solver::new();
+        let [a, b] = [solver.new_variable(), solver.new_variable()];
+        for clause in [[a, b], [!a, b], [a, !b], [!a, !b]] {
+            solver.add_clause(&clause);
+        }
+        assert_eq!(solver.solve(), None);
+
+        let mut solver = Solver::new();
+        let a = solver.new_variable();
+        solver.add_clause(&[a]);
+        solver.add_clause(&[!a]);
+        assert_eq!(solver.solve(), None);
+    }
+
+    #[test]
+    fn test_pigeonhole() {
+        // Five pigeons do not fit into four holes, which needs many conflicts to show
+        let (pigeons, holes) = (5, 4);
+        let mut solver = Solver::new();
+        let sits: Vec<Vec<Literal>> =
+            (0..pigeons).map(|_| (0..holes).map(|_| solver.new_variable()).collect()).collect();
+        for pigeon in &sits {
+            solver.add_clause(pigeon);
+        }
+        // No two pigeons share a hole
+        for (i, first) in sits.iter().enumerate() {
+            for second in &sits[i + 1..] {
+                for (&a, &b) in first.iter().zip(second) {
+                    solver.add_clause(&[!a, !b]);
+                }
+            }
+        }
+        assert_eq!(solver.solve(), None);
+    }
+
+    #[test]
+    fn test_chain_of_implications() {
+        let mut solver = Solver::new();
+        let variables: Vec<Literal> = (0..50).map(|_| solver.new_variable()).collect();
+        let mut clauses = vec![vec![variables[0]]];
+        for pair in variables.windows(2) {
+            clauses.push(vec![!pair[0], pair[1]]);
+        }
+        for clause in &clauses {
+            solver.add_clause(clause);
+        }
+        let model = solver.solve().unwrap();
+        assert!(model.iter().all(|&value| value));
+        assert!(satisfies(&model, &clauses));
+    }
+}
@@ -0,0 +1,324 @@
+use core::fmt;
+
+use crate::analysis::ParallelCircuit;
+use crate::components::Level;
+use crate::simulation::{Circuit, SimulationError, Simulator};
+
+// Largest number of two-valued inputs a table is generated for, i.e. at most 2^20 rows
+pub const MAX_TRUTH_TABLE_INPUTS: usize = 20;
+
+// Output levels of a circuit for every combination of its input levels
+#[derive(Debug, Clone, PartialEq)]
+pub struct TruthTable {
+    inputs: Vec<String>,
+    outputs: Vec<String>,
+    rows: Vec<(Vec<Level>, Vec<Level>)>,
+}
+
+// A row whose outputs differ between two tables, `None` where a table lacks the row
+#[derive(Debug, Clone, PartialEq)]
+pub struct Mismatch {
+    pub inputs: Vec<Level>,
+    pub expected: Option<Vec<Level>>,
+    pub actual: Option<Vec<Level>>,
+}
+
+impl fmt::Display for Mismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let row = |levels: &Option<Vec<Level>>| match levels {
+            Some(levels) => symbols(levels),
+            None => String::from("missing"),
+        };
+        write!(
+            f,
+            "{}: expected {}, found {}",
+            symbols(&self.inputs),
+            row(&self.expected),
+            row(&self.actual)
+        )
+    }
+}
+
+impl TruthTable {
+    // Enumerate the input ports of a circuit, the first input being the most significant.
+    // With `undefined` every input also takes the Undefined level.
+    // Combinational gate circuits are swept 64 rows at a time with a ParallelCircuit.
+    pub fn from_circuit(circuit: Circuit, undefined: bool) -> Result<TruthTable, SimulationError> {
+        row_count(circuit.inputs().len(), if undefined { 3 } else { 2 })?;
+        match ParallelCircuit::new(&circuit) {
+            Ok(mut parallel) if !undefined => TruthTable::sweep(&mut parallel),
+            _ => TruthTable::generate(&mut Simulator::new(circuit), undefined),
+        }
+    }
+
+    fn sweep(circuit: &mut ParallelCircuit) -> Result<TruthTable, SimulationError> {
+        let inputs = circuit.inputs().to_vec();
+        let level = |bit: u64| if bit == 1 { Level::High } else { Level::Low };
+        let count = row_count(inputs.len(), 2)? as u64;
+        let mut rows = Vec::new();
+        for first_row in (0..count).step_by(64) {
+            let words = circuit.evaluate_rows(first_row)?;
+            for row in first_row..count.min(first_row + 64) {
+                let combination = (0..inputs.len()).map(|i| level((row >> (inputs.len() - 1 - i)) & 1)).collect();
+                let result = words.iter().map(|word| level((word >> (row - first_row)) & 1)).collect();
+                rows.push((combination, result));
+            }
+        }
+        Ok(TruthTable { inputs, outputs: circuit.outputs().to_vec(), rows })
+    }
+
+    // Enumerate the input ports of a simulated circuit.
+    // Rows are simulated one after another, so circuits with state depend on the rows before.
+    pub fn generate(simulator: &mut Simulator, undefined: bool) -> Result<TruthTable, SimulationError> {
+        let inputs = simulator.circuit().inputs().to_vec();
+        let outputs = simulator.circuit().outputs().to_vec();
+        let levels: &[Level] = if undefined {
+            &[Level::Low, Level::High, Level::Undefined]
+        } else {
+            &[Level::Low, Level::High]
+        };
+
+        let mut rows = Vec::new();
+        for row in 0..row_count(inputs.len(), levels.len())? {
+            let mut combination = vec![Level::Low; inputs.len()];
+            let mut rest = row;
+            for level in combination.iter_mut().rev() {
+                *level = levels[rest % levels.len()];
+                rest /= levels.len();
+            }
+
+            for (name, &level) in inputs.iter().zip(&combination) {
+                simulator.set_level(name, level)?;
+            }
+            simulator.settle()?;
+            let result = outputs.iter().map(|name| simulator.level(name)).collect::<Result<Vec<Level>, _>>()?;
+            rows.push((combination, result));
+        }
+        Ok(TruthTable { inputs, outputs, rows })
+    }
+
+    // Read a table in the format written by `Display`:
+    //
+    //     # a comment
+    //     a b | carry
+    //     0 0 | 0
+    //     1 x | x
+    pub fn parse(text: &str) -> Result<TruthTable, String> {
+        let mut lines = text
+            .lines()
+            .enumerate()
+            .map(|(number, line)| (number + 1, line.split('#').next().unwrap().trim()))
+            .filter(|(_, line)| !line.is_empty());
+
+        let (number, header) = lines.next().ok_or("Truth table is empty")?;
+        let (inputs, outputs) = split(header).ok_or_else(|| format!("Line {}: expected 'inputs | outputs'", number))?;
+        let mut table = TruthTable {
+            inputs: inputs.iter().map(|name| name.to_string()).collect(),
+            outputs: outputs.iter().map(|name| name.to_string()).collect(),
+            rows: Vec::new(),
+        };
+
+        for (number, line) in lines {
+            let (inputs, outputs) = split(line).ok_or_else(|| format!("Line {}: expected 'inputs | outputs'", number))?;
+            if inputs.len() != table.inputs.len() || outputs.len() != table.outputs.len() {
+                return Err(format!(
+                    "Line {}: expected {} inputs and {} outputs",
+                    number,
+                    table.inputs.len(),
+                    table.outputs.len()
+                ));
+            }
+            let levels = |symbols: Vec<&str>| {
+                symbols
+                    .iter()
+                    .map(|symbol| match symbol.chars().collect::<Vec<char>>()[..] {
+                        [c] => Level::from_symbol(c),
+                        _ => None,
+                    })
+                    .collect::<Option<Vec<Level>>>()
+                    .ok_or_else(|| format!("Line {}: levels are 0, 1, x or z", number))
+            };
+            table.rows.push((levels(inputs)?, levels(outputs)?));
+        }
+        Ok(table)
+    }
+
+    pub fn inputs(&self) -> &[String] {
+        &self.inputs
+    }
+
+    pub fn outputs(&self) -> &[String] {
+        &self.outputs
+    }
+
+    pub fn rows(&self) -> &[(Vec<Level>, Vec<Level>)] {
+        &self.rows
+    }
+
+    // Outputs for the given input levels, if the table has that row
+    pub fn output(&self, inputs: &[Level]) -> Option<&[Level]> {
+        self.rows.iter().find(|(row, _)| row == inputs).map(|(_, outputs)| outputs.as_slice())
+    }
+
+    // Rows that differ from the expected table, including rows only one of them has.
+    // Fails if the tables have different ports.
+    pub fn compare(&self, expected: &TruthTable) -> Result<Vec<Mismatch>, String> {
+        if self.inputs != expected.inputs || self.outputs != expected.outputs {
+            return Err(format!(
+                "Ports differ: expected {} | {}, found {} | {}",
+                expected.inputs.join(" "),
+                expected.outputs.join(" "),
+                self.inputs.join(" "),
+                self.outputs.join(" ")
+            ));
+        }
+
+        let mut mismatches = Vec::new();
+        for (inputs, outputs) in &expected.rows {
+            let actual = self.output(inputs);
+            if actual != Some(outputs.as_slice()) {
+                mismatches.push(Mismatch {
+                    inputs: inputs.clone(),
+                    expected: Some(outputs.clone()),
+                    actual: actual.map(|levels| levels.to_vec()),
+                });
+            }
+        }
+        for (inputs, outputs) in &self.rows {
+            if expected.output(inputs).is_none() {
+                mismatches.push(Mismatch { inputs: inputs.clone(), expected: None, actual: Some(outputs.clone()) });
+            }
+        }
+        Ok(mismatches)
+    }
+}
+
+impl fmt::Display for TruthTable {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "{} | {}", self.inputs.join(" "), self.outputs.join(" "))?;
+        // Levels are padded to the width of their port name so the columns line up
+        let row = |names: &[String], levels: &[Level]| {
+            names
+                .iter()
+                .zip(levels)
+                .map(|(name, level)| format!("{:<width$}", level.symbol(), width = name.len()))
+                .collect::<Vec<String>>()
+                .join(" ")
+        };
+        for (inputs, outputs) in &self.rows {
+            writeln!(f, "{} | {}", row(&self.inputs, inputs), row(&self.outputs, outputs).trim_end())?;
+        }
+        Ok(())
+    }
+}
+
+// Names or levels on both sides of the `|`
+fn split(line: &str) -> Option<(Vec<&str>, Vec<&str>)> {
+    let (inputs, outputs) = line.split_once('|')?;
+    Some((inputs.split_whitespace().collect(), outputs.split_whitespace().collect()))
+}
+
+fn symbols(levels: &[Level]) -> String {
+    levels.iter().map(|level| level.symbol()).collect()
+}
+
+// Number of rows when each of `inputs` inputs takes `levels` levels, if it is within the
+// limit of 2^MAX_TRUTH_TABLE_INPUTS rows
+pub(crate) fn row_count(inputs: usize, levels: usize) -> Result<usize, SimulationError> {
+    let max_rows = 1 << MAX_TRUTH_TABLE_INPUTS;
+    match u32::try_from(inputs).ok().and_then(|inputs| levels.checked_pow(inputs)) {
+        Some(rows) if rows <= max_rows => Ok(rows),
+        _ => Err(SimulationError::TooManyInputs {
+            inputs,
+            limit: (0..=MAX_TRUTH_TABLE_INPUTS).take_while(|&n| levels.pow(n as u32) <= max_rows).count() - 1,
+        }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::components::gates::ANDGate;
+
+    fn and_circuit() -> Circuit {
+        let mut circuit = Circuit::new();
+        let a = circuit.add_input("a").unwrap();
+        let b = circuit.add_input("b").unwrap();
+        let y = circuit.add_output("y").unwrap();
+        circuit.add_component(Box::new(ANDGate::new(a, b, y)));
+        circuit
+    }
+
+    #[test]
+    fn test_generate() {
+        let table = TruthTable::from_circuit(and_circuit(), false).unwrap();
+        assert_eq!(table.rows().len(), 4);
+        assert_eq!(table.output(&[Level::High, Level::High]), Some(&[Level::High][..]));
+        assert_eq!(table.output(&[Level::High, Level::Low]), Some(&[Level::Low][..]));
+        assert_eq!(table.to_string(), "a b | y\n0 0 | 0\n0 1 | 0\n1 0 | 0\n1 1 | 1\n");
+
+        let table = TruthTable::from_circuit(and_circuit(), true).unwrap();
+        assert_eq!(table.rows().len(), 9);
+        assert_eq!(table.output(&[Level::Undefined, Level::Low]), Some(&[Level::Low][..]));
+    }
+
+    #[test]
+    fn test_parallel_sweep_matches_simulation() {
+        let circuit = || crate::digital_logic::arithmetic::RippleCarryAdder::new(4).into_circuit();
+        let simulated = TruthTable::generate(&mut Simulator::new(circuit()), false).unwrap();
+        assert_eq!(TruthTable::from_circuit(circuit(), false).unwrap(), simulated);
+    }
+
+    #[test]
+    fn test_too_many_inputs() {
+        let circuit = || crate::digital_logic::arithmetic::RippleCarryAdder::new(16).into_circuit();
+        let error = TruthTable::from_circuit(circuit(), false).unwrap_err();
+        assert_eq!(error, SimulationError::TooManyInputs { inputs: 32, limit: 20 });
+        assert_eq!(error.to_string(), "32 inputs are too many to enumerate, the limit is 20");
+        let circuit = crate::digital_logic::arithmetic::RippleCarryAdder::new(7).into_circuit();
+        assert_eq!(
+            TruthTable::generate(&mut Simulator::new(circuit), true),
+            Err(SimulationError::TooManyInputs { inputs: 14, limit: 12 })
+        );
+        // Far more inputs than rows could ever be counted
+        let circuit = crate::digital_logic::arithmetic::RippleCarryAdder::new(64).into_circuit();
+        assert!(TruthTable::from_circuit(circuit, false).is_err());
+    }
+
+    #[test]
+    fn test_parse_round_trip() {
+        let table = TruthTable::from_circuit(and_circuit(), true).unwrap();
+        assert_eq!(TruthTable::parse(&table.to_string()).unwrap(), table);
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert_eq!(TruthTable::parse("# nothing\n"), Err("Truth table is empty".to_string()));
+        assert_eq!(TruthTable::parse("a b y\n"), Err("Line 1: expected 'inputs | outputs'".to_string()));
+        assert_eq!(
+            TruthTable::parse("a b | y\n0 0 | 0\n0 | 1\n"),
+            Err("Line 3: expected 2 inputs and 1 outputs".to_string())
+        );
+        assert_eq!(TruthTable::parse("a | y\n2 | 1\n"), Err("Line 2: levels are 0, 1, x or z".to_string()));
+    }
+
+    #[test]
+    fn test_compare() {
+        let table = TruthTable::from_circuit(and_circuit(), false).unwrap();
+        let expected = TruthTable::parse("a b | y\n0 0 | 0\n0 1 | 0\n1 0 | 1\n").unwrap();
+        let mismatches = table.compare(&expected).unwrap();
+        assert_eq!(
+            mismatches,
+            vec![
+                Mismatch {
+                    inputs: vec![Level::High, Level::Low],
+                    expected: Some(vec![Level::High]),
+                    actual: Some(vec![Level::Low]),
+                },
+                Mismatch { inputs: vec![Level::High, Level::High], expected: None, actual: Some(vec![Level::High]) },
+            ]
+        );
+        assert_eq!(mismatches[0].to_string(), "10: expected 1, found 0");
+        assert!(table.compare(&TruthTable::parse("a c | y\n").unwrap()).is_err());
+    }
+}
@@ -1,5 +1,28 @@
+pub mod subcircuit;
 pub mod half_adder;
 pub mod full_adder;
+pub mod decoder;
+pub mod multiplexer;
+pub mod demultiplexer;
+pub mod priority_encoder;
+pub mod magnitude_comparator;
+pub mod parity_generator;
 
+pub use subcircuit::Subcircuit;
 pub use half_adder::HalfAdder;
-pub use full_adder::FullAdder;
\ No newline at end of file
+pub use full_adder::FullAdder;
+pub use decoder::Decoder;
+pub use multiplexer::Multiplexer;
+pub use demultiplexer::Demultiplexer;
+pub use priority_encoder::PriorityEncoder;
+pub use magnitude_comparator::MagnitudeComparator;
+pub use parity_generator::ParityGenerator;
+
+// Check the truth table of a circuit against an expected one, e.g. from the tables directory
+#[cfg(test)]
+pub(crate) fn assert_truth_table(circuit: crate::simulation::Circuit, undefined: bool, expected: &str) {
+    use crate::analysis::TruthTable;
+    let table = TruthTable::from_circuit(circuit, undefined).unwrap();
+    let expected = TruthTable::parse(expected).unwrap();
+    assert_eq!(table.compare(&expected).unwrap(), vec![]);
+}
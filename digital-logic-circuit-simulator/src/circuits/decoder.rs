@@ -0,0 +1,136 @@
+use core::fmt;
+use crate::components::{
+    BaseComponent, Component, NetId, Nets,
+    gates::{ANDGate, NOTGate},
+};
+
+// Binary decoder setting the one output whose index the inputs encode, with the first input
+// being the least significant bit. There may be fewer outputs than input combinations.
+pub struct Decoder {
+    base: BaseComponent,
+    components: Vec<Box<dyn Component>>,
+}
+
+impl Decoder {
+    pub fn new(nets: &mut Nets, inputs: Vec<NetId>, outputs: Vec<NetId>) -> Decoder {
+        let mut base = BaseComponent::new("Decoder");
+        for &input in &inputs {
+            base.add_input(input);
+        }
+        for &output in &outputs {
+            base.add_output(output);
+        }
+
+        let mut components: Vec<Box<dyn Component>> = Vec::new();
+        let lines = SelectLines::new(nets, &inputs, &mut components);
+        for (index, output) in outputs.into_iter().enumerate() {
+            components.push(Box::new(ANDGate::with_inputs(lines.literals(index), output)));
+        }
+
+        Decoder { base, components }
+    }
+}
+
+// Select inputs of a decoder, multiplexer or demultiplexer together with their inversions
+pub(crate) struct SelectLines {
+    select: Vec<NetId>,
+    inverted: Vec<NetId>,
+}
+
+impl SelectLines {
+    // Add the inverters to `components`
+    pub fn new(nets: &mut Nets, select: &[NetId], components: &mut Vec<Box<dyn Component>>) -> SelectLines {
+        assert!(!select.is_empty(), "At least one select input is needed");
+        let inverted = nets.add_many(select.len());
+        for (&line, &inverse) in select.iter().zip(&inverted) {
+            components.push(Box::new(NOTGate::new(line, inverse)));
+        }
+        SelectLines { select: select.to_vec(), inverted }
+    }
+
+    // Lines that are all 1 exactly when the select inputs encode `index`
+    pub fn literals(&self, index: usize) -> Vec<NetId> {
+        assert!(index >> self.select.len() == 0, "{} select inputs cannot encode {}", self.select.len(), index);
+        (0..self.select.len())
+            .map(|bit| if (index >> bit) & 1 == 1 { self.select[bit] } else { self.inverted[bit] })
+            .collect()
+    }
+}
+
+impl fmt::Display for Decoder {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, component) in self.components.iter().enumerate() {
+            writeln!(f, "Component {}:", i + 1)?;
+            writeln!(f, "{}", component)?;
+        }
+        Ok(())
+    }
+}
+
+impl Component for Decoder {
+    fn update(&mut self, nets: &mut Nets) {
+        for component in self.components.iter_mut() {
+            component.update(nets);
+        }
+    }
+
+    fn inputs(&self) -> &[NetId] {
+        self.base.inputs()
+    }
+
+    fn outputs(&self) -> &[NetId] {
+        self.base.outputs()
+    }
+
+    fn flatten(self: Box<Self>) -> Vec<Box<dyn Component>> {
+        self.components
+            .into_iter()
+            .flat_map(|component| component.flatten())
+            .collect()
+    }
+
+    fn set_delay(&mut self, delay: u64) {
+        for component in self.components.iter_mut() {
+            component.set_delay(delay);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analysis::TruthTable;
+    use crate::circuits::assert_truth_table;
+    use crate::simulation::Circuit;
+
+    #[test]
+    fn test_update_decoder() {
+        let mut nets = Nets::new();
+        let (inputs, outputs) = (nets.add_many(3), nets.add_many(8));
+        let decoder = Decoder::new(&mut nets, inputs, outputs);
+        let circuit = Circuit::from_component(
+            "decoder",
+            nets,
+            Box::new(decoder),
+            &["a0", "a1", "a2"],
+            &["y0", "y1", "y2", "y3", "y4", "y5", "y6", "y7"],
+        );
+        assert_truth_table(circuit, false, include_str!("../../tables/decoder.txt"));
+    }
+
+    #[test]
+    fn test_fewer_outputs() {
+        // Three outputs of a 2-to-4 decoder, so input 3 sets none of them
+        let mut nets = Nets::new();
+        let (inputs, outputs) = (nets.add_many(2), nets.add_many(3));
+        let decoder = Decoder::new(&mut nets, inputs, outputs);
+        let circuit = Circuit::from_component("decoder", nets, Box::new(decoder), &["a0", "a1"], &["y0", "y1", "y2"]);
+        let table = TruthTable::from_circuit(circuit, false).unwrap();
+        let ones: Vec<usize> = table
+            .rows()
+            .iter()
+            .map(|(_, outputs)| outputs.iter().filter(|level| level.symbol() == '1').count())
+            .collect();
+        assert_eq!(ones, vec![1, 1, 1, 0]);
+    }
+}
@@ -0,0 +1,97 @@
+use core::fmt;
+use crate::components::{
+    BaseComponent, Component, NetId, Nets,
+    gates::ANDGate,
+};
+use crate::circuits::decoder::SelectLines;
+
+// 1:N demultiplexer routing the input to the output whose index the select inputs encode,
+// with the first select input being the least significant bit. The other outputs are 0.
+pub struct Demultiplexer {
+    base: BaseComponent,
+    components: Vec<Box<dyn Component>>,
+}
+
+impl Demultiplexer {
+    pub fn new(
+        nets: &mut Nets,
+        input: NetId,
+        select: Vec<NetId>,
+        outputs: Vec<NetId>,
+    ) -> Demultiplexer {
+        let mut base = BaseComponent::new("Demultiplexer");
+        base.add_input(input);
+        for &line in &select {
+            base.add_input(line);
+        }
+        for &output in &outputs {
+            base.add_output(output);
+        }
+
+        let mut components: Vec<Box<dyn Component>> = Vec::new();
+        let lines = SelectLines::new(nets, &select, &mut components);
+        for (index, output) in outputs.into_iter().enumerate() {
+            let mut inputs = lines.literals(index);
+            inputs.push(input);
+            components.push(Box::new(ANDGate::with_inputs(inputs, output)));
+        }
+
+        Demultiplexer { base, components }
+    }
+}
+
+impl fmt::Display for Demultiplexer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, component) in self.components.iter().enumerate() {
+            writeln!(f, "Component {}:", i + 1)?;
+            writeln!(f, "{}", component)?;
+        }
+        Ok(())
+    }
+}
+
+impl Component for Demultiplexer {
+    fn update(&mut self, nets: &mut Nets) {
+        for component in self.components.iter_mut() {
+            component.update(nets);
+        }
+    }
+
+    fn inputs(&self) -> &[NetId] {
+        self.base.inputs()
+    }
+
+    fn outputs(&self) -> &[NetId] {
+        self.base.outputs()
+    }
+
+    fn flatten(self: Box<Self>) -> Vec<Box<dyn Component>> {
+        self.components
+            .into_iter()
+            .flat_map(|component| component.flatten())
+            .collect()
+    }
+
+    fn set_delay(&mut self, delay: u64) {
+        for component in self.components.iter_mut() {
+            component.set_delay(delay);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::circuits::assert_truth_table;
+    use crate::simulation::Circuit;
+
+    #[test]
+    fn test_update_demultiplexer() {
+        let mut nets = Nets::new();
+        let (input, select, outputs) = (nets.add(), nets.add_many(2), nets.add_many(4));
+        let demultiplexer = Demultiplexer::new(&mut nets, input, select, outputs);
+        let circuit =
+            Circuit::from_component("demux4", nets, Box::new(demultiplexer), &["d", "s0", "s1"], &["y0", "y1", "y2", "y3"]);
+        assert_truth_table(circuit, false, include_str!("../../tables/demux4.txt"));
+    }
+}
@@ -1,107 +1,94 @@
 use core::fmt;
-use std::rc::Rc;
-use std::cell::RefCell;
 use crate::components::{
-    Connection, Component,
+    Component, NetId, Nets,
     gates::ORGate,
 };
-use crate::circuits::HalfAdder;
+use crate::circuits::{HalfAdder, Subcircuit};
 
+// Ports a, b, cin -> sum, cout; instances `ha1`, `ha2` and `or`
 pub struct FullAdder {
-    components: Vec<Box<dyn Component>>,
+    circuit: Subcircuit,
 }
 
 impl FullAdder {
     pub fn new(
-        input1: Rc<RefCell<Connection>>,
-        input2: Rc<RefCell<Connection>>,
-        carry_in: Rc<RefCell<Connection>>,
-        sum: Rc<RefCell<Connection>>,
-        carry_out: Rc<RefCell<Connection>>,
+        nets: &mut Nets,
+        input1: NetId,
+        input2: NetId,
+        carry_in: NetId,
+        sum: NetId,
+        carry_out: NetId,
     ) -> FullAdder {
-        let half_adder1_sum = Rc::new(RefCell::new(Connection::new()));
-        let half_adder1_carry = Rc::new(RefCell::new(Connection::new()));
-        let half_adder1 = HalfAdder::new(input1.clone(), input2.clone(), half_adder1_sum.clone(), half_adder1_carry.clone());
+        let mut circuit = Subcircuit::new("Full Adder");
+        circuit.add_input("a", input1);
+        circuit.add_input("b", input2);
+        circuit.add_input("cin", carry_in);
+        circuit.add_output("sum", sum);
+        circuit.add_output("cout", carry_out);
 
-        let half_adder2_sum = sum.clone();
-        let half_adder2_carry = Rc::new(RefCell::new(Connection::new()));
-        let half_adder2 = HalfAdder::new(half_adder1_sum.clone(), carry_in.clone(), half_adder2_sum.clone(), half_adder2_carry.clone());
+        let half_adder1_sum = circuit.add_net(nets, "s1");
+        let half_adder1_carry = circuit.add_net(nets, "c1");
+        let half_adder2_carry = circuit.add_net(nets, "c2");
+        let half_adder1 = HalfAdder::new(input1, input2, half_adder1_sum, half_adder1_carry);
+        let half_adder2 = HalfAdder::new(half_adder1_sum, carry_in, sum, half_adder2_carry);
+        let or_gate = ORGate::new(half_adder1_carry, half_adder2_carry, carry_out);
 
-        let or_gate = ORGate::new(half_adder1_carry.clone(), half_adder2_carry.clone(), carry_out.clone());
+        circuit.add_instance("ha1", Box::new(half_adder1));
+        circuit.add_instance("ha2", Box::new(half_adder2));
+        circuit.add_instance("or", Box::new(or_gate));
 
-        FullAdder {
-            components: vec![Box::new(half_adder1), Box::new(half_adder2), Box::new(or_gate)],
-        }
+        FullAdder { circuit }
+    }
+
+    pub fn subcircuit(&self) -> &Subcircuit {
+        &self.circuit
     }
 }
 
 impl fmt::Display for FullAdder {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        for (i, component) in self.components.iter().enumerate() {
-            writeln!(f, "Component {}:", i + 1)?;
-            writeln!(f, "{}", component)?;
-        }
-        Ok(())
+        write!(f, "{}", self.circuit)
     }
 }
 
 impl Component for FullAdder {
-    fn update(&mut self) {
-        for component in self.components.iter_mut() {
-            component.update();
-        }
+    fn update(&mut self, nets: &mut Nets) {
+        self.circuit.update(nets);
+    }
+
+    fn inputs(&self) -> &[NetId] {
+        self.circuit.inputs()
+    }
+
+    fn outputs(&self) -> &[NetId] {
+        self.circuit.outputs()
+    }
+
+    fn flatten(self: Box<Self>) -> Vec<Box<dyn Component>> {
+        Box::new(self.circuit).flatten()
+    }
+
+    fn set_delay(&mut self, delay: u64) {
+        self.circuit.set_delay(delay);
+    }
+
+    fn named_nets(&self) -> Vec<(String, NetId)> {
+        self.circuit.named_nets()
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::components::Level;
     use super::*;
+    use crate::circuits::assert_truth_table;
+    use crate::simulation::Circuit;
 
     #[test]
     fn test_update_full_adder() {
-        let input1 = Rc::new(RefCell::new(Connection::new()));
-        let input2 = Rc::new(RefCell::new(Connection::new()));
-        let carry_in = Rc::new(RefCell::new(Connection::new()));
-        let sum = Rc::new(RefCell::new(Connection::new()));
-        let carry_out = Rc::new(RefCell::new(Connection::new()));
-        let mut full_adder = FullAdder::new(input1.clone(), input2.clone(), carry_in.clone(), sum.clone(), carry_out.clone());
-
-        for (input1_level, input2_level, carry_in_level, expected_sum, expected_carry_out) in vec![
-            (Level::Low, Level::Low, Level::Low, Level::Low, Level::Low),
-            (Level::Low, Level::Low, Level::High, Level::High, Level::Low),
-            (Level::Low, Level::High, Level::Low, Level::High, Level::Low),
-            (Level::Low, Level::High, Level::High, Level::Low, Level::High),
-            (Level::High, Level::Low, Level::Low, Level::High, Level::Low),
-            (Level::High, Level::Low, Level::High, Level::Low, Level::High),
-            (Level::High, Level::High, Level::Low, Level::Low, Level::High),
-            (Level::High, Level::High, Level::High, Level::High, Level::High),
-            (Level::Undefined, Level::Low, Level::Low, Level::Undefined, Level::Undefined),
-            (Level::Low, Level::Undefined, Level::Low, Level::Undefined, Level::Undefined),
-            (Level::Undefined, Level::High, Level::Low, Level::Undefined, Level::Undefined),
-            (Level::High, Level::Undefined, Level::Low, Level::Undefined, Level::Undefined),
-            (Level::Undefined, Level::Low, Level::High, Level::Undefined, Level::Undefined),
-            (Level::Low, Level::Undefined, Level::High, Level::Undefined, Level::Undefined),
-            (Level::Undefined, Level::High, Level::High, Level::Undefined, Level::Undefined),
-            (Level::High, Level::Undefined, Level::High, Level::Undefined, Level::Undefined),
-            (Level::Undefined, Level::Undefined, Level::Low, Level::Undefined, Level::Undefined),
-            (Level::Undefined, Level::Undefined, Level::High, Level::Undefined, Level::Undefined),
-            (Level::Low, Level::Low, Level::Undefined, Level::Undefined, Level::Undefined),
-            (Level::Low, Level::High, Level::Undefined, Level::Undefined, Level::Undefined),
-            (Level::High, Level::Low, Level::Undefined, Level::Undefined, Level::Undefined),
-            (Level::High, Level::High, Level::Undefined, Level::Undefined, Level::Undefined),
-            (Level::Undefined, Level::Low, Level::Undefined, Level::Undefined, Level::Undefined),
-            (Level::Low, Level::Undefined, Level::Undefined, Level::Undefined, Level::Undefined),
-            (Level::Undefined, Level::High, Level::Undefined, Level::Undefined, Level::Undefined),
-            (Level::High, Level::Undefined, Level::Undefined, Level::Undefined, Level::Undefined),
-            (Level::Undefined, Level::Undefined, Level::Undefined, Level::Undefined, Level::Undefined),
-        ] {
-            input1.borrow_mut().set_level(input1_level);
-            input2.borrow_mut().set_level(input2_level);
-            carry_in.borrow_mut().set_level(carry_in_level);
-            full_adder.update();
-            assert_eq!(sum.borrow().level(), expected_sum);
-            assert_eq!(carry_out.borrow().level(), expected_carry_out);
-        }
+        let mut nets = Nets::new();
+        let [a, b, cin, sum, cout] = [(); 5].map(|_| nets.add());
+        let full_adder = FullAdder::new(&mut nets, a, b, cin, sum, cout);
+        let circuit = Circuit::from_component("full_adder", nets, Box::new(full_adder), &["a", "b", "cin"], &["sum", "cout"]);
+        assert_truth_table(circuit, true, include_str!("../../tables/full_adder.txt"));
     }
-}
\ No newline at end of file
+}
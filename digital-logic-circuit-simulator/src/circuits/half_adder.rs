@@ -1,78 +1,78 @@
 use core::fmt;
-use std::rc::Rc;
-use std::cell::RefCell;
 use crate::components::{
-    Connection, Component,
+    Component, NetId, Nets,
     gates::{ANDGate, XORGate},
 };
+use crate::circuits::Subcircuit;
 
+// Ports a, b -> sum, carry; instances `and` and `xor`
 pub struct HalfAdder {
-    components: Vec<Box<dyn Component>>,
+    circuit: Subcircuit,
 }
 
 impl HalfAdder {
-    pub fn new(
-        input1: Rc<RefCell<Connection>>,
-        input2: Rc<RefCell<Connection>>,
-        sum: Rc<RefCell<Connection>>,
-        carry: Rc<RefCell<Connection>>,
-    ) -> HalfAdder {
-        let and_gate = ANDGate::new(input1.clone(), input2.clone(), carry.clone());
-        let xor_gate = XORGate::new(input1, input2, sum);
+    pub fn new(input1: NetId, input2: NetId, sum: NetId, carry: NetId) -> HalfAdder {
+        let mut circuit = Subcircuit::new("Half Adder");
+        circuit.add_input("a", input1);
+        circuit.add_input("b", input2);
+        circuit.add_output("sum", sum);
+        circuit.add_output("carry", carry);
 
-        HalfAdder {
-            components: vec![Box::new(and_gate), Box::new(xor_gate)],
-        }
+        circuit.add_instance("and", Box::new(ANDGate::new(input1, input2, carry)));
+        circuit.add_instance("xor", Box::new(XORGate::new(input1, input2, sum)));
+
+        HalfAdder { circuit }
+    }
+
+    pub fn subcircuit(&self) -> &Subcircuit {
+        &self.circuit
     }
 }
 
 impl fmt::Display for HalfAdder {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        for (i, component) in self.components.iter().enumerate() {
-            writeln!(f, "Component {}:", i + 1)?;
-            writeln!(f, "{}", component)?;
-        }
-        Ok(())
+        write!(f, "{}", self.circuit)
     }
 }
 
 impl Component for HalfAdder {
-    fn update(&mut self) {
-        for component in self.components.iter_mut() {
-            component.update();
-        }
+    fn update(&mut self, nets: &mut Nets) {
+        self.circuit.update(nets);
+    }
+
+    fn inputs(&self) -> &[NetId] {
+        self.circuit.inputs()
+    }
+
+    fn outputs(&self) -> &[NetId] {
+        self.circuit.outputs()
+    }
+
+    fn flatten(self: Box<Self>) -> Vec<Box<dyn Component>> {
+        Box::new(self.circuit).flatten()
+    }
+
+    fn set_delay(&mut self, delay: u64) {
+        self.circuit.set_delay(delay);
+    }
+
+    fn named_nets(&self) -> Vec<(String, NetId)> {
+        self.circuit.named_nets()
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::components::Level;
     use super::*;
+    use crate::circuits::assert_truth_table;
+    use crate::simulation::Circuit;
 
     #[test]
     fn test_update_half_adder() {
-        let input1 = Rc::new(RefCell::new(Connection::new()));
-        let input2 = Rc::new(RefCell::new(Connection::new()));
-        let sum = Rc::new(RefCell::new(Connection::new()));
-        let carry = Rc::new(RefCell::new(Connection::new()));
-        let mut half_adder = HalfAdder::new(input1.clone(), input2.clone(), sum.clone(), carry.clone());
-
-        for (input1_level, input2_level, expected_sum, expected_carry) in vec![
-            (Level::Low, Level::Low, Level::Low, Level::Low),
-            (Level::Low, Level::High, Level::High, Level::Low),
-            (Level::High, Level::Low, Level::High, Level::Low),
-            (Level::High, Level::High, Level::Low, Level::High),
-            (Level::Undefined, Level::Low, Level::Undefined, Level::Undefined),
-            (Level::Low, Level::Undefined, Level::Undefined, Level::Undefined),
-            (Level::Undefined, Level::High, Level::Undefined, Level::Undefined),
-            (Level::High, Level::Undefined, Level::Undefined, Level::Undefined),
-            (Level::Undefined, Level::Undefined, Level::Undefined, Level::Undefined),
-        ] {
-            input1.borrow_mut().set_level(input1_level);
-            input2.borrow_mut().set_level(input2_level);
-            half_adder.update();
-            assert_eq!(sum.borrow().level(), expected_sum);
-            assert_eq!(carry.borrow().level(), expected_carry);
-        }
+        let mut nets = Nets::new();
+        let [a, b, sum, carry] = [(); 4].map(|_| nets.add());
+        let half_adder = HalfAdder::new(a, b, sum, carry);
+        let circuit = Circuit::from_component("half_adder", nets, Box::new(half_adder), &["a", "b"], &["sum", "carry"]);
+        assert_truth_table(circuit, true, include_str!("../../tables/half_adder.txt"));
     }
 }
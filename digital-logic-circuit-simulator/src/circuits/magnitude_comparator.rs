@@ -0,0 +1,155 @@
+use core::fmt;
+use crate::components::{
+    BaseComponent, Component, NetId, Nets,
+    gates::{ANDGate, NOTGate, ORGate, XNORGate},
+};
+
+// Comparator of two unsigned numbers of the same width, least significant bit first.
+// Exactly one of `less`, `equal` and `greater` is set.
+pub struct MagnitudeComparator {
+    base: BaseComponent,
+    components: Vec<Box<dyn Component>>,
+}
+
+impl MagnitudeComparator {
+    pub fn new(
+        nets: &mut Nets,
+        a: Vec<NetId>,
+        b: Vec<NetId>,
+        less: NetId,
+        equal: NetId,
+        greater: NetId,
+    ) -> MagnitudeComparator {
+        assert!(!a.is_empty() && a.len() == b.len(), "Numbers need the same width");
+        let mut base = BaseComponent::new("Magnitude Comparator");
+        for &input in a.iter().chain(&b) {
+            base.add_input(input);
+        }
+        for output in [less, equal, greater] {
+            base.add_output(output);
+        }
+
+        let mut components: Vec<Box<dyn Component>> = Vec::new();
+        let mut same = Vec::new();
+        let mut a_only = Vec::new();
+        let mut b_only = Vec::new();
+        for (&a, &b) in a.iter().zip(&b) {
+            let (not_a, not_b) = (nets.add(), nets.add());
+            components.push(Box::new(NOTGate::new(a, not_a)));
+            components.push(Box::new(NOTGate::new(b, not_b)));
+            let (x, y, z) = (nets.add(), nets.add(), nets.add());
+            components.push(Box::new(XNORGate::new(a, b, x)));
+            components.push(Box::new(ANDGate::new(a, not_b, y)));
+            components.push(Box::new(ANDGate::new(not_a, b, z)));
+            same.push(x);
+            a_only.push(y);
+            b_only.push(z);
+        }
+
+        // The highest bit in which the numbers differ decides: a > b if a has it set and
+        // every bit above it is equal
+        let mut greater_terms = Vec::new();
+        let mut less_terms = Vec::new();
+        for bit in 0..a.len() {
+            for (terms, only) in [(&mut greater_terms, &a_only), (&mut less_terms, &b_only)] {
+                let mut inputs = same[bit + 1..].to_vec();
+                inputs.push(only[bit]);
+                let term = nets.add();
+                components.push(Box::new(ANDGate::with_inputs(inputs, term)));
+                terms.push(term);
+            }
+        }
+        components.push(Box::new(ORGate::with_inputs(greater_terms, greater)));
+        components.push(Box::new(ORGate::with_inputs(less_terms, less)));
+        components.push(Box::new(ANDGate::with_inputs(same, equal)));
+
+        MagnitudeComparator { base, components }
+    }
+}
+
+impl fmt::Display for MagnitudeComparator {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, component) in self.components.iter().enumerate() {
+            writeln!(f, "Component {}:", i + 1)?;
+            writeln!(f, "{}", component)?;
+        }
+        Ok(())
+    }
+}
+
+impl Component for MagnitudeComparator {
+    fn update(&mut self, nets: &mut Nets) {
+        for component in self.components.iter_mut() {
+            component.update(nets);
+        }
+    }
+
+    fn inputs(&self) -> &[NetId] {
+        self.base.inputs()
+    }
+
+    fn outputs(&self) -> &[NetId] {
+        self.base.outputs()
+    }
+
+    fn flatten(self: Box<Self>) -> Vec<Box<dyn Component>> {
+        self.components
+            .into_iter()
+            .flat_map(|component| component.flatten())
+            .collect()
+    }
+
+    fn set_delay(&mut self, delay: u64) {
+        for component in self.components.iter_mut() {
+            component.set_delay(delay);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::circuits::assert_truth_table;
+    use crate::components::Level;
+    use crate::simulation::Circuit;
+
+    fn comparator(bits: usize) -> (Nets, MagnitudeComparator) {
+        let mut nets = Nets::new();
+        let (a, b) = (nets.add_many(bits), nets.add_many(bits));
+        let [less, equal, greater] = [(); 3].map(|_| nets.add());
+        let comparator = MagnitudeComparator::new(&mut nets, a, b, less, equal, greater);
+        (nets, comparator)
+    }
+
+    #[test]
+    fn test_update_comparator() {
+        let (nets, comparator) = comparator(2);
+        let circuit = Circuit::from_component(
+            "comparator",
+            nets,
+            Box::new(comparator),
+            &["a0", "a1", "b0", "b1"],
+            &["less", "equal", "greater"],
+        );
+        assert_truth_table(circuit, false, include_str!("../../tables/comparator.txt"));
+    }
+
+    #[test]
+    fn test_wide_comparator() {
+        let (mut nets, mut comparator) = comparator(5);
+        let set = |nets: &mut Nets, operand: &[NetId], value: usize| {
+            for (bit, &net) in operand.iter().enumerate() {
+                nets.set_level(net, if (value >> bit) & 1 == 1 { Level::High } else { Level::Low });
+            }
+        };
+        for a in 0..32 {
+            for b in 0..32 {
+                set(&mut nets, &comparator.inputs()[..5], a);
+                set(&mut nets, &comparator.inputs()[5..], b);
+                comparator.update(&mut nets);
+                let levels: Vec<bool> = comparator.outputs().iter().map(|&net| nets.level(net) == Level::High).collect();
+                assert_eq!(levels, vec![a < b, a == b, a > b], "{} against {}", a, b);
+            }
+        }
+    }
+}
@@ -0,0 +1,136 @@
+use core::fmt;
+use crate::components::{
+    BaseComponent, Component, NetId, Nets,
+    gates::{ANDGate, ORGate},
+};
+use crate::circuits::decoder::SelectLines;
+
+// N:1 multiplexer passing the data input whose index the select inputs encode, with the
+// first select input being the least significant bit. Select codes without a data input
+// give 0.
+pub struct Multiplexer {
+    base: BaseComponent,
+    components: Vec<Box<dyn Component>>,
+}
+
+impl Multiplexer {
+    pub fn new(nets: &mut Nets, data: Vec<NetId>, select: Vec<NetId>, output: NetId) -> Multiplexer {
+        let mut base = BaseComponent::new("Multiplexer");
+        for &input in data.iter().chain(&select) {
+            base.add_input(input);
+        }
+        base.add_output(output);
+
+        let mut components: Vec<Box<dyn Component>> = Vec::new();
+        let lines = SelectLines::new(nets, &select, &mut components);
+        let mut terms = Vec::new();
+        for (index, input) in data.into_iter().enumerate() {
+            let mut inputs = lines.literals(index);
+            inputs.push(input);
+            let term = nets.add();
+            components.push(Box::new(ANDGate::with_inputs(inputs, term)));
+            terms.push(term);
+        }
+        components.push(Box::new(ORGate::with_inputs(terms, output)));
+
+        Multiplexer { base, components }
+    }
+
+    // 2:1 multiplexer passing `input1` when `select` is 1, otherwise `input0`
+    pub fn two_to_one(nets: &mut Nets, input0: NetId, input1: NetId, select: NetId, output: NetId) -> Multiplexer {
+        Multiplexer::new(nets, vec![input0, input1], vec![select], output)
+    }
+
+    // 4:1 multiplexer with two select inputs
+    pub fn four_to_one(nets: &mut Nets, data: [NetId; 4], select: [NetId; 2], output: NetId) -> Multiplexer {
+        Multiplexer::new(nets, data.to_vec(), select.to_vec(), output)
+    }
+}
+
+impl fmt::Display for Multiplexer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, component) in self.components.iter().enumerate() {
+            writeln!(f, "Component {}:", i + 1)?;
+            writeln!(f, "{}", component)?;
+        }
+        Ok(())
+    }
+}
+
+impl Component for Multiplexer {
+    fn update(&mut self, nets: &mut Nets) {
+        for component in self.components.iter_mut() {
+            component.update(nets);
+        }
+    }
+
+    fn inputs(&self) -> &[NetId] {
+        self.base.inputs()
+    }
+
+    fn outputs(&self) -> &[NetId] {
+        self.base.outputs()
+    }
+
+    fn flatten(self: Box<Self>) -> Vec<Box<dyn Component>> {
+        self.components
+            .into_iter()
+            .flat_map(|component| component.flatten())
+            .collect()
+    }
+
+    fn set_delay(&mut self, delay: u64) {
+        for component in self.components.iter_mut() {
+            component.set_delay(delay);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::circuits::assert_truth_table;
+    use crate::components::Level;
+    use crate::simulation::Circuit;
+
+    #[test]
+    fn test_update_two_to_one() {
+        let mut nets = Nets::new();
+        let [d0, d1, s, y] = [(); 4].map(|_| nets.add());
+        let multiplexer = Multiplexer::two_to_one(&mut nets, d0, d1, s, y);
+        let circuit = Circuit::from_component("mux2", nets, Box::new(multiplexer), &["d0", "d1", "s"], &["y"]);
+        assert_truth_table(circuit, false, include_str!("../../tables/mux2.txt"));
+    }
+
+    #[test]
+    fn test_update_four_to_one() {
+        let mut nets = Nets::new();
+        let data = [(); 4].map(|_| nets.add());
+        let select = [(); 2].map(|_| nets.add());
+        let output = nets.add();
+        let multiplexer = Multiplexer::four_to_one(&mut nets, data, select, output);
+        let circuit =
+            Circuit::from_component("mux4", nets, Box::new(multiplexer), &["d0", "d1", "d2", "d3", "s0", "s1"], &["y"]);
+        assert_truth_table(circuit, false, include_str!("../../tables/mux4.txt"));
+    }
+
+    #[test]
+    fn test_update_n_to_one() {
+        // 5:1 multiplexer, selecting nothing for the codes 5 to 7 even with every input at 1
+        let mut nets = Nets::new();
+        let data = nets.add_many(5);
+        let select = nets.add_many(3);
+        let output = nets.add();
+        let mut multiplexer = Multiplexer::new(&mut nets, data.clone(), select.clone(), output);
+        for code in 0..8 {
+            for (bit, &line) in select.iter().enumerate() {
+                nets.set_level(line, if (code >> bit) & 1 == 1 { Level::High } else { Level::Low });
+            }
+            for (index, &input) in data.iter().enumerate() {
+                nets.set_level(input, if index == code || code >= 5 { Level::High } else { Level::Low });
+            }
+            multiplexer.update(&mut nets);
+            assert_eq!(nets.level(output), if code < 5 { Level::High } else { Level::Low }, "code {}", code);
+        }
+    }
+}
@@ -0,0 +1,115 @@
+use core::fmt;
+use crate::components::{
+    BaseComponent, Component, NetId, Nets,
+    gates::{XNORGate, XORGate},
+};
+
+// Parity generator built from a balanced tree of two-input XOR gates. `even` is the bit that
+// makes the number of ones including it even, which is 1 if the inputs have an odd number of
+// ones, and `odd` is its inverse.
+pub struct ParityGenerator {
+    base: BaseComponent,
+    components: Vec<Box<dyn Component>>,
+}
+
+impl ParityGenerator {
+    pub fn new(nets: &mut Nets, inputs: Vec<NetId>, even: NetId, odd: NetId) -> ParityGenerator {
+        assert!(inputs.len() >= 2, "Parity needs at least two inputs");
+        let mut base = BaseComponent::new("Parity Generator");
+        for &input in &inputs {
+            base.add_input(input);
+        }
+        base.add_output(even);
+        base.add_output(odd);
+
+        // Pair up the signals level by level until two are left for the final gates
+        let mut components: Vec<Box<dyn Component>> = Vec::new();
+        let mut level = inputs;
+        while level.len() > 2 {
+            let mut next = Vec::new();
+            for pair in level.chunks(2) {
+                match pair {
+                    &[a, b] => {
+                        let output = nets.add();
+                        components.push(Box::new(XORGate::new(a, b, output)));
+                        next.push(output);
+                    }
+                    _ => next.push(pair[0]),
+                }
+            }
+            level = next;
+        }
+        components.push(Box::new(XORGate::new(level[0], level[1], even)));
+        components.push(Box::new(XNORGate::new(level[0], level[1], odd)));
+
+        ParityGenerator { base, components }
+    }
+}
+
+impl fmt::Display for ParityGenerator {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, component) in self.components.iter().enumerate() {
+            writeln!(f, "Component {}:", i + 1)?;
+            writeln!(f, "{}", component)?;
+        }
+        Ok(())
+    }
+}
+
+impl Component for ParityGenerator {
+    fn update(&mut self, nets: &mut Nets) {
+        for component in self.components.iter_mut() {
+            component.update(nets);
+        }
+    }
+
+    fn inputs(&self) -> &[NetId] {
+        self.base.inputs()
+    }
+
+    fn outputs(&self) -> &[NetId] {
+        self.base.outputs()
+    }
+
+    fn flatten(self: Box<Self>) -> Vec<Box<dyn Component>> {
+        self.components
+            .into_iter()
+            .flat_map(|component| component.flatten())
+            .collect()
+    }
+
+    fn set_delay(&mut self, delay: u64) {
+        for component in self.components.iter_mut() {
+            component.set_delay(delay);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analysis::logic_depth;
+    use crate::circuits::assert_truth_table;
+    use crate::simulation::Circuit;
+
+    fn parity_circuit(bits: usize) -> Circuit {
+        let mut nets = Nets::new();
+        let (inputs, even, odd) = (nets.add_many(bits), nets.add(), nets.add());
+        let generator = ParityGenerator::new(&mut nets, inputs, even, odd);
+        let inputs: Vec<String> = (0..bits).map(|i| format!("d{}", i)).collect();
+        let inputs: Vec<&str> = inputs.iter().map(String::as_str).collect();
+        Circuit::from_component("parity", nets, Box::new(generator), &inputs, &["even", "odd"])
+    }
+
+    #[test]
+    fn test_update_parity_generator() {
+        assert_truth_table(parity_circuit(4), false, include_str!("../../tables/parity.txt"));
+    }
+
+    #[test]
+    fn test_tree_depth() {
+        for (bits, depth) in [(2, 1), (3, 2), (8, 3), (9, 4)] {
+            assert_eq!(logic_depth(&parity_circuit(bits)), Ok(depth), "{} inputs", bits);
+        }
+    }
+}
@@ -0,0 +1,122 @@
+use core::fmt;
+use crate::components::{
+    BaseComponent, Component, Level, NetId, Nets,
+    gates::{ANDGate, NOTGate, ORGate},
+};
+
+// Priority encoder giving the index of the highest set input in binary, the first output
+// being the least significant bit. `valid` tells whether any input is set at all; without
+// one the outputs are 0.
+pub struct PriorityEncoder {
+    base: BaseComponent,
+    components: Vec<Box<dyn Component>>,
+}
+
+impl PriorityEncoder {
+    pub fn new(nets: &mut Nets, inputs: Vec<NetId>, outputs: Vec<NetId>, valid: NetId) -> PriorityEncoder {
+        assert!(inputs.len() <= 1 << outputs.len(), "{} outputs cannot encode {} inputs", outputs.len(), inputs.len());
+        let mut base = BaseComponent::new("Priority Encoder");
+        for &input in &inputs {
+            base.add_input(input);
+        }
+        for &output in outputs.iter().chain([&valid]) {
+            base.add_output(output);
+        }
+
+        let mut components: Vec<Box<dyn Component>> = Vec::new();
+        // An input wins if it is set and no input above it is; `above` is the OR of those
+        let mut winners = vec![*inputs.last().unwrap()];
+        let mut above = *inputs.last().unwrap();
+        for &input in inputs.iter().rev().skip(1) {
+            let none_above = nets.add();
+            components.push(Box::new(NOTGate::new(above, none_above)));
+            let winner = nets.add();
+            components.push(Box::new(ANDGate::new(input, none_above, winner)));
+            winners.push(winner);
+            let any = nets.add();
+            components.push(Box::new(ORGate::new(input, above, any)));
+            above = any;
+        }
+        winners.reverse();
+        components.push(Box::new(ORGate::with_inputs(inputs.clone(), valid)));
+
+        // Output bit k is set if the winner's index has bit k set
+        for (bit, output) in outputs.into_iter().enumerate() {
+            let selected: Vec<_> = winners
+                .iter()
+                .enumerate()
+                .filter(|(index, _)| (index >> bit) & 1 == 1)
+                .map(|(_, &winner)| winner)
+                .collect();
+            if selected.is_empty() {
+                // No input has an index this high
+                nets.set_level(output, Level::Low);
+            } else {
+                components.push(Box::new(ORGate::with_inputs(selected, output)));
+            }
+        }
+
+        PriorityEncoder { base, components }
+    }
+}
+
+impl fmt::Display for PriorityEncoder {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, component) in self.components.iter().enumerate() {
+            writeln!(f, "Component {}:", i + 1)?;
+            writeln!(f, "{}", component)?;
+        }
+        Ok(())
+    }
+}
+
+impl Component for PriorityEncoder {
+    fn update(&mut self, nets: &mut Nets) {
+        for component in self.components.iter_mut() {
+            component.update(nets);
+        }
+    }
+
+    fn inputs(&self) -> &[NetId] {
+        self.base.inputs()
+    }
+
+    fn outputs(&self) -> &[NetId] {
+        self.base.outputs()
+    }
+
+    fn flatten(self: Box<Self>) -> Vec<Box<dyn Component>> {
+        self.components
+            .into_iter()
+            .flat_map(|component| component.flatten())
+            .collect()
+    }
+
+    fn set_delay(&mut self, delay: u64) {
+        for component in self.components.iter_mut() {
+            component.set_delay(delay);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::circuits::assert_truth_table;
+    use crate::simulation::Circuit;
+
+    #[test]
+    fn test_update_priority_encoder() {
+        let mut nets = Nets::new();
+        let (inputs, outputs, valid) = (nets.add_many(4), nets.add_many(2), nets.add());
+        let encoder = PriorityEncoder::new(&mut nets, inputs, outputs, valid);
+        let circuit = Circuit::from_component(
+            "priority_encoder",
+            nets,
+            Box::new(encoder),
+            &["d0", "d1", "d2", "d3"],
+            &["y0", "y1", "valid"],
+        );
+        assert_truth_table(circuit, false, include_str!("../../tables/priority_encoder.txt"));
+    }
+}
@@ -0,0 +1,191 @@
+use core::fmt;
+use crate::components::{BaseComponent, Component, Level, NetId, Nets};
+
+// A component built from named child instances, with named ports and internal nets.
+// Every net inside it can be looked up by its path, e.g. `ha1.sum` for the sum
+// port of the child `ha1`, or `ha1.xor.out0` for the output of a gate inside it.
+// Pins of children without a hierarchy of their own are named in0.. and out0...
+pub struct Subcircuit {
+    base: BaseComponent,
+    input_names: Vec<String>,
+    output_names: Vec<String>,
+    nets: Vec<(String, NetId)>,
+    instances: Vec<(String, Box<dyn Component>)>,
+}
+
+impl Subcircuit {
+    pub fn new(name: &str) -> Subcircuit {
+        Subcircuit {
+            base: BaseComponent::new(name),
+            input_names: Vec::new(),
+            output_names: Vec::new(),
+            nets: Vec::new(),
+            instances: Vec::new(),
+        }
+    }
+
+    pub fn add_input(&mut self, name: &str, net: NetId) {
+        self.input_names.push(name.to_string());
+        self.base.add_input(net);
+    }
+
+    pub fn add_output(&mut self, name: &str, net: NetId) {
+        self.output_names.push(name.to_string());
+        self.base.add_output(net);
+    }
+
+    // Create a named net internal to the subcircuit
+    pub fn add_net(&mut self, nets: &mut Nets, name: &str) -> NetId {
+        let net = nets.add();
+        self.nets.push((name.to_string(), net));
+        net
+    }
+
+    // Children are updated in the order they are added
+    pub fn add_instance(&mut self, name: &str, component: Box<dyn Component>) {
+        assert!(self.instance(name).is_none(), "Instance '{}' is added twice", name);
+        self.instances.push((name.to_string(), component));
+    }
+
+    pub fn input_names(&self) -> &[String] {
+        &self.input_names
+    }
+
+    pub fn output_names(&self) -> &[String] {
+        &self.output_names
+    }
+
+    pub fn instance_names(&self) -> Vec<&str> {
+        self.instances.iter().map(|(name, _)| name.as_str()).collect()
+    }
+
+    pub fn instance(&self, name: &str) -> Option<&dyn Component> {
+        self.instances
+            .iter()
+            .find(|(instance, _)| instance == name)
+            .map(|(_, component)| component.as_ref())
+    }
+
+    // Net at a hierarchical path
+    pub fn net(&self, path: &str) -> Option<NetId> {
+        self.named_nets().into_iter().find(|(name, _)| name == path).map(|(_, net)| net)
+    }
+
+    pub fn probe(&self, nets: &Nets, path: &str) -> Option<Level> {
+        self.net(path).map(|net| nets.level(net))
+    }
+}
+
+impl fmt::Display for Subcircuit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (name, component) in &self.instances {
+            writeln!(f, "Component {}:", name)?;
+            writeln!(f, "{}", component)?;
+        }
+        Ok(())
+    }
+}
+
+impl Component for Subcircuit {
+    fn update(&mut self, nets: &mut Nets) {
+        for (_, component) in self.instances.iter_mut() {
+            component.update(nets);
+        }
+    }
+
+    fn inputs(&self) -> &[NetId] {
+        self.base.inputs()
+    }
+
+    fn outputs(&self) -> &[NetId] {
+        self.base.outputs()
+    }
+
+    fn flatten(self: Box<Self>) -> Vec<Box<dyn Component>> {
+        self.instances
+            .into_iter()
+            .flat_map(|(_, component)| component.flatten())
+            .collect()
+    }
+
+    fn set_delay(&mut self, delay: u64) {
+        for (_, component) in self.instances.iter_mut() {
+            component.set_delay(delay);
+        }
+    }
+
+    fn named_nets(&self) -> Vec<(String, NetId)> {
+        let mut nets: Vec<(String, NetId)> = self
+            .input_names
+            .iter()
+            .zip(self.base.inputs())
+            .chain(self.output_names.iter().zip(self.base.outputs()))
+            .map(|(name, &net)| (name.clone(), net))
+            .collect();
+        nets.extend(self.nets.iter().cloned());
+        for (instance, component) in &self.instances {
+            let mut children = component.named_nets();
+            if children.is_empty() {
+                let pins = component.inputs().iter().enumerate().map(|(i, net)| (format!("in{}", i), net));
+                let pins = pins.chain(component.outputs().iter().enumerate().map(|(i, net)| (format!("out{}", i), net)));
+                children = pins.map(|(name, &net)| (name, net)).collect();
+            }
+            nets.extend(children.into_iter().map(|(name, net)| (format!("{}.{}", instance, name), net)));
+        }
+        nets
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::circuits::FullAdder;
+    use crate::components::gates::ANDGate;
+
+    #[test]
+    fn test_ports_and_instances() {
+        let mut subcircuit = Subcircuit::new("and3");
+        let mut nets = Nets::new();
+        let [a, b, c, y] = [(); 4].map(|_| nets.add());
+        for (name, net) in [("a", a), ("b", b), ("c", c)] {
+            subcircuit.add_input(name, net);
+        }
+        subcircuit.add_output("y", y);
+        let ab = subcircuit.add_net(&mut nets, "ab");
+        subcircuit.add_instance("and1", Box::new(ANDGate::new(a, b, ab)));
+        subcircuit.add_instance("and2", Box::new(ANDGate::new(ab, c, y)));
+
+        assert_eq!(subcircuit.input_names(), ["a", "b", "c"]);
+        assert_eq!(subcircuit.output_names(), ["y"]);
+        assert_eq!(subcircuit.instance_names(), ["and1", "and2"]);
+        assert!(subcircuit.instance("and3").is_none());
+        assert_eq!(subcircuit.net("and1.out0"), Some(ab));
+        assert_eq!(subcircuit.net("and2.in1"), Some(c));
+
+        for net in [a, b, c] {
+            nets.set_level(net, Level::High);
+        }
+        subcircuit.update(&mut nets);
+        assert_eq!(subcircuit.probe(&nets, "ab"), Some(Level::High));
+        assert_eq!(subcircuit.probe(&nets, "y"), Some(Level::High));
+        assert_eq!(subcircuit.probe(&nets, "nothing"), None);
+    }
+
+    #[test]
+    fn test_nested_paths() {
+        let mut subcircuit = Subcircuit::new("adder");
+        let mut nets = Nets::new();
+        let [a, b, cin, sum, cout] = [(); 5].map(|_| nets.add());
+        subcircuit.add_instance("fa", Box::new(FullAdder::new(&mut nets, a, b, cin, sum, cout)));
+        nets.set_level(a, Level::High);
+        nets.set_level(b, Level::Low);
+        nets.set_level(cin, Level::High);
+        subcircuit.update(&mut nets);
+
+        assert_eq!(subcircuit.probe(&nets, "fa.ha1.sum"), Some(Level::High));
+        assert_eq!(subcircuit.probe(&nets, "fa.ha2.carry"), Some(Level::High));
+        assert_eq!(subcircuit.probe(&nets, "fa.ha1.xor.out0"), Some(Level::High));
+        assert_eq!(subcircuit.probe(&nets, "fa.or.out0"), Some(Level::High));
+        assert_eq!(subcircuit.probe(&nets, "fa.sum"), Some(Level::Low));
+    }
+}
@@ -1,5 +1,6 @@
 mod command;
 mod completer;
+mod session;
 
 use rustyline::error::ReadlineError;
 use rustyline::{CompletionType, Config, EditMode, Editor};
@@ -8,10 +9,12 @@ use rustyline::history::DefaultHistory;
 
 pub use command::{Command, CommandResult};
 pub use completer::SimulatorHelper;
+pub use session::Session;
 
 pub struct Cli {
     rl: Editor<SimulatorHelper, DefaultHistory>,
     prompt: String,
+    session: Session,
 }
 
 impl Cli {
@@ -32,7 +35,7 @@ impl Cli {
         };
        
         rl.set_helper(Some(helper));
-        Self { rl, prompt: String::from("simulator> ") }
+        Self { rl, prompt: String::from("simulator> "), session: Session::new() }
     }
 
 
@@ -49,7 +52,7 @@ impl Cli {
                 Ok(line) => {
                     self.rl.add_history_entry(&line).ok();
                     let command = Command::parse(&line).unwrap_or(Command::Help);
-                    match command.execute() {
+                    match command.execute(&mut self.session) {
                         Ok(CommandResult::Continue(output)) => println!("{}", output),
                         Ok(CommandResult::Exit) => break,
                         Err(err) => println!("Error: {}", err),
@@ -1,4 +1,21 @@
-use crate::digital_logic::arithmetic::RippleCarryAdder;
+use std::thread;
+use std::time::Instant;
+
+use digital_logic_circuit_simulator::analysis::{self, Method, RandomVectors, SymbolicCircuit, TruthTable, VariableOrder};
+use digital_logic_circuit_simulator::components::{Level, Nets};
+use digital_logic_circuit_simulator::digital_logic::arithmetic::{
+    AddSubtractor, ArrayMultiplier, CarryLookaheadAdder, CarrySaveAdder, CarrySelectAdder, RippleCarryAdder,
+    WallaceMultiplier,
+};
+use digital_logic_circuit_simulator::digital_logic::{
+    Opcode, Register, RippleCounter, ShiftRegister, SynchronousCounter, ALU,
+};
+use digital_logic_circuit_simulator::expression;
+use digital_logic_circuit_simulator::netlist;
+use digital_logic_circuit_simulator::simulation::{Circuit, NetId, Simulator, ThreadedCircuit};
+use digital_logic_circuit_simulator::verilog;
+
+use crate::cli::Session;
 
 #[derive(Debug)]
 pub enum CommandResult {
@@ -15,6 +32,93 @@ pub enum Command {
         num1: String,
         num2: String,
     },
+    Subtract {
+        bits: usize,
+        num1: String,
+        num2: String,
+    },
+    RippleDelay {
+        bits: usize,
+        operands: Option<(String, String)>,
+    },
+    Bench {
+        bits: usize,
+        vectors: usize,
+    },
+    Threads {
+        bits: usize,
+        threads: Option<usize>,
+        vectors: usize,
+    },
+    Adders {
+        bits: usize,
+    },
+    Multiply {
+        bits: usize,
+        num1: String,
+        num2: String,
+    },
+    Alu {
+        bits: usize,
+        op: Opcode,
+        num1: String,
+        num2: String,
+    },
+    Load {
+        path: String,
+        top: Option<String>,
+    },
+    Set {
+        net: String,
+        level: Level,
+    },
+    Show {
+        net: Option<String>,
+    },
+    Probe {
+        path: String,
+    },
+    Export {
+        path: String,
+    },
+    Trace {
+        nets: Vec<String>,
+    },
+    Vcd {
+        path: String,
+    },
+    Clock {
+        cycles: usize,
+        net: String,
+    },
+    Truth {
+        circuit: String,
+        undefined: bool,
+        expected: Option<String>,
+    },
+    Expr {
+        source: String,
+    },
+    Minimize {
+        source: String,
+    },
+    Equiv {
+        first: String,
+        second: String,
+        method: Option<Method>,
+    },
+    Bdd {
+        circuit: String,
+        declared: bool,
+    },
+    Faults {
+        circuit: String,
+        path: String,
+    },
+    Atpg {
+        circuit: String,
+        coverage: f64,
+    },
 }
 
 impl Command {
@@ -24,6 +128,28 @@ impl Command {
             "help".to_string(),
             "exit".to_string(),
             "ripple <bits> <num1> <num2>".to_string(),
+            "sub <bits> <num1> <num2>".to_string(),
+            "delay <bits> [<num1> <num2>]".to_string(),
+            "adders <bits>".to_string(),
+            "bench [<bits>] [<vectors>]".to_string(),
+            "threads [<bits>] [<threads>] [<vectors>]".to_string(),
+            "mul <bits> <num1> <num2>".to_string(),
+            "alu <bits> <op> <num1> <num2>".to_string(),
+            "load <file> [<circuit>]".to_string(),
+            "set <net> <0|1>".to_string(),
+            "show [<net>]".to_string(),
+            "probe <path>".to_string(),
+            "export <file>".to_string(),
+            "trace [<net>...]".to_string(),
+            "vcd <file>".to_string(),
+            "clock <cycles> [<net>]".to_string(),
+            "truth <circuit> [--undefined] [<expected>]".to_string(),
+            "expr <expression>".to_string(),
+            "minimize <circuit|expression>".to_string(),
+            "equiv <circuit> <circuit> [--sat|--bdd]".to_string(),
+            "bdd <circuit> [--declared]".to_string(),
+            "faults <circuit> <vectors>".to_string(),
+            "atpg <circuit> [<coverage>]".to_string(),
         ]
     }
 
@@ -43,18 +169,199 @@ impl Command {
                 let num2 = parts[3].to_string();
                 Some(Command::RippleAdd { bits, num1, num2 })
             }
+            Some("sub") => match parts[..] {
+                [_, bits, num1, num2] => {
+                    Some(Command::Subtract { bits: bits.parse().ok()?, num1: num1.to_string(), num2: num2.to_string() })
+                }
+                _ => None,
+            },
+            Some("delay") => {
+                let bits = parts.get(1)?.parse().ok()?;
+                let operands = match parts.len() {
+                    2 => None,
+                    4 => Some((parts[2].to_string(), parts[3].to_string())),
+                    _ => return None,
+                };
+                Some(Command::RippleDelay { bits, operands })
+            }
+            Some("bench") => {
+                let bits = parts.get(1).map_or(Some(1024), |bits| bits.parse().ok())?;
+                let vectors = parts.get(2).map_or(Some(20), |vectors| vectors.parse().ok())?;
+                match parts.len() {
+                    1..=3 => Some(Command::Bench { bits, vectors }),
+                    _ => None,
+                }
+            }
+            Some("threads") => {
+                let bits = parts.get(1).map_or(Some(64), |bits| bits.parse().ok())?;
+                let threads = match parts.get(2) {
+                    Some(threads) => Some(threads.parse().ok()?),
+                    None => None,
+                };
+                let vectors = parts.get(3).map_or(Some(20), |vectors| vectors.parse().ok())?;
+                match parts.len() {
+                    1..=4 => Some(Command::Threads { bits, threads, vectors }),
+                    _ => None,
+                }
+            }
+            Some("adders") => match parts[..] {
+                [_, bits] => Some(Command::Adders { bits: bits.parse().ok()? }),
+                _ => None,
+            },
+            Some("mul") => match parts[..] {
+                [_, bits, num1, num2] => {
+                    Some(Command::Multiply { bits: bits.parse().ok()?, num1: num1.to_string(), num2: num2.to_string() })
+                }
+                _ => None,
+            },
+            Some("alu") => match parts[..] {
+                [_, bits, op, num1, num2] => Some(Command::Alu {
+                    bits: bits.parse().ok()?,
+                    op: Opcode::from_name(op)?,
+                    num1: num1.to_string(),
+                    num2: num2.to_string(),
+                }),
+                _ => None,
+            },
+            Some("load") => {
+                let path = parts.get(1)?.to_string();
+                let top = match parts.len() {
+                    2 => None,
+                    3 => Some(parts[2].to_string()),
+                    _ => return None,
+                };
+                Some(Command::Load { path, top })
+            }
+            Some("set") => {
+                if parts.len() != 3 {
+                    return None;
+                }
+                let level = match parts[2] {
+                    "0" => Level::Low,
+                    "1" => Level::High,
+                    _ => return None,
+                };
+                Some(Command::Set { net: parts[1].to_string(), level })
+            }
+            Some("probe") => match parts[..] {
+                [_, path] => Some(Command::Probe { path: path.to_string() }),
+                _ => None,
+            },
+            Some("show") => match parts.len() {
+                1 => Some(Command::Show { net: None }),
+                2 => Some(Command::Show { net: Some(parts[1].to_string()) }),
+                _ => None,
+            },
+            Some("export") => {
+                if parts.len() != 2 {
+                    return None;
+                }
+                Some(Command::Export { path: parts[1].to_string() })
+            }
+            Some("trace") => Some(Command::Trace { nets: parts[1..].iter().map(|net| net.to_string()).collect() }),
+            Some("vcd") => {
+                if parts.len() != 2 {
+                    return None;
+                }
+                Some(Command::Vcd { path: parts[1].to_string() })
+            }
+            Some("clock") => match parts[..] {
+                [_, cycles] => Some(Command::Clock { cycles: cycles.parse().ok()?, net: "clk".to_string() }),
+                [_, cycles, net] => Some(Command::Clock { cycles: cycles.parse().ok()?, net: net.to_string() }),
+                _ => None,
+            },
+            Some("truth") => {
+                let circuit = parts.get(1)?.to_string();
+                let undefined = parts.contains(&"--undefined");
+                let rest: Vec<&str> = parts[2..].iter().copied().filter(|&part| part != "--undefined").collect();
+                let expected = match rest[..] {
+                    [] => None,
+                    [path] => Some(path.to_string()),
+                    _ => return None,
+                };
+                Some(Command::Truth { circuit, undefined, expected })
+            }
+            Some("expr") => {
+                let source = input.trim_start().strip_prefix("expr")?.trim();
+                if source.is_empty() {
+                    return None;
+                }
+                Some(Command::Expr { source: source.to_string() })
+            }
+            Some("minimize") => {
+                let source = input.trim_start().strip_prefix("minimize")?.trim();
+                if source.is_empty() {
+                    return None;
+                }
+                Some(Command::Minimize { source: source.to_string() })
+            }
+            Some("equiv") => {
+                let method = match (parts.contains(&"--sat"), parts.contains(&"--bdd")) {
+                    (false, false) => None,
+                    (true, false) => Some(Method::Sat),
+                    (false, true) => Some(Method::Bdd),
+                    (true, true) => return None,
+                };
+                let circuits: Vec<&str> = parts[1..].iter().copied().filter(|part| !part.starts_with("--")).collect();
+                match circuits[..] {
+                    [first, second] => Some(Command::Equiv { first: first.to_string(), second: second.to_string(), method }),
+                    _ => None,
+                }
+            }
+            Some("bdd") => {
+                let declared = parts.contains(&"--declared");
+                let circuits: Vec<&str> = parts[1..].iter().copied().filter(|&part| part != "--declared").collect();
+                match circuits[..] {
+                    [circuit] => Some(Command::Bdd { circuit: circuit.to_string(), declared }),
+                    _ => None,
+                }
+            }
+            Some("faults") => match parts[..] {
+                [_, circuit, path] => Some(Command::Faults { circuit: circuit.to_string(), path: path.to_string() }),
+                _ => None,
+            },
+            Some("atpg") => {
+                let circuit = parts.get(1)?.to_string();
+                let coverage: f64 = parts.get(2).map_or(Some(100.0), |coverage| coverage.trim_end_matches('%').parse().ok())?;
+                match parts.len() {
+                    2 | 3 if (0.0..=100.0).contains(&coverage) => Some(Command::Atpg { circuit, coverage }),
+                    _ => None,
+                }
+            }
             _ => None
         }
 
     }
 
     // Execute the command
-    pub fn execute(&self) -> Result<CommandResult, String> {
+    pub fn execute(&self, session: &mut Session) -> Result<CommandResult, String> {
         match self {
             Command::Help => Ok(CommandResult::Continue("Available commands:\n\
                               help - Show this message\n\
                               exit - Exit the program\n\
-                              ripple <bits> <num1> <num2> - Add two binary numbers using ripple carry adder where bits is the maximum number of bits sum can have\n".to_string())),
+                              ripple <bits> <num1> <num2> - Add two binary numbers using ripple carry adder where bits is the maximum number of bits sum can have\n\
+                              sub <bits> <num1> <num2> - Subtract two binary numbers with the add/subtract unit and show the borrow, signed overflow and negative flags\n\
+                              delay <bits> [<num1> <num2>] - Measure how many ticks the ripple carry adder needs to settle, using the worst case carry chain if no numbers are given\n\
+                              bench [<bits>] [<vectors>] - Time the event-driven simulator against the arena engine adding pseudo-random numbers with a ripple carry adder (1024 bits and 20 additions by default)\n\
+                              threads [<bits>] [<threads>] [<vectors>] - Time the multi-threaded engine on one thread against several, multiplying pseudo-random numbers with a Wallace tree multiplier (64 bits, one thread per CPU and 20 products by default)\n\
+                              adders <bits> - Compare gate counts and logic depth of the ripple carry, carry-lookahead, carry-select and carry-save adders\n\
+                              mul <bits> <num1> <num2> - Multiply two binary numbers of up to bits bits with the array and the Wallace tree multiplier and compare their gate counts and logic depth\n\
+                              alu <bits> <op> <num1> <num2> - Run the ALU with op add, sub, and, or, xor, shl, shr or slt (signed less than) and show the result with the zero, carry, overflow and negative flags\n\
+                              load <file> [<circuit>] - Load a netlist or structural Verilog (.v) file, using the last circuit in it unless one is named, or a circuit given as for truth, like counter4, ripple_counter4, register8 or shift_register8\n\
+                              set <net> <0|1> - Drive a net of the loaded circuit\n\
+                              show [<net>] - Settle the loaded circuit and show the level of a net, or of all ports\n\
+                              probe <path> - Settle the loaded circuit and show the level of a net inside it by hierarchical path like fa3.ha1.sum, or of every net below an instance like fa3.ha1\n\
+                              export <file> - Write the loaded circuit as a structural Verilog module\n\
+                              trace [<net>...] - Record level changes of the given nets of the loaded circuit, or of all nets\n\
+                              vcd <file> - Write the recorded level changes as a VCD file for waveform viewers like GTKWave\n\
+                              clock <cycles> [<net>] - Pulse the clock input (clk by default) of the loaded circuit and print its outputs after every rising edge, e.g. after 'load counter4', 'set enable 1', 'set reset 1', 'set reset 0'\n\
+                              truth <circuit> [--undefined] [<expected>] - Print the truth table of a built-in like full_adder, an adder like ripple4, lookahead8, select8 or save8 (three operands), a multiplier like array4 or wallace4, a netlist or Verilog file or the loaded circuit ('loaded') with at most 20 inputs, or compare it with the table in the expected file\n\
+                              expr <expression> - Compile a boolean expression like (a & b) | !c into gates, print the netlist and truth table and load the circuit\n\
+                              minimize <circuit|expression> - Minimise a circuit given as for truth, or an expression, to sums of products, compare gate counts and load the optimised circuit\n\
+                              equiv <circuit> <circuit> [--sat|--bdd] - Check that two circuits given as for truth compute the same outputs, by simulation or with a SAT solver for many inputs, and show inputs on which they differ\n\
+                              bdd <circuit> [--declared] - Build BDDs of the outputs of a circuit given as for truth and show their sizes and how many inputs make them 1, with inputs ordered by a depth-first walk or as declared\n\
+                              faults <circuit> <vectors> - Simulate every stuck-at-0 and stuck-at-1 fault of a combinational gate circuit given as for truth with the input vectors in a file, one line of 0s and 1s in input order each, and show the fault coverage\n\
+                              atpg <circuit> [<coverage>] - Generate input vectors detecting at least the given percentage (100 by default) of the stuck-at faults of a circuit given as for truth, in the format read by faults, and list faults no vector can detect\n".to_string())),
 
             Command::Exit => Ok(CommandResult::Exit),
 
@@ -64,6 +371,635 @@ impl Command {
                 Ok(CommandResult::Continue(format!("Sum: {}\n{}", sum, if overflow { "Overflow occurred!" } else { "" })))
             }
 
+            Command::Subtract { bits, num1, num2 } => {
+                if *bits == 0 {
+                    return Err("Bits must be at least 1".to_string());
+                }
+                to_levels(num1, *bits)?;
+                to_levels(num2, *bits)?;
+                let mut unit = AddSubtractor::new(*bits);
+                let (difference, flags) = unit.calculate(num1, num2, true);
+                // Read as two's complement the sign bit is worth -2^(bits - 1), shown up to 64 bits
+                let signed = match *bits {
+                    1..=64 => {
+                        let unsigned = difference.chars().fold(0i128, |value, c| value * 2 + i128::from(c == '1'));
+                        let value = if flags.negative { unsigned - (1i128 << bits) } else { unsigned };
+                        format!(" ({} signed)", value)
+                    }
+                    _ => String::new(),
+                };
+                let flag = |set: bool| if set { 1 } else { 0 };
+                Ok(CommandResult::Continue(format!(
+                    "Difference: {}{}\nBorrow: {}\nOverflow: {}\nNegative: {}",
+                    difference,
+                    signed,
+                    flag(flags.borrow),
+                    flag(flags.overflow),
+                    flag(flags.negative),
+                )))
+            }
+
+            Command::RippleDelay { bits, operands } => {
+                if *bits == 0 {
+                    return Err("Bits must be at least 1".to_string());
+                }
+                // Without operands, 11..1 + 00..1 makes the carry ripple through every stage
+                let (num1, num2) = operands.clone().unwrap_or_else(|| ("1".repeat(*bits), "1".to_string()));
+                let num1 = to_levels(&num1, *bits)?;
+                let num2 = to_levels(&num2, *bits)?;
+
+                let mut circuit = RippleCarryAdder::new(*bits).into_circuit();
+                circuit.set_delay(1);
+                let mut simulator = Simulator::new(circuit);
+                for i in 0..*bits {
+                    simulator.set_level(&format!("a{}", i), Level::Low).map_err(|e| e.to_string())?;
+                    simulator.set_level(&format!("b{}", i), Level::Low).map_err(|e| e.to_string())?;
+                }
+                let start = simulator.settle().map_err(|e| e.to_string())?;
+
+                for i in 0..*bits {
+                    simulator.set_level(&format!("a{}", i), num1[i]).map_err(|e| e.to_string())?;
+                    simulator.set_level(&format!("b{}", i), num2[i]).map_err(|e| e.to_string())?;
+                }
+                let end = simulator.settle().map_err(|e| e.to_string())?;
+
+                let mut sum = String::new();
+                let mut report = String::new();
+                let outputs = (0..*bits).rev().map(|i| format!("sum{}", i)).chain([format!("carry{}", bits - 1)]);
+                for name in outputs {
+                    let changed = simulator.last_change(&name).map_err(|e| e.to_string())?;
+                    if changed > start {
+                        report.push_str(&format!("{}: settled after {} ticks\n", name, changed - start));
+                    } else {
+                        report.push_str(&format!("{}: unchanged\n", name));
+                    }
+                    if name.starts_with("sum") {
+                        let level = simulator.level(&name).map_err(|e| e.to_string())?;
+                        sum.push(if level == Level::High { '1' } else { '0' });
+                    }
+                }
+                Ok(CommandResult::Continue(format!("Sum: {}\n{}Adder settled after {} ticks\n", sum, report, end - start)))
+            }
+
+            Command::Bench { bits, vectors } => {
+                if *bits == 0 {
+                    return Err("Bits must be at least 1".to_string());
+                }
+                // Operand bits follow a fixed pattern per addition, so runs are comparable
+                let level = |high: bool| if high { Level::High } else { Level::Low };
+                let operands: Vec<Vec<(String, Level)>> = (0..*vectors)
+                    .map(|k| {
+                        (0..*bits)
+                            .flat_map(|i| {
+                                [
+                                    (format!("a{}", i), level((i * 7 + k * 3) % 5 < 2)),
+                                    (format!("b{}", i), level((i * 3 + k) % 4 != 0)),
+                                ]
+                            })
+                            .collect()
+                    })
+                    .collect();
+
+                let circuit = RippleCarryAdder::new(*bits).into_circuit();
+                let outputs = circuit.outputs().to_vec();
+                let mut arena = RippleCarryAdder::arena_circuit(*bits);
+                let mut simulator = Simulator::new(circuit);
+
+                let start = Instant::now();
+                let mut expected = Vec::new();
+                for vector in &operands {
+                    for (name, level) in vector {
+                        simulator.set_level(name, *level).map_err(|e| e.to_string())?;
+                    }
+                    simulator.settle().map_err(|e| e.to_string())?;
+                    for name in &outputs {
+                        expected.push(simulator.level(name).map_err(|e| e.to_string())?);
+                    }
+                }
+                let event_driven = start.elapsed();
+
+                // Names are resolved once up front, as a caller of the arena engine would
+                let operands: Vec<Vec<(NetId, Level)>> = operands
+                    .iter()
+                    .map(|vector| vector.iter().map(|(name, level)| (arena.net(name).unwrap(), *level)).collect())
+                    .collect();
+                let outputs: Vec<NetId> = outputs.iter().map(|name| arena.net(name).unwrap()).collect();
+                let start = Instant::now();
+                let mut levels = Vec::new();
+                for vector in &operands {
+                    for &(net, level) in vector {
+                        arena.set_level(net, level);
+                    }
+                    arena.settle().map_err(|e| e.to_string())?;
+                    levels.extend(outputs.iter().map(|&net| arena.level(net)));
+                }
+                let compiled = start.elapsed();
+                if levels != expected {
+                    return Err("The engines disagree on the sums".to_string());
+                }
+
+                Ok(CommandResult::Continue(format!(
+                    "{} additions on a {}-bit ripple carry adder with {} gates\n\
+                     Event-driven simulator: {:>10.2} ms\n\
+                     Arena engine:           {:>10.2} ms\n\
+                     Speedup:                {:>10.1}x",
+                    vectors,
+                    bits,
+                    arena.component_count(),
+                    event_driven.as_secs_f64() * 1000.0,
+                    compiled.as_secs_f64() * 1000.0,
+                    event_driven.as_secs_f64() / compiled.as_secs_f64().max(f64::EPSILON),
+                )))
+            }
+
+            Command::Threads { bits, threads, vectors } => {
+                if *bits == 0 {
+                    return Err("Bits must be at least 1".to_string());
+                }
+                let threads = match threads {
+                    Some(0) => return Err("Threads must be at least 1".to_string()),
+                    Some(threads) => *threads,
+                    None => thread::available_parallelism().map_or(1, |threads| threads.get()),
+                };
+                let circuit = WallaceMultiplier::new(*bits, *bits).into_circuit();
+                let mut single = ThreadedCircuit::new(&circuit, 1)?;
+                let mut pool = ThreadedCircuit::new(&circuit, threads)?;
+
+                // Operands repeat from run to run, so timings are comparable
+                let operands = RandomVectors::new().vectors(*vectors, circuit.inputs().len());
+
+                let time = |engine: &mut ThreadedCircuit| {
+                    let inputs = engine.inputs().to_vec();
+                    let start = Instant::now();
+                    let mut levels = Vec::new();
+                    for vector in &operands {
+                        for (&net, &level) in inputs.iter().zip(vector) {
+                            engine.set_level(net, level);
+                        }
+                        engine.evaluate();
+                        levels.extend(engine.outputs().iter().map(|&net| engine.level(net)));
+                    }
+                    (start.elapsed(), levels)
+                };
+                let (serial, expected) = time(&mut single);
+                let (parallel, levels) = time(&mut pool);
+                if levels != expected {
+                    return Err("The thread counts disagree on the products".to_string());
+                }
+
+                Ok(CommandResult::Continue(format!(
+                    "{} products on a {}x{}-bit Wallace tree multiplier with {} gates in {} levels\n\
+                     {} gates in {} levels shared between the threads\n\
+                     {:<14}{:>10.2} ms\n\
+                     {:<14}{:>10.2} ms\n\
+                     {:<14}{:>10.1}x",
+                    vectors,
+                    bits,
+                    bits,
+                    pool.component_count(),
+                    pool.depth(),
+                    pool.parallel_gates(),
+                    pool.parallel_levels(),
+                    "1 thread:",
+                    serial.as_secs_f64() * 1000.0,
+                    format!("{} thread{}:", pool.threads(), if pool.threads() == 1 { "" } else { "s" }),
+                    parallel.as_secs_f64() * 1000.0,
+                    "Speedup:",
+                    serial.as_secs_f64() / parallel.as_secs_f64().max(f64::EPSILON),
+                )))
+            }
+
+            Command::Adders { bits } => {
+                if *bits == 0 {
+                    return Err("Bits must be at least 1".to_string());
+                }
+                let mut report = format!("{:<32}{:>8}{:>8}\n", "Adder", "Gates", "Depth");
+                for (_, adder) in ADDERS {
+                    let circuit = adder(*bits);
+                    let depth = analysis::logic_depth(&circuit)?;
+                    let mut name = circuit.name().to_string();
+                    if circuit.inputs().len() == 3 * bits {
+                        name.push_str(" (3 operands)");
+                    }
+                    report.push_str(&format!("{:<32}{:>8}{:>8}\n", name, circuit.component_count(), depth));
+                }
+                Ok(CommandResult::Continue(report))
+            }
+
+            Command::Multiply { bits, num1, num2 } => {
+                if *bits == 0 {
+                    return Err("Bits must be at least 1".to_string());
+                }
+                to_levels(num1, *bits)?;
+                to_levels(num2, *bits)?;
+                let product = ArrayMultiplier::new(*bits, *bits).calculate(num1, num2);
+                let wallace = WallaceMultiplier::new(*bits, *bits).calculate(num1, num2);
+                if wallace != product {
+                    return Err(format!("Multipliers disagree: {} and {}", product, wallace));
+                }
+                let mut report = format!("Product: {}\n{:<32}{:>8}{:>8}\n", product, "Multiplier", "Gates", "Depth");
+                for (_, multiplier) in MULTIPLIERS {
+                    let circuit = multiplier(*bits);
+                    let depth = analysis::logic_depth(&circuit)?;
+                    report.push_str(&format!("{:<32}{:>8}{:>8}\n", circuit.name(), circuit.component_count(), depth));
+                }
+                Ok(CommandResult::Continue(report))
+            }
+
+            Command::Alu { bits, op, num1, num2 } => {
+                if *bits == 0 {
+                    return Err("Bits must be at least 1".to_string());
+                }
+                to_levels(num1, *bits)?;
+                to_levels(num2, *bits)?;
+                let mut nets = Nets::new();
+                let mut alu = ALU::with_width(&mut nets, *bits);
+                let (result, flags) = alu.calculate(&mut nets, *op, num1, num2);
+                let flag = |set: bool| if set { 1 } else { 0 };
+                Ok(CommandResult::Continue(format!(
+                    "Result: {}\nZero: {}\nCarry: {}\nOverflow: {}\nNegative: {}",
+                    result,
+                    flag(flags.zero),
+                    flag(flags.carry),
+                    flag(flags.overflow),
+                    flag(flags.negative),
+                )))
+            }
+
+            Command::Load { path, top } => {
+                let circuit = match top {
+                    // Names of files have an extension, those of generated circuits do not
+                    None if !path.contains('.') && !std::path::Path::new(path).exists() => find_circuit(path)?,
+                    _ => read_circuit(path, top.as_deref())?,
+                };
+                let summary = format!(
+                    "Loaded circuit '{}' with {} components and {} nets\nInputs: {}\nOutputs: {}",
+                    circuit.name(),
+                    circuit.component_count(),
+                    circuit.net_count(),
+                    circuit.inputs().join(", "),
+                    circuit.outputs().join(", "),
+                );
+                session.simulator = Some(Simulator::new(circuit));
+                Ok(CommandResult::Continue(summary))
+            }
+
+            Command::Set { net, level } => {
+                let simulator = session.simulator()?;
+                simulator.set_level(net, *level).map_err(|e| e.to_string())?;
+                // Settle right away so clocked components see every level, e.g. a reset pulse
+                simulator.settle().map_err(|e| e.to_string())?;
+                Ok(CommandResult::Continue(format!("{} set to {:?}", net, level)))
+            }
+
+            Command::Show { net } => {
+                let simulator = session.simulator()?;
+                simulator.settle().map_err(|e| e.to_string())?;
+                let names = match net {
+                    Some(net) => vec![net.clone()],
+                    None => {
+                        let circuit = simulator.circuit();
+                        circuit.inputs().iter().chain(circuit.outputs()).cloned().collect()
+                    }
+                };
+                let mut output = String::new();
+                for name in names {
+                    let level = simulator.level(&name).map_err(|e| e.to_string())?;
+                    output.push_str(&format!("{}: {:?}\n", name, level));
+                }
+                Ok(CommandResult::Continue(output))
+            }
+
+            Command::Probe { path } => {
+                let simulator = session.simulator()?;
+                simulator.settle().map_err(|e| e.to_string())?;
+                let circuit = simulator.circuit();
+                // The path may start with the name of the circuit itself, e.g. adder.fa3.ha1.xor
+                let path = path
+                    .strip_prefix(&format!("{}.", circuit.name()))
+                    .filter(|_| circuit.net(path).is_none() && circuit.nets_under(path).is_empty())
+                    .unwrap_or(path);
+                let names: Vec<String> = match circuit.net(path) {
+                    Some(_) => vec![path.to_string()],
+                    None => circuit.nets_under(path).into_iter().map(String::from).collect(),
+                };
+                if names.is_empty() {
+                    return Err(format!("No net or instance '{}' in {}", path, circuit.name()));
+                }
+                let mut output = String::new();
+                for name in names {
+                    let level = simulator.level(&name).map_err(|e| e.to_string())?;
+                    output.push_str(&format!("{}: {:?}\n", name, level));
+                }
+                Ok(CommandResult::Continue(output))
+            }
+
+            Command::Export { path } => {
+                let source = verilog::export(session.simulator()?.circuit())?;
+                std::fs::write(path, source).map_err(|e| format!("Cannot write {}: {}", path, e))?;
+                Ok(CommandResult::Continue(format!("Wrote {}", path)))
+            }
+
+            Command::Trace { nets } => {
+                let simulator = session.simulator()?;
+                if nets.is_empty() {
+                    simulator.trace_all();
+                }
+                for net in nets {
+                    simulator.trace(net).map_err(|e| e.to_string())?;
+                }
+                Ok(CommandResult::Continue(format!("Tracing {}", simulator.traced().signals().join(", "))))
+            }
+
+            Command::Vcd { path } => {
+                let simulator = session.simulator()?;
+                if simulator.traced().is_empty() {
+                    return Err("No nets are traced, use 'trace' first".to_string());
+                }
+                let mut file = std::fs::File::create(path).map_err(|e| format!("Cannot write {}: {}", path, e))?;
+                simulator.write_vcd(&mut file).map_err(|e| format!("Cannot write {}: {}", path, e))?;
+                Ok(CommandResult::Continue(format!("Wrote {}", path)))
+            }
+
+            Command::Clock { cycles, net } => {
+                let simulator = session.simulator()?;
+                let outputs = simulator.circuit().outputs().to_vec();
+                let row = |simulator: &Simulator, cycle: usize| -> Result<String, String> {
+                    let mut line = format!("{:<5} |", cycle);
+                    for name in &outputs {
+                        let level = simulator.level(name).map_err(|e| e.to_string())?;
+                        line.push_str(&format!(" {:<width$}", level.symbol(), width = name.len()));
+                    }
+                    Ok(line.trim_end().to_string())
+                };
+
+                simulator.settle().map_err(|e| e.to_string())?;
+                let mut report = vec![format!("cycle | {}", outputs.join(" ")), row(simulator, 0)?];
+                for cycle in 1..=*cycles {
+                    for level in [Level::Low, Level::High] {
+                        simulator.set_level(net, level).map_err(|e| e.to_string())?;
+                        simulator.settle().map_err(|e| e.to_string())?;
+                    }
+                    report.push(row(simulator, cycle)?);
+                }
+                Ok(CommandResult::Continue(report.join("\n")))
+            }
+
+            Command::Truth { circuit, undefined, expected } => {
+                let table = if circuit == "loaded" {
+                    TruthTable::generate(session.simulator()?, *undefined).map_err(|e| e.to_string())?
+                } else {
+                    TruthTable::from_circuit(find_circuit(circuit)?, *undefined).map_err(|e| e.to_string())?
+                };
+
+                let Some(path) = expected else {
+                    return Ok(CommandResult::Continue(table.to_string()));
+                };
+                let source = std::fs::read_to_string(path).map_err(|e| format!("Cannot read {}: {}", path, e))?;
+                let expected = TruthTable::parse(&source).map_err(|e| format!("{}: {}", path, e))?;
+                let mismatches = table.compare(&expected)?;
+                if mismatches.is_empty() {
+                    return Ok(CommandResult::Continue(format!("All {} rows match {}", table.rows().len(), path)));
+                }
+                let mut report = format!("{} rows differ from {}:\n", mismatches.len(), path);
+                for mismatch in mismatches {
+                    report.push_str(&format!("{}\n", mismatch));
+                }
+                Ok(CommandResult::Continue(report))
+            }
+
+            Command::Expr { source } => {
+                let expr = expression::parse(source).map_err(|e| e.to_string())?;
+                let circuit = expression::compile(&expr);
+                let netlist = netlist::write(&circuit)?;
+                let gates = circuit.component_count();
+                let mut simulator = Simulator::new(circuit);
+                let table = TruthTable::generate(&mut simulator, false).map_err(|e| e.to_string())?;
+                session.simulator = Some(simulator);
+                Ok(CommandResult::Continue(format!("{}\nGates: {}\n\n{}", netlist, gates, table)))
+            }
+
+            Command::Minimize { source } => {
+                let (name, table, before) = if source == "loaded" {
+                    let simulator = session.simulator()?;
+                    let gates = simulator.circuit().component_count();
+                    let name = simulator.circuit().name().to_string();
+                    (name, TruthTable::generate(simulator, false).map_err(|e| e.to_string())?, gates)
+                } else {
+                    let circuit = match find_circuit(source) {
+                        Ok(circuit) => circuit,
+                        // Anything that is not a circuit is read as an expression
+                        Err(error) => match expression::parse(source) {
+                            Ok(expr) => expression::compile(&expr),
+                            Err(_) => return Err(error),
+                        },
+                    };
+                    let gates = circuit.component_count();
+                    let name = circuit.name().to_string();
+                    (name, TruthTable::from_circuit(circuit, false).map_err(|e| e.to_string())?, gates)
+                };
+
+                let expressions = analysis::minimize_table(&table);
+                let circuit = expression::compile_outputs(&format!("{}_min", name), table.inputs(), &expressions)?;
+                let mut report = String::new();
+                for (output, expr) in &expressions {
+                    report.push_str(&format!("{} = {}\n", output, expr));
+                }
+                report.push_str(&format!("Gates before: {}\nGates after: {}", before, circuit.component_count()));
+                session.simulator = Some(Simulator::new(circuit));
+                Ok(CommandResult::Continue(report))
+            }
+
+            Command::Equiv { first, second, method } => {
+                let first = find_circuit(first)?;
+                let second = find_circuit(second)?;
+                let method = method.unwrap_or(if first.inputs().len() > analysis::equivalence::MAX_EXHAUSTIVE_INPUTS {
+                    Method::Sat
+                } else {
+                    Method::Exhaustive
+                });
+                let result = match analysis::check_equivalence_with(first, second, method)? {
+                    None => format!(
+                        "Equivalent ({})",
+                        match method {
+                            Method::Exhaustive => "all inputs simulated",
+                            Method::Sat => "proved with SAT",
+                            Method::Bdd => "proved with BDDs",
+                        }
+                    ),
+                    Some(counterexample) => format!("Not equivalent: {}", counterexample),
+                };
+                Ok(CommandResult::Continue(result))
+            }
+
+            Command::Bdd { circuit, declared } => {
+                let circuit = find_circuit(circuit)?;
+                let order = if *declared { VariableOrder::Declaration } else { VariableOrder::DepthFirst };
+                let symbolic = SymbolicCircuit::new(&circuit, &order)?;
+                let mut report = format!("Variable order: {}\n", symbolic.variables.join(" "));
+                for (name, output) in &symbolic.outputs {
+                    let count = match symbolic.bdd.count(*output) {
+                        Some(count) => count.to_string(),
+                        None => "2^128 or more".to_string(),
+                    };
+                    report.push_str(&format!(
+                        "{}: {} nodes, 1 for {} of 2^{} inputs\n",
+                        name,
+                        symbolic.bdd.size(*output),
+                        count,
+                        symbolic.variables.len()
+                    ));
+                }
+                report.push_str(&format!("Nodes in total: {}", symbolic.bdd.node_count()));
+                Ok(CommandResult::Continue(report))
+            }
+
+            Command::Faults { circuit, path } => {
+                let circuit = find_circuit(circuit)?;
+                let vectors = read_vectors(path)?;
+                let report = analysis::simulate_faults(&circuit, &vectors).map_err(|e| format!("{}: {}", path, e))?;
+                Ok(CommandResult::Continue(report.to_string()))
+            }
+
+            Command::Atpg { circuit, coverage } => {
+                let circuit = find_circuit(circuit)?;
+                let tests = analysis::generate_tests(&circuit, coverage / 100.0)?;
+                let mut report = format!("# {}\n", circuit.inputs().join(" "));
+                for vector in &tests.vectors {
+                    report.extend(vector.iter().map(|level| level.symbol()));
+                    report.push('\n');
+                }
+                report.push_str(&format!("# {}", tests.report));
+                if !tests.redundant.is_empty() {
+                    let redundant: Vec<String> = tests.redundant.iter().map(|fault| fault.to_string()).collect();
+                    report.push_str(&format!("\n# Undetectable: {}", redundant.join(", ")));
+                }
+                Ok(CommandResult::Continue(report))
+            }
+
         }
     }
-}
\ No newline at end of file
+}
+
+// Load a netlist or, for files ending in .v, a structural Verilog file
+fn read_circuit(path: &str, top: Option<&str>) -> Result<Circuit, String> {
+    let source = std::fs::read_to_string(path).map_err(|e| format!("Cannot read {}: {}", path, e))?;
+    let circuit = if path.ends_with(".v") {
+        verilog::import(&source, top)
+    } else {
+        netlist::load(&source, top)
+    };
+    circuit.map_err(|e| format!("{}: {}", path, e))
+}
+
+// Read input vectors, one per line of 0s and 1s with optional spaces and # comments
+fn read_vectors(path: &str) -> Result<Vec<Vec<Level>>, String> {
+    let source = std::fs::read_to_string(path).map_err(|e| format!("Cannot read {}: {}", path, e))?;
+    source
+        .lines()
+        .enumerate()
+        .map(|(number, line)| (number + 1, line.split('#').next().unwrap()))
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(number, line)| {
+            line.chars()
+                .filter(|c| !c.is_whitespace())
+                .map(|c| match c {
+                    '0' => Ok(Level::Low),
+                    '1' => Ok(Level::High),
+                    _ => Err(format!("{}: line {}: '{}' is not 0 or 1", path, number, c)),
+                })
+                .collect()
+        })
+        .collect()
+}
+
+// Builds an arithmetic circuit of the given width
+type WidthBuilder = fn(usize) -> Circuit;
+
+// Adders by the prefix naming them together with their width, like ripple16
+const ADDERS: &[(&str, WidthBuilder)] = &[
+    ("ripple", |bits| RippleCarryAdder::new(bits).into_circuit()),
+    ("lookahead", |bits| CarryLookaheadAdder::new(bits).into_circuit()),
+    ("select", |bits| CarrySelectAdder::new(bits).into_circuit()),
+    ("save", |bits| CarrySaveAdder::new(bits).into_circuit()),
+];
+
+// Multipliers of two numbers of the given width, like array8
+const MULTIPLIERS: &[(&str, WidthBuilder)] = &[
+    ("array", |bits| ArrayMultiplier::new(bits, bits).into_circuit()),
+    ("wallace", |bits| WallaceMultiplier::new(bits, bits).into_circuit()),
+];
+
+// Registers and counters of the given width, like counter8
+const SEQUENTIAL: &[(&str, WidthBuilder)] = &[
+    ("register", Register::circuit),
+    ("counter", SynchronousCounter::circuit),
+    ("ripple_counter", RippleCounter::circuit),
+    ("shift_register", ShiftRegister::circuit),
+];
+
+// Circuit from a netlist or Verilog file, an adder like ripple4 or lookahead16, a multiplier
+// like wallace8, a register or counter like counter4 or a built-in
+fn find_circuit(name: &str) -> Result<Circuit, String> {
+    if std::path::Path::new(name).is_file() {
+        return read_circuit(name, None);
+    }
+    for (prefix, adder) in ADDERS.iter().chain(MULTIPLIERS).chain(SEQUENTIAL) {
+        if let Some(bits) = name.strip_prefix(prefix).and_then(|bits| bits.parse::<usize>().ok()) {
+            if bits == 0 {
+                return Err("Bits must be at least 1".to_string());
+            }
+            return Ok(adder(bits));
+        }
+    }
+    netlist::load_builtin(name).map_err(|e| e.message)
+}
+
+// Convert a binary string into levels, least significant bit first
+fn to_levels(binary: &str, bits: usize) -> Result<Vec<Level>, String> {
+    if binary.len() > bits {
+        return Err(format!("{} does not fit into {} bits", binary, bits));
+    }
+    format!("{:0>width$}", binary, width = bits)
+        .chars()
+        .rev()
+        .map(|c| match c {
+            '0' => Ok(Level::Low),
+            '1' => Ok(Level::High),
+            _ => Err(format!("{} is not a binary number", binary)),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run(session: &mut Session, input: &str) -> String {
+        match Command::parse(input).unwrap().execute(session).unwrap() {
+            CommandResult::Continue(output) => output,
+            CommandResult::Exit => panic!("{} exited", input),
+        }
+    }
+
+    #[test]
+    fn test_clock_counter() {
+        // The sequence from the help text of clock
+        let mut session = Session::new();
+        for input in ["load counter4", "set enable 1", "set reset 1", "set reset 0"] {
+            run(&mut session, input);
+        }
+        let report = run(&mut session, "clock 5");
+        let counts: Vec<&str> = report.lines().skip(1).map(|line| line.split('|').nth(1).unwrap().trim()).collect();
+        assert_eq!(counts, ["0  0  0  0", "1  0  0  0", "0  1  0  0", "1  1  0  0", "0  0  1  0", "1  0  1  0"]);
+    }
+
+    #[test]
+    fn test_threads() {
+        let mut session = Session::new();
+        let report = run(&mut session, "threads 32 2 3");
+        assert!(report.contains("2 threads:"), "{}", report);
+        assert!(!report.contains("\n0 gates"), "{}", report);
+        assert!(Command::parse("threads 32 0").unwrap().execute(&mut session).is_err());
+    }
+}
@@ -0,0 +1,17 @@
+use digital_logic_circuit_simulator::simulation::Simulator;
+
+// State kept between commands, e.g. the circuit loaded with `load`
+#[derive(Default)]
+pub struct Session {
+    pub simulator: Option<Simulator>,
+}
+
+impl Session {
+    pub fn new() -> Session {
+        Session::default()
+    }
+
+    pub fn simulator(&mut self) -> Result<&mut Simulator, String> {
+        self.simulator.as_mut().ok_or_else(|| "No circuit loaded, use 'load <file>' first".to_string())
+    }
+}
@@ -1,7 +1,9 @@
 pub mod connection;
 pub mod component;
 pub mod gates;
+pub mod nets;
+pub mod sequential;
 
-pub use connection::{Connection, Level};
+pub use connection::{Connection, Level, Resolution};
 pub use component::{BaseComponent, Component};
-
+pub use nets::{ComponentId, NetId, Nets};
@@ -1,52 +1,126 @@
-use std::rc::Rc;
-use std::cell::RefCell;
 use std::fmt;
-use crate::components::Connection;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use crate::components::{Level, NetId, Nets};
+use crate::components::gates::GateKind;
 
-pub trait Component : fmt::Display {
-    fn update(&mut self);
+pub trait Component : fmt::Display + Send {
+    // Read the inputs from the nets and drive the outputs
+    fn update(&mut self, nets: &mut Nets);
+
+    // Nets the component reads from
+    fn inputs(&self) -> &[NetId];
+
+    // Nets the component drives
+    fn outputs(&self) -> &[NetId];
+
+    // Break the component down into the primitives it is built from.
+    // Primitives return themselves.
+    fn flatten(self: Box<Self>) -> Vec<Box<dyn Component>>;
+
+    // Propagation delay in simulated ticks
+    fn delay(&self) -> u64 {
+        0
+    }
+
+    fn set_delay(&mut self, _delay: u64) {}
+
+    // Kind of a primitive logic gate, None for every other component
+    fn gate_kind(&self) -> Option<GateKind> {
+        None
+    }
+
+    // Named nets inside the component, by path relative to it (e.g. `ha1.sum`).
+    // Components without a hierarchy have none.
+    fn named_nets(&self) -> Vec<(String, NetId)> {
+        Vec::new()
+    }
 }
 
+// Source of the ids components use to identify themselves as drivers of a connection
+static NEXT_ID: AtomicUsize = AtomicUsize::new(0);
+
 #[derive(PartialEq)]
 pub struct BaseComponent {
+    id: usize,
     name: String,
-    inputs: Vec<Rc<RefCell<Connection>>>,
-    outputs: Vec<Rc<RefCell<Connection>>>,
+    inputs: Vec<NetId>,
+    outputs: Vec<NetId>,
+    delay: u64,
 }
 
 impl BaseComponent {
     pub fn new(name: &str) -> BaseComponent {
         BaseComponent {
+            id: NEXT_ID.fetch_add(1, Ordering::Relaxed),
             name: String::from(name),
             inputs: Vec::new(),
             outputs: Vec::new(),
+            delay: 0,
         }
     }
 
 
-    pub fn add_input(&mut self, input: Rc<RefCell<Connection>>) {
+    pub fn id(&self) -> usize {
+        self.id
+    }
+
+    pub fn add_input(&mut self, input: NetId) {
         self.inputs.push(input);
     }
 
-    pub fn add_output(&mut self, output: Rc<RefCell<Connection>>) {
+    pub fn add_output(&mut self, output: NetId) {
         self.outputs.push(output);
     }
     
-    pub fn get_input(&self, index: usize) -> Option<Rc<RefCell<Connection>>> {
-        self.inputs.get(index).cloned()
+    pub fn get_input(&self, index: usize) -> Option<NetId> {
+        self.inputs.get(index).copied()
+    }
+
+    pub fn get_output(&self, index: usize) -> Option<NetId> {
+        self.outputs.get(index).copied()
+    }
+
+    // Level of an input, Undefined if it is not connected or floating
+    pub fn input_level(&self, nets: &Nets, index: usize) -> Level {
+        match self.inputs.get(index).map(|&net| nets.level(net)) {
+            Some(Level::HighZ) | None => Level::Undefined,
+            Some(level) => level,
+        }
     }
 
-    pub fn get_output(&self, index: usize) -> Option<Rc<RefCell<Connection>>> {
-        self.outputs.get(index).cloned()
+    // Drive an output. Components only drive outputs they were given, so a missing
+    // one is a wiring bug in the component.
+    pub fn set_output_level(&self, nets: &mut Nets, index: usize, level: Level) {
+        debug_assert!(index < self.outputs.len(), "Output {} not found for {}", index, self.name);
+        if let Some(&output) = self.outputs.get(index) {
+            nets[output].drive(self.id, level);
+        }
+    }
+
+    pub fn inputs(&self) -> &[NetId] {
+        &self.inputs
+    }
+
+    pub fn outputs(&self) -> &[NetId] {
+        &self.outputs
     }
     
+    pub fn delay(&self) -> u64 {
+        self.delay
+    }
+
+    pub fn set_delay(&mut self, delay: u64) {
+        self.delay = delay;
+    }
+
+    // The nets the component is wired to; their levels live in the Nets arena
     pub fn display(&self, f: &mut fmt::Formatter) -> fmt::Result {
         writeln!(f, "Component: {}", self.name)?;
         for (i, input) in self.inputs.iter().enumerate() {
-            writeln!(f, "Input {}: Level = {}", i, input.borrow().level())?;
+            writeln!(f, "Input {}: net {}", i, input.index())?;
         }
         for (i, output) in self.outputs.iter().enumerate() {
-            writeln!(f, "Output {}: Level = {}", i, output.borrow().level())?;
+            writeln!(f, "Output {}: net {}", i, output.index())?;
         }
         Ok(())
     }
@@ -68,13 +142,14 @@ mod tests {
         assert_eq!(base_component.name, "Base Component");
         assert_eq!(base_component.inputs.len(), 0);
         assert_eq!(base_component.outputs.len(), 0);
+        assert_eq!(base_component.delay, 0);
     }
     
     #[test]
     fn test_add_input() {
         let mut base_component = BaseComponent::new("Base Component");
-        let connection = Rc::new(RefCell::new(Connection::new()));
-        base_component.add_input(connection.clone());
+        let connection = Nets::new().add();
+        base_component.add_input(connection);
         assert_eq!(base_component.inputs.len(), 1);
         assert_eq!(base_component.inputs[0], connection);
     }
@@ -82,8 +157,8 @@ mod tests {
     #[test]
     fn test_add_output() {
         let mut base_component = BaseComponent::new("Base Component");
-        let connection = Rc::new(RefCell::new(Connection::new()));
-        base_component.add_output(connection.clone());
+        let connection = Nets::new().add();
+        base_component.add_output(connection);
         assert_eq!(base_component.outputs.len(), 1);
         assert_eq!(base_component.outputs[0], connection);
     }
@@ -91,16 +166,69 @@ mod tests {
     #[test]
     fn test_get_input() {
         let mut base_component = BaseComponent::new("Base Component");
-        let connection = Rc::new(RefCell::new(Connection::new()));
-        base_component.add_input(connection.clone());
+        let connection = Nets::new().add();
+        base_component.add_input(connection);
         assert_eq!(base_component.get_input(0).unwrap(), connection);
     }
     
     #[test]
     fn test_get_output() {
         let mut base_component = BaseComponent::new("Base Component");
-        let connection = Rc::new(RefCell::new(Connection::new()));
-        base_component.add_output(connection.clone());
+        let connection = Nets::new().add();
+        base_component.add_output(connection);
         assert_eq!(base_component.get_output(0).unwrap(), connection);
     }
+
+    #[test]
+    fn test_set_delay() {
+        let mut base_component = BaseComponent::new("Base Component");
+        base_component.set_delay(3);
+        assert_eq!(base_component.delay(), 3);
+    }
+
+    #[test]
+    fn test_input_and_output_levels() {
+        let mut base_component = BaseComponent::new("Base Component");
+        let mut nets = Nets::new();
+        let input = nets.add();
+        let output = nets.add();
+        base_component.add_input(input);
+        base_component.add_output(output);
+
+        nets.set_level(input, Level::High);
+        assert_eq!(base_component.input_level(&nets, 0), Level::High);
+        assert_eq!(base_component.input_level(&nets, 1), Level::Undefined);
+        nets.set_level(input, Level::HighZ);
+        assert_eq!(base_component.input_level(&nets, 0), Level::Undefined);
+
+        base_component.set_output_level(&mut nets, 0, Level::Low);
+        assert_eq!(nets.level(output), Level::Low);
+        assert_eq!(nets[output].driver_level(base_component.id()), Some(Level::Low));
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(expected = "Output 1 not found for Base Component")]
+    fn test_missing_output() {
+        let mut base_component = BaseComponent::new("Base Component");
+        let mut nets = Nets::new();
+        base_component.add_output(nets.add());
+        base_component.set_output_level(&mut nets, 1, Level::Low);
+    }
+
+    #[test]
+    fn test_unique_ids() {
+        let first = BaseComponent::new("First");
+        let second = BaseComponent::new("Second");
+        assert_ne!(first.id(), second.id());
+    }
+
+    #[test]
+    fn test_display() {
+        let mut base_component = BaseComponent::new("Base Component");
+        let mut nets = Nets::new();
+        base_component.add_input(nets.add());
+        base_component.add_output(nets.add());
+        assert_eq!(base_component.to_string(), "Component: Base Component\nInput 0: net 0\nOutput 0: net 1\n");
+    }
 }
@@ -1,28 +1,154 @@
 use core::fmt;
+use std::ops::Not;
 
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub enum Level {
     Undefined,
     High,
     Low,
+    // Not driven, e.g. a disabled tri-state output
+    HighZ,
 }
 
+// How a net with several drivers combines their levels.
+// Drivers that are HighZ are ignored by every mode.
 #[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Resolution {
+    // Only one driver may be active, opposing drivers are a contention
+    Exclusive,
+    // Low wins, like open-collector outputs with a pull-up
+    WiredAnd,
+    // High wins, like open-emitter outputs with a pull-down
+    WiredOr,
+}
+
+#[derive(Debug, Clone, PartialEq)]
 pub struct Connection {
     level: Level,
+    resolution: Resolution,
+    drivers: Vec<(usize, Level)>,
+    contention: bool,
 }
 
 impl Connection {
     pub fn new() -> Connection {
-        Connection { level: Level::Undefined }
+        Connection::with_resolution(Resolution::Exclusive)
+    }
+
+    pub fn with_resolution(resolution: Resolution) -> Connection {
+        Connection {
+            level: Level::Undefined,
+            resolution,
+            drivers: Vec::new(),
+            contention: false,
+        }
     }
 
     pub fn level(&self) -> Level {
         self.level
     }
 
-   pub fn set_level(&mut self, level: Level) {
+    // Force the net to a level, e.g. from a test bench.
+    // The level holds until one of the drivers updates the net again.
+    pub fn set_level(&mut self, level: Level) {
         self.level = level;
+        self.drivers.clear();
+        self.contention = false;
+    }
+
+    // Set the level the given driver puts onto the net and resolve all drivers
+    pub fn drive(&mut self, driver: usize, level: Level) {
+        match self.drivers.iter_mut().find(|(id, _)| *id == driver) {
+            Some(entry) => entry.1 = level,
+            None => self.drivers.push((driver, level)),
+        }
+        self.resolve();
+    }
+
+    pub fn driver_level(&self, driver: usize) -> Option<Level> {
+        self.drivers.iter().find(|(id, _)| *id == driver).map(|&(_, level)| level)
+    }
+
+    pub fn drivers(&self) -> &[(usize, Level)] {
+        &self.drivers
+    }
+
+    pub fn resolution(&self) -> Resolution {
+        self.resolution
+    }
+
+    pub fn set_resolution(&mut self, resolution: Resolution) {
+        self.resolution = resolution;
+        if !self.drivers.is_empty() {
+            self.resolve();
+        }
+    }
+
+    // True if active drivers put opposite levels onto an exclusive net
+    pub fn has_contention(&self) -> bool {
+        self.contention
+    }
+
+    fn resolve(&mut self) {
+        let active: Vec<Level> = self
+            .drivers
+            .iter()
+            .map(|&(_, level)| level)
+            .filter(|&level| level != Level::HighZ)
+            .collect();
+        let high = active.contains(&Level::High);
+        let low = active.contains(&Level::Low);
+        let undefined = active.contains(&Level::Undefined);
+
+        self.contention = self.resolution == Resolution::Exclusive && high && low;
+        self.level = match self.resolution {
+            _ if active.is_empty() => Level::HighZ,
+            Resolution::WiredAnd if low => Level::Low,
+            Resolution::WiredOr if high => Level::High,
+            _ if undefined || (high && low) => Level::Undefined,
+            _ if high => Level::High,
+            _ => Level::Low,
+        };
+    }
+}
+
+impl Default for Connection {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Level {
+    // Single character as used in truth tables and waveforms
+    pub fn symbol(self) -> char {
+        match self {
+            Level::Low => '0',
+            Level::High => '1',
+            Level::Undefined => 'x',
+            Level::HighZ => 'z',
+        }
+    }
+
+    pub fn from_symbol(symbol: char) -> Option<Level> {
+        match symbol {
+            '0' => Some(Level::Low),
+            '1' => Some(Level::High),
+            'x' | 'X' => Some(Level::Undefined),
+            'z' | 'Z' => Some(Level::HighZ),
+            _ => None,
+        }
+    }
+}
+
+impl Not for Level {
+    type Output = Level;
+
+    fn not(self) -> Level {
+        match self {
+            Level::High => Level::Low,
+            Level::Low => Level::High,
+            Level::Undefined | Level::HighZ => Level::Undefined,
+        }
     }
 }
 
@@ -32,6 +158,7 @@ impl fmt::Display for Level {
             Level::Undefined => write!(f, "Undefined"),
             Level::High => write!(f, "High"),
             Level::Low => write!(f, "Low"),
+            Level::HighZ => write!(f, "HighZ"),
         }
     }
 }
@@ -78,5 +205,78 @@ mod tests {
         assert_eq!(format!("{}", Level::Undefined), "Undefined");
         assert_eq!(format!("{}", Level::High), "High");
         assert_eq!(format!("{}", Level::Low), "Low");
+        assert_eq!(format!("{}", Level::HighZ), "HighZ");
+    }
+
+    #[test]
+    fn test_level_symbols() {
+        for level in [Level::Low, Level::High, Level::Undefined, Level::HighZ] {
+            assert_eq!(Level::from_symbol(level.symbol()), Some(level));
+        }
+        assert_eq!(Level::from_symbol('X'), Some(Level::Undefined));
+        assert_eq!(Level::from_symbol('2'), None);
+    }
+
+    #[test]
+    fn test_not_level() {
+        assert_eq!(!Level::High, Level::Low);
+        assert_eq!(!Level::Low, Level::High);
+        assert_eq!(!Level::Undefined, Level::Undefined);
+        assert_eq!(!Level::HighZ, Level::Undefined);
+    }
+
+    #[test]
+    fn test_single_driver() {
+        let mut connection = Connection::new();
+        connection.drive(1, Level::High);
+        assert_eq!(connection.level(), Level::High);
+        connection.drive(1, Level::Low);
+        assert_eq!(connection.level(), Level::Low);
+        assert_eq!(connection.driver_level(1), Some(Level::Low));
+        assert_eq!(connection.drivers().len(), 1);
+    }
+
+    #[test]
+    fn test_set_level_overrides_drivers() {
+        let mut connection = Connection::new();
+        connection.drive(1, Level::High);
+        connection.set_level(Level::Low);
+        assert_eq!(connection.level(), Level::Low);
+        assert!(connection.drivers().is_empty());
+    }
+
+    #[test]
+    fn test_exclusive_resolution() {
+        let mut connection = Connection::new();
+        connection.drive(1, Level::HighZ);
+        connection.drive(2, Level::HighZ);
+        assert_eq!(connection.level(), Level::HighZ);
+
+        connection.drive(2, Level::High);
+        assert_eq!(connection.level(), Level::High);
+        assert!(!connection.has_contention());
+
+        connection.drive(1, Level::Low);
+        assert_eq!(connection.level(), Level::Undefined);
+        assert!(connection.has_contention());
+
+        connection.drive(1, Level::Undefined);
+        assert_eq!(connection.level(), Level::Undefined);
+        assert!(!connection.has_contention());
+    }
+
+    #[test]
+    fn test_wired_resolution() {
+        let mut connection = Connection::with_resolution(Resolution::WiredAnd);
+        connection.drive(1, Level::High);
+        connection.drive(2, Level::Low);
+        assert_eq!(connection.level(), Level::Low);
+        assert!(!connection.has_contention());
+
+        connection.set_resolution(Resolution::WiredOr);
+        assert_eq!(connection.level(), Level::High);
+
+        connection.drive(1, Level::Undefined);
+        assert_eq!(connection.level(), Level::Undefined);
     }
 }
@@ -1,7 +1,21 @@
+pub mod logic_gate;
 pub mod or_gate;
 pub mod and_gate;
 pub mod xor_gate;
+pub mod nand_gate;
+pub mod nor_gate;
+pub mod xnor_gate;
+pub mod not_gate;
+pub mod buffer;
+pub mod tri_state_buffer;
 
+pub use crate::components::gates::logic_gate::{GateKind, GateType, LogicGate, MultiInput};
 pub use crate::components::gates::or_gate::ORGate;
 pub use crate::components::gates::and_gate::ANDGate;
 pub use crate::components::gates::xor_gate::XORGate;
+pub use crate::components::gates::nand_gate::NANDGate;
+pub use crate::components::gates::nor_gate::NORGate;
+pub use crate::components::gates::xnor_gate::XNORGate;
+pub use crate::components::gates::not_gate::NOTGate;
+pub use crate::components::gates::buffer::Buffer;
+pub use crate::components::gates::tri_state_buffer::TriStateBuffer;
@@ -0,0 +1,41 @@
+use crate::components::NetId;
+use crate::components::gates::logic_gate::{GateKind, GateType, LogicGate};
+
+pub struct Buf;
+
+impl GateType for Buf {
+    const KIND: GateKind = GateKind::Buffer;
+}
+
+pub type Buffer = LogicGate<Buf>;
+
+impl Buffer {
+    pub fn new(input: NetId, output: NetId) -> Buffer {
+        LogicGate::with_connections(vec![input], output)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::components::{Component, Level, Nets};
+
+    #[test]
+    fn test_update_buffer() {
+        let mut nets = Nets::new();
+        let input = nets.add();
+        let output = nets.add();
+        let mut buffer = Buffer::new(input, output);
+
+        for (input_level, expected_output) in [
+            (Level::Low, Level::Low),
+            (Level::High, Level::High),
+            (Level::Undefined, Level::Undefined),
+            (Level::HighZ, Level::Undefined),
+        ] {
+            nets.set_level(input, input_level);
+            buffer.update(&mut nets);
+            assert_eq!(nets.level(output), expected_output);
+        }
+    }
+}
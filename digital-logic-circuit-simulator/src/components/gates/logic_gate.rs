@@ -0,0 +1,221 @@
+use core::fmt;
+use std::marker::PhantomData;
+use crate::components::{Level, BaseComponent, Component, NetId, Nets};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum GateKind {
+    And,
+    Or,
+    Xor,
+    Nand,
+    Nor,
+    Xnor,
+    Not,
+    Buffer,
+    // Inputs are data and enable
+    TriState,
+}
+
+impl GateKind {
+    pub fn name(self) -> &'static str {
+        match self {
+            GateKind::And => "AND Gate",
+            GateKind::Or => "OR Gate",
+            GateKind::Xor => "XOR Gate",
+            GateKind::Nand => "NAND Gate",
+            GateKind::Nor => "NOR Gate",
+            GateKind::Xnor => "XNOR Gate",
+            GateKind::Not => "NOT Gate",
+            GateKind::Buffer => "Buffer",
+            GateKind::TriState => "Tri-State Buffer",
+        }
+    }
+
+    // Truth function of the gate. An undefined (or floating) input only makes the output
+    // undefined if the result depends on it, e.g. any Low input forces AND to Low.
+    pub fn evaluate(self, inputs: &[Level]) -> Level {
+        let defined = |level: Level| level == Level::High || level == Level::Low;
+        match self {
+            GateKind::TriState => match inputs.get(1).copied().unwrap_or(Level::Undefined) {
+                Level::High => GateKind::Buffer.evaluate(&inputs[..1]),
+                Level::Low => Level::HighZ,
+                _ => Level::Undefined,
+            },
+            GateKind::Not | GateKind::Nand | GateKind::Nor | GateKind::Xnor => !self.inverse().evaluate(inputs),
+            _ if inputs.is_empty() => Level::Undefined,
+            GateKind::And | GateKind::Or => {
+                let dominant = if self == GateKind::And { Level::Low } else { Level::High };
+                if inputs.contains(&dominant) {
+                    dominant
+                } else if inputs.iter().all(|&level| defined(level)) {
+                    !dominant
+                } else {
+                    Level::Undefined
+                }
+            }
+            GateKind::Xor => {
+                if !inputs.iter().all(|&level| defined(level)) {
+                    Level::Undefined
+                } else if inputs.iter().filter(|&&level| level == Level::High).count() % 2 == 1 {
+                    Level::High
+                } else {
+                    Level::Low
+                }
+            }
+            GateKind::Buffer => match inputs[0] {
+                Level::HighZ => Level::Undefined,
+                level => level,
+            },
+        }
+    }
+
+    // Gate kind whose output the inverting gates negate
+    fn inverse(self) -> GateKind {
+        match self {
+            GateKind::Nand => GateKind::And,
+            GateKind::Nor => GateKind::Or,
+            GateKind::Xnor => GateKind::Xor,
+            GateKind::Not => GateKind::Buffer,
+            other => other,
+        }
+    }
+}
+
+// Marker for the kind of a LogicGate
+pub trait GateType: Send + 'static {
+    const KIND: GateKind;
+}
+
+// Gates that accept any number of inputs
+pub trait MultiInput: GateType {}
+
+// A primitive gate whose output is the truth function of its kind applied to its inputs
+pub struct LogicGate<T: GateType> {
+    base: BaseComponent,
+    gate_type: PhantomData<T>,
+}
+
+impl<T: GateType> LogicGate<T> {
+    pub(crate) fn with_connections(inputs: Vec<NetId>, output: NetId) -> LogicGate<T> {
+        let mut gate = LogicGate {
+            base: BaseComponent::new(T::KIND.name()),
+            gate_type: PhantomData,
+        };
+        for input in inputs {
+            gate.base.add_input(input);
+        }
+        gate.base.add_output(output);
+        gate
+    }
+
+    pub fn with_delay(mut self, delay: u64) -> LogicGate<T> {
+        self.base.set_delay(delay);
+        self
+    }
+
+    pub fn kind(&self) -> GateKind {
+        T::KIND
+    }
+}
+
+impl<T: MultiInput> LogicGate<T> {
+    // Gate with an arbitrary fan-in, e.g. an 8-input AND for address decoding
+    pub fn with_inputs(inputs: Vec<NetId>, output: NetId) -> LogicGate<T> {
+        LogicGate::with_connections(inputs, output)
+    }
+}
+
+impl<T: GateType> Component for LogicGate<T> {
+    fn update(&mut self, nets: &mut Nets) {
+        let levels: Vec<Level> = (0..self.base.inputs().len())
+            .map(|i| self.base.input_level(nets, i))
+            .collect();
+        self.base.set_output_level(nets, 0, T::KIND.evaluate(&levels));
+    }
+
+    fn inputs(&self) -> &[NetId] {
+        self.base.inputs()
+    }
+
+    fn outputs(&self) -> &[NetId] {
+        self.base.outputs()
+    }
+
+    fn flatten(self: Box<Self>) -> Vec<Box<dyn Component>> {
+        vec![self]
+    }
+
+    fn delay(&self) -> u64 {
+        self.base.delay()
+    }
+
+    fn set_delay(&mut self, delay: u64) {
+        self.base.set_delay(delay);
+    }
+
+    fn gate_kind(&self) -> Option<GateKind> {
+        Some(T::KIND)
+    }
+}
+
+impl<T: GateType> fmt::Display for LogicGate<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.base.display(f)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_evaluate_wide_inputs() {
+        let levels = [Level::High, Level::High, Level::Low];
+        assert_eq!(GateKind::And.evaluate(&levels), Level::Low);
+        assert_eq!(GateKind::Nand.evaluate(&levels), Level::High);
+        assert_eq!(GateKind::Or.evaluate(&levels), Level::High);
+        assert_eq!(GateKind::Xor.evaluate(&levels), Level::Low);
+        assert_eq!(GateKind::Xnor.evaluate(&levels), Level::High);
+    }
+
+    #[test]
+    fn test_evaluate_undefined_inputs() {
+        let levels = [Level::Undefined, Level::High, Level::Low];
+        assert_eq!(GateKind::And.evaluate(&levels), Level::Low);
+        assert_eq!(GateKind::Nand.evaluate(&levels), Level::High);
+        assert_eq!(GateKind::Or.evaluate(&levels), Level::High);
+        assert_eq!(GateKind::Nor.evaluate(&levels), Level::Low);
+        assert_eq!(GateKind::Xor.evaluate(&levels), Level::Undefined);
+        assert_eq!(GateKind::And.evaluate(&[Level::Undefined, Level::High]), Level::Undefined);
+        assert_eq!(GateKind::Or.evaluate(&[Level::Undefined, Level::Low]), Level::Undefined);
+    }
+
+    #[test]
+    fn test_with_inputs() {
+        use crate::components::gates::ANDGate;
+
+        let mut nets = Nets::new();
+        let address: Vec<_> = nets.add_many(8);
+        let output = nets.add();
+        let mut and_gate = ANDGate::with_inputs(address.clone(), output);
+        assert_eq!(and_gate.inputs().len(), 8);
+
+        for &line in &address {
+            nets.set_level(line, Level::High);
+        }
+        and_gate.update(&mut nets);
+        assert_eq!(nets.level(output), Level::High);
+
+        nets.set_level(address[5], Level::Low);
+        and_gate.update(&mut nets);
+        assert_eq!(nets.level(output), Level::Low);
+    }
+
+    #[test]
+    fn test_evaluate_high_impedance_input() {
+        assert_eq!(GateKind::And.evaluate(&[Level::High, Level::HighZ]), Level::Undefined);
+        assert_eq!(GateKind::And.evaluate(&[Level::Low, Level::HighZ]), Level::Low);
+        assert_eq!(GateKind::Not.evaluate(&[Level::HighZ]), Level::Undefined);
+        assert_eq!(GateKind::Buffer.evaluate(&[]), Level::Undefined);
+    }
+}
@@ -0,0 +1,81 @@
+use crate::components::NetId;
+use crate::components::gates::logic_gate::{GateKind, GateType, LogicGate, MultiInput};
+
+pub struct Nand;
+
+impl GateType for Nand {
+    const KIND: GateKind = GateKind::Nand;
+}
+
+impl MultiInput for Nand {}
+
+pub type NANDGate = LogicGate<Nand>;
+
+impl NANDGate {
+    pub fn new(input1: NetId, input2: NetId, output: NetId) -> NANDGate {
+        LogicGate::with_connections(vec![input1, input2], output)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::components::{Component, Level, Nets};
+
+    #[test]
+    fn test_update_nand_gate() {
+        let mut nets = Nets::new();
+        let input1 = nets.add();
+        let input2 = nets.add();
+        let output = nets.add();
+        let mut nand_gate = NANDGate::new(input1, input2, output);
+
+        for (input1_level, input2_level, expected_output) in [
+            (Level::Low, Level::Low, Level::High),
+            (Level::Low, Level::High, Level::High),
+            (Level::High, Level::Low, Level::High),
+            (Level::High, Level::High, Level::Low),
+            (Level::Undefined, Level::Low, Level::High),
+            (Level::Low, Level::Undefined, Level::High),
+            (Level::Undefined, Level::High, Level::Undefined),
+            (Level::High, Level::Undefined, Level::Undefined),
+            (Level::Undefined, Level::Undefined, Level::Undefined),
+            (Level::HighZ, Level::High, Level::Undefined),
+        ] {
+            nets.set_level(input1, input1_level);
+            nets.set_level(input2, input2_level);
+            nand_gate.update(&mut nets);
+            assert_eq!(nets.level(output), expected_output);
+        }
+    }
+
+    #[test]
+    fn test_cross_coupled_nand_gates_store_a_bit() {
+        use crate::simulation::{Circuit, Simulator};
+
+        let mut circuit = Circuit::new();
+        let set_n = circuit.add_net("set_n").unwrap();
+        let reset_n = circuit.add_net("reset_n").unwrap();
+        let q = circuit.add_net("q").unwrap();
+        let q_bar = circuit.add_net("q_bar").unwrap();
+        circuit.add_component(Box::new(NANDGate::new(set_n, q_bar, q).with_delay(1)));
+        circuit.add_component(Box::new(NANDGate::new(reset_n, q, q_bar).with_delay(1)));
+
+        let mut simulator = Simulator::new(circuit);
+        simulator.set_level("set_n", Level::Low).unwrap();
+        simulator.set_level("reset_n", Level::High).unwrap();
+        simulator.settle().unwrap();
+        assert_eq!(simulator.level("q").unwrap(), Level::High);
+        assert_eq!(simulator.level("q_bar").unwrap(), Level::Low);
+
+        simulator.set_level("set_n", Level::High).unwrap();
+        simulator.settle().unwrap();
+        assert_eq!(simulator.level("q").unwrap(), Level::High);
+
+        simulator.set_level("reset_n", Level::Low).unwrap();
+        simulator.settle().unwrap();
+        assert_eq!(simulator.level("q").unwrap(), Level::Low);
+        assert_eq!(simulator.level("q_bar").unwrap(), Level::High);
+    }
+
+}
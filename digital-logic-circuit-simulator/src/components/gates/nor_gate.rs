@@ -0,0 +1,51 @@
+use crate::components::NetId;
+use crate::components::gates::logic_gate::{GateKind, GateType, LogicGate, MultiInput};
+
+pub struct Nor;
+
+impl GateType for Nor {
+    const KIND: GateKind = GateKind::Nor;
+}
+
+impl MultiInput for Nor {}
+
+pub type NORGate = LogicGate<Nor>;
+
+impl NORGate {
+    pub fn new(input1: NetId, input2: NetId, output: NetId) -> NORGate {
+        LogicGate::with_connections(vec![input1, input2], output)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::components::{Component, Level, Nets};
+
+    #[test]
+    fn test_update_nor_gate() {
+        let mut nets = Nets::new();
+        let input1 = nets.add();
+        let input2 = nets.add();
+        let output = nets.add();
+        let mut nor_gate = NORGate::new(input1, input2, output);
+
+        for (input1_level, input2_level, expected_output) in [
+            (Level::Low, Level::Low, Level::High),
+            (Level::Low, Level::High, Level::Low),
+            (Level::High, Level::Low, Level::Low),
+            (Level::High, Level::High, Level::Low),
+            (Level::Undefined, Level::Low, Level::Undefined),
+            (Level::Low, Level::Undefined, Level::Undefined),
+            (Level::Undefined, Level::High, Level::Low),
+            (Level::High, Level::Undefined, Level::Low),
+            (Level::Undefined, Level::Undefined, Level::Undefined),
+            (Level::HighZ, Level::High, Level::Low),
+        ] {
+            nets.set_level(input1, input1_level);
+            nets.set_level(input2, input2_level);
+            nor_gate.update(&mut nets);
+            assert_eq!(nets.level(output), expected_output);
+        }
+    }
+}
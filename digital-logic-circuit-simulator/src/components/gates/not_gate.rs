@@ -0,0 +1,41 @@
+use crate::components::NetId;
+use crate::components::gates::logic_gate::{GateKind, GateType, LogicGate};
+
+pub struct Not;
+
+impl GateType for Not {
+    const KIND: GateKind = GateKind::Not;
+}
+
+pub type NOTGate = LogicGate<Not>;
+
+impl NOTGate {
+    pub fn new(input: NetId, output: NetId) -> NOTGate {
+        LogicGate::with_connections(vec![input], output)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::components::{Component, Level, Nets};
+
+    #[test]
+    fn test_update_not_gate() {
+        let mut nets = Nets::new();
+        let input = nets.add();
+        let output = nets.add();
+        let mut not_gate = NOTGate::new(input, output);
+
+        for (input_level, expected_output) in [
+            (Level::Low, Level::High),
+            (Level::High, Level::Low),
+            (Level::Undefined, Level::Undefined),
+            (Level::HighZ, Level::Undefined),
+        ] {
+            nets.set_level(input, input_level);
+            not_gate.update(&mut nets);
+            assert_eq!(nets.level(output), expected_output);
+        }
+    }
+}
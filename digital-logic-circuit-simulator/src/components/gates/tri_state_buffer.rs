@@ -0,0 +1,46 @@
+use crate::components::NetId;
+use crate::components::gates::logic_gate::{GateKind, GateType, LogicGate};
+
+pub struct TriState;
+
+impl GateType for TriState {
+    const KIND: GateKind = GateKind::TriState;
+}
+
+pub type TriStateBuffer = LogicGate<TriState>;
+
+impl TriStateBuffer {
+    pub fn new(input: NetId, enable: NetId, output: NetId) -> TriStateBuffer {
+        LogicGate::with_connections(vec![input, enable], output)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::components::{Component, Level, Nets};
+
+    #[test]
+    fn test_update_tri_state_buffer() {
+        let mut nets = Nets::new();
+        let input = nets.add();
+        let enable = nets.add();
+        let output = nets.add();
+        let mut tri_state_buffer = TriStateBuffer::new(input, enable, output);
+
+        for (input_level, enable_level, expected_output) in [
+            (Level::Low, Level::High, Level::Low),
+            (Level::High, Level::High, Level::High),
+            (Level::Undefined, Level::High, Level::Undefined),
+            (Level::Low, Level::Low, Level::HighZ),
+            (Level::High, Level::Low, Level::HighZ),
+            (Level::High, Level::Undefined, Level::Undefined),
+            (Level::High, Level::HighZ, Level::Undefined),
+        ] {
+            nets.set_level(input, input_level);
+            nets.set_level(enable, enable_level);
+            tri_state_buffer.update(&mut nets);
+            assert_eq!(nets.level(output), expected_output);
+        }
+    }
+}
@@ -0,0 +1,110 @@
+use std::ops::{Index, IndexMut};
+
+use crate::components::{Connection, Level};
+
+// Index of a net in a Nets arena, and so in a Circuit or an ArenaCircuit
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct NetId(pub(crate) u32);
+
+// Index of a component in a Circuit, or of a gate in an ArenaCircuit
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct ComponentId(pub(crate) u32);
+
+impl NetId {
+    pub fn index(self) -> usize {
+        self.0 as usize
+    }
+}
+
+impl ComponentId {
+    pub fn index(self) -> usize {
+        self.0 as usize
+    }
+}
+
+pub(crate) fn net_id(index: usize) -> NetId {
+    NetId(index as u32)
+}
+
+pub(crate) fn component_id(index: usize) -> ComponentId {
+    ComponentId(index as u32)
+}
+
+// The connections components are wired with, stored back to back and addressed by NetId.
+// Components only keep the ids of their nets and are handed the arena when they update.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Nets {
+    connections: Vec<Connection>,
+}
+
+impl Nets {
+    pub fn new() -> Nets {
+        Nets { connections: Vec::new() }
+    }
+
+    // Create a new net, initially undefined
+    pub fn add(&mut self) -> NetId {
+        self.connections.push(Connection::new());
+        net_id(self.connections.len() - 1)
+    }
+
+    pub fn add_many(&mut self, count: usize) -> Vec<NetId> {
+        (0..count).map(|_| self.add()).collect()
+    }
+
+    pub fn len(&self) -> usize {
+        self.connections.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.connections.is_empty()
+    }
+
+    pub fn level(&self, net: NetId) -> Level {
+        self.connections[net.index()].level()
+    }
+
+    // Force a net to a level, see Connection::set_level
+    pub fn set_level(&mut self, net: NetId, level: Level) {
+        self.connections[net.index()].set_level(level);
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (NetId, &Connection)> {
+        self.connections.iter().enumerate().map(|(index, connection)| (net_id(index), connection))
+    }
+}
+
+impl Index<NetId> for Nets {
+    type Output = Connection;
+
+    fn index(&self, net: NetId) -> &Connection {
+        &self.connections[net.index()]
+    }
+}
+
+impl IndexMut<NetId> for Nets {
+    fn index_mut(&mut self, net: NetId) -> &mut Connection {
+        &mut self.connections[net.index()]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_add_and_index() {
+        let mut nets = Nets::new();
+        let a = nets.add();
+        let rest = nets.add_many(2);
+        assert_eq!(nets.len(), 3);
+        assert_eq!(rest.iter().map(|net| net.index()).collect::<Vec<_>>(), [1, 2]);
+        assert_eq!(nets.level(a), Level::Undefined);
+
+        nets.set_level(rest[1], Level::High);
+        nets[a].drive(7, Level::Low);
+        assert_eq!(nets[rest[1]].level(), Level::High);
+        assert_eq!(nets.level(a), Level::Low);
+        assert_eq!(nets.iter().filter(|(_, connection)| connection.level() == Level::Undefined).count(), 1);
+    }
+}
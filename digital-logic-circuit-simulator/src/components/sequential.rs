@@ -0,0 +1,11 @@
+pub mod sr_latch;
+pub mod d_latch;
+pub mod d_flip_flop;
+pub mod jk_flip_flop;
+pub mod clock;
+
+pub use crate::components::sequential::sr_latch::{LatchKind, SRLatch};
+pub use crate::components::sequential::d_latch::DLatch;
+pub use crate::components::sequential::d_flip_flop::DFlipFlop;
+pub use crate::components::sequential::jk_flip_flop::JKFlipFlop;
+pub use crate::components::sequential::clock::Clock;
@@ -0,0 +1,123 @@
+use core::fmt;
+use crate::components::{NetId, Nets, Level, BaseComponent, Component};
+
+// Square wave source. Every update toggles the output.
+// The clock reads its own output, so inside a simulator it re-triggers itself
+// and toggles every `half_period` ticks, starting Low at time 0.
+pub struct Clock {
+    base: BaseComponent,
+}
+
+impl Clock {
+    // The output starts Low
+    pub fn new(nets: &mut Nets, output: NetId, half_period: u64) -> Clock {
+        nets.set_level(output, Level::Low);
+        let mut clock = Clock {
+            base: BaseComponent::new("Clock"),
+        };
+        clock.base.add_input(output);
+        clock.base.add_output(output);
+        clock.base.set_delay(half_period.max(1));
+        clock
+    }
+
+    pub fn half_period(&self) -> u64 {
+        self.base.delay()
+    }
+}
+
+impl Component for Clock {
+    fn update(&mut self, nets: &mut Nets) {
+        let level = match self.base.input_level(nets, 0) {
+            Level::High => Level::Low,
+            _ => Level::High,
+        };
+        self.base.set_output_level(nets, 0, level);
+    }
+
+    fn inputs(&self) -> &[NetId] {
+        self.base.inputs()
+    }
+
+    fn outputs(&self) -> &[NetId] {
+        self.base.outputs()
+    }
+
+    fn flatten(self: Box<Self>) -> Vec<Box<dyn Component>> {
+        vec![self]
+    }
+
+    // The half period is the delay; it is not affected by circuit wide delay changes
+    fn delay(&self) -> u64 {
+        self.base.delay()
+    }
+}
+
+impl fmt::Display for Clock {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.base.display(f)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::components::sequential::DFlipFlop;
+    use crate::simulation::{Circuit, Simulator};
+
+    #[test]
+    fn test_update_clock() {
+        let mut nets = Nets::new();
+        let output = nets.add();
+        let mut clock = Clock::new(&mut nets, output, 5);
+        assert_eq!(nets.level(output), Level::Low);
+        clock.update(&mut nets);
+        assert_eq!(nets.level(output), Level::High);
+        clock.update(&mut nets);
+        assert_eq!(nets.level(output), Level::Low);
+        assert_eq!(clock.half_period(), 5);
+    }
+
+    #[test]
+    fn test_clock_in_simulator() {
+        let mut circuit = Circuit::new();
+        let clk = circuit.add_net("clk").unwrap();
+        let clock = Clock::new(circuit.nets_mut(), clk, 3);
+        circuit.add_component(Box::new(clock));
+        circuit.set_delay(1);
+
+        let mut simulator = Simulator::new(circuit);
+        for (time, expected) in [(2, Level::Low), (3, Level::High), (5, Level::High), (6, Level::Low), (9, Level::High)] {
+            simulator.run_until(time).unwrap();
+            assert_eq!(simulator.level("clk").unwrap(), expected);
+        }
+    }
+
+    #[test]
+    fn test_clock_drives_flip_flop() {
+        let mut circuit = Circuit::new();
+        let clk = circuit.add_net("clk").unwrap();
+        let d = circuit.add_net("d").unwrap();
+        let q = circuit.add_net("q").unwrap();
+        let q_bar = circuit.add_net("q_bar").unwrap();
+        let clock = Clock::new(circuit.nets_mut(), clk, 2);
+        circuit.add_component(Box::new(clock));
+        circuit.add_component(Box::new(DFlipFlop::new(d, clk, q, q_bar)));
+
+        // Rising edges at 2, 6, 10, ...
+        let mut simulator = Simulator::new(circuit);
+        simulator.set_level("d", Level::High).unwrap();
+        simulator.run_until(1).unwrap();
+        assert_eq!(simulator.level("q").unwrap(), Level::Undefined);
+        simulator.run_until(2).unwrap();
+        assert_eq!(simulator.level("q").unwrap(), Level::High);
+
+        simulator.run_until(3).unwrap();
+        simulator.set_level("d", Level::Low).unwrap();
+        simulator.run_until(5).unwrap();
+        assert_eq!(simulator.level("q").unwrap(), Level::High);
+        simulator.run_until(6).unwrap();
+        assert_eq!(simulator.level("q").unwrap(), Level::Low);
+        assert_eq!(simulator.last_change("q").unwrap(), 6);
+    }
+}
@@ -0,0 +1,161 @@
+use core::fmt;
+use crate::components::{NetId, Nets, Level, BaseComponent, Component};
+
+// Rising edge triggered D flip-flop, optionally with an asynchronous reset
+pub struct DFlipFlop {
+    base: BaseComponent,
+    state: Level,
+    last_clock: Level,
+}
+
+impl DFlipFlop {
+    pub fn new(
+        data: NetId,
+        clock: NetId,
+        q: NetId,
+        q_bar: NetId,
+    ) -> DFlipFlop {
+        let mut d_flip_flop = DFlipFlop {
+            base: BaseComponent::new("D Flip-Flop"),
+            state: Level::Undefined,
+            last_clock: Level::Undefined,
+        };
+        d_flip_flop.base.add_input(data);
+        d_flip_flop.base.add_input(clock);
+        d_flip_flop.base.add_output(q);
+        d_flip_flop.base.add_output(q_bar);
+        d_flip_flop
+    }
+
+    // Flip-flop cleared to 0 whenever `reset` is 1, independent of the clock
+    pub fn with_reset(
+        data: NetId,
+        clock: NetId,
+        reset: NetId,
+        q: NetId,
+        q_bar: NetId,
+    ) -> DFlipFlop {
+        let mut d_flip_flop = DFlipFlop::new(data, clock, q, q_bar);
+        d_flip_flop.base.add_input(reset);
+        d_flip_flop
+    }
+
+    pub fn with_delay(mut self, delay: u64) -> DFlipFlop {
+        self.base.set_delay(delay);
+        self
+    }
+
+    // Stored bit
+    pub fn state(&self) -> Level {
+        self.state
+    }
+}
+
+impl Component for DFlipFlop {
+    fn update(&mut self, nets: &mut Nets) {
+        let clock = self.base.input_level(nets, 1);
+        if self.last_clock == Level::Low && clock == Level::High {
+            self.state = self.base.input_level(nets, 0);
+        }
+        self.last_clock = clock;
+        if self.base.inputs().len() > 2 {
+            self.state = reset(self.state, self.base.input_level(nets, 2));
+        }
+
+        self.base.set_output_level(nets, 0, self.state);
+        self.base.set_output_level(nets, 1, !self.state);
+    }
+
+    fn inputs(&self) -> &[NetId] {
+        self.base.inputs()
+    }
+
+    fn outputs(&self) -> &[NetId] {
+        self.base.outputs()
+    }
+
+    fn flatten(self: Box<Self>) -> Vec<Box<dyn Component>> {
+        vec![self]
+    }
+
+    fn delay(&self) -> u64 {
+        self.base.delay()
+    }
+
+    fn set_delay(&mut self, delay: u64) {
+        self.base.set_delay(delay);
+    }
+}
+
+// State after applying an asynchronous reset input. An undefined reset may or may not clear
+// the state, so only a state that is already 0 stays known.
+pub(crate) fn reset(state: Level, reset: Level) -> Level {
+    match reset {
+        Level::Low => state,
+        Level::High => Level::Low,
+        _ if state == Level::Low => Level::Low,
+        _ => Level::Undefined,
+    }
+}
+
+impl fmt::Display for DFlipFlop {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.base.display(f)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_update_d_flip_flop() {
+        let mut nets = Nets::new();
+        let data = nets.add();
+        let clock = nets.add();
+        let q = nets.add();
+        let q_bar = nets.add();
+        let mut d_flip_flop = DFlipFlop::new(data, clock, q, q_bar);
+
+        for (data_level, clock_level, expected_q, expected_q_bar) in [
+            (Level::High, Level::High, Level::Undefined, Level::Undefined),
+            (Level::High, Level::Low, Level::Undefined, Level::Undefined),
+            (Level::High, Level::High, Level::High, Level::Low),
+            (Level::Low, Level::High, Level::High, Level::Low),
+            (Level::Low, Level::Low, Level::High, Level::Low),
+            (Level::Low, Level::High, Level::Low, Level::High),
+            (Level::High, Level::Low, Level::Low, Level::High),
+            (Level::Undefined, Level::High, Level::Undefined, Level::Undefined),
+        ] {
+            nets.set_level(data, data_level);
+            nets.set_level(clock, clock_level);
+            d_flip_flop.update(&mut nets);
+            assert_eq!(nets.level(q), expected_q);
+            assert_eq!(nets.level(q_bar), expected_q_bar);
+        }
+    }
+
+    #[test]
+    fn test_reset() {
+        let mut nets = Nets::new();
+        let [data, clock, reset, q, q_bar] = [(); 5].map(|_| nets.add());
+        let mut d_flip_flop = DFlipFlop::with_reset(data, clock, reset, q, q_bar);
+
+        // Clearing works without a clock edge and wins over one
+        for (data_level, clock_level, reset_level, expected_q) in [
+            (Level::High, Level::Low, Level::Undefined, Level::Undefined),
+            (Level::High, Level::Low, Level::High, Level::Low),
+            (Level::High, Level::High, Level::High, Level::Low),
+            (Level::High, Level::Low, Level::Undefined, Level::Low),
+            (Level::High, Level::Low, Level::Low, Level::Low),
+            (Level::High, Level::High, Level::Low, Level::High),
+            (Level::High, Level::High, Level::Undefined, Level::Undefined),
+        ] {
+            nets.set_level(data, data_level);
+            nets.set_level(clock, clock_level);
+            nets.set_level(reset, reset_level);
+            d_flip_flop.update(&mut nets);
+            assert_eq!(nets.level(q), expected_q);
+        }
+    }
+}
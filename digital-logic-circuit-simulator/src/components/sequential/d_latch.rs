@@ -0,0 +1,112 @@
+use core::fmt;
+use crate::components::{NetId, Nets, Level, BaseComponent, Component};
+
+// Gated D latch: transparent while enable is High, holds its state while enable is Low
+pub struct DLatch {
+    base: BaseComponent,
+    state: Level,
+}
+
+impl DLatch {
+    pub fn new(
+        data: NetId,
+        enable: NetId,
+        q: NetId,
+        q_bar: NetId,
+    ) -> DLatch {
+        let mut d_latch = DLatch {
+            base: BaseComponent::new("D Latch"),
+            state: Level::Undefined,
+        };
+        d_latch.base.add_input(data);
+        d_latch.base.add_input(enable);
+        d_latch.base.add_output(q);
+        d_latch.base.add_output(q_bar);
+        d_latch
+    }
+
+    pub fn with_delay(mut self, delay: u64) -> DLatch {
+        self.base.set_delay(delay);
+        self
+    }
+
+    // Stored bit
+    pub fn state(&self) -> Level {
+        self.state
+    }
+}
+
+impl Component for DLatch {
+    fn update(&mut self, nets: &mut Nets) {
+        let data = self.base.input_level(nets, 0);
+        match self.base.input_level(nets, 1) {
+            Level::High => self.state = data,
+            Level::Low => {}
+            // Whether the latch is open or not only matters if data differs from the state
+            Level::Undefined | Level::HighZ => {
+                if data != self.state {
+                    self.state = Level::Undefined;
+                }
+            }
+        }
+
+        self.base.set_output_level(nets, 0, self.state);
+        self.base.set_output_level(nets, 1, !self.state);
+    }
+
+    fn inputs(&self) -> &[NetId] {
+        self.base.inputs()
+    }
+
+    fn outputs(&self) -> &[NetId] {
+        self.base.outputs()
+    }
+
+    fn flatten(self: Box<Self>) -> Vec<Box<dyn Component>> {
+        vec![self]
+    }
+
+    fn delay(&self) -> u64 {
+        self.base.delay()
+    }
+
+    fn set_delay(&mut self, delay: u64) {
+        self.base.set_delay(delay);
+    }
+}
+
+impl fmt::Display for DLatch {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.base.display(f)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_update_d_latch() {
+        let mut nets = Nets::new();
+        let data = nets.add();
+        let enable = nets.add();
+        let q = nets.add();
+        let q_bar = nets.add();
+        let mut d_latch = DLatch::new(data, enable, q, q_bar);
+
+        for (data_level, enable_level, expected_q, expected_q_bar) in [
+            (Level::High, Level::Low, Level::Undefined, Level::Undefined),
+            (Level::High, Level::High, Level::High, Level::Low),
+            (Level::Low, Level::High, Level::Low, Level::High),
+            (Level::High, Level::Low, Level::Low, Level::High),
+            (Level::Low, Level::Undefined, Level::Low, Level::High),
+            (Level::High, Level::Undefined, Level::Undefined, Level::Undefined),
+        ] {
+            nets.set_level(data, data_level);
+            nets.set_level(enable, enable_level);
+            d_latch.update(&mut nets);
+            assert_eq!(nets.level(q), expected_q);
+            assert_eq!(nets.level(q_bar), expected_q_bar);
+        }
+    }
+}
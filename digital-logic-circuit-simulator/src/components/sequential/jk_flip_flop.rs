@@ -0,0 +1,164 @@
+use core::fmt;
+use crate::components::{NetId, Nets, Level, BaseComponent, Component};
+use crate::components::sequential::d_flip_flop::reset;
+
+// Rising edge triggered JK flip-flop, optionally with an asynchronous reset
+pub struct JKFlipFlop {
+    base: BaseComponent,
+    state: Level,
+    last_clock: Level,
+}
+
+impl JKFlipFlop {
+    pub fn new(
+        j: NetId,
+        k: NetId,
+        clock: NetId,
+        q: NetId,
+        q_bar: NetId,
+    ) -> JKFlipFlop {
+        let mut jk_flip_flop = JKFlipFlop {
+            base: BaseComponent::new("JK Flip-Flop"),
+            state: Level::Undefined,
+            last_clock: Level::Undefined,
+        };
+        jk_flip_flop.base.add_input(j);
+        jk_flip_flop.base.add_input(k);
+        jk_flip_flop.base.add_input(clock);
+        jk_flip_flop.base.add_output(q);
+        jk_flip_flop.base.add_output(q_bar);
+        jk_flip_flop
+    }
+
+    // Flip-flop cleared to 0 whenever `reset` is 1, independent of the clock
+    pub fn with_reset(
+        j: NetId,
+        k: NetId,
+        clock: NetId,
+        reset: NetId,
+        q: NetId,
+        q_bar: NetId,
+    ) -> JKFlipFlop {
+        let mut jk_flip_flop = JKFlipFlop::new(j, k, clock, q, q_bar);
+        jk_flip_flop.base.add_input(reset);
+        jk_flip_flop
+    }
+
+    pub fn with_delay(mut self, delay: u64) -> JKFlipFlop {
+        self.base.set_delay(delay);
+        self
+    }
+
+    // Stored bit
+    pub fn state(&self) -> Level {
+        self.state
+    }
+}
+
+impl Component for JKFlipFlop {
+    fn update(&mut self, nets: &mut Nets) {
+        let clock = self.base.input_level(nets, 2);
+        if self.last_clock == Level::Low && clock == Level::High {
+            self.state = match (self.base.input_level(nets, 0), self.base.input_level(nets, 1)) {
+                (Level::Low, Level::Low) => self.state,
+                (Level::High, Level::Low) => Level::High,
+                (Level::Low, Level::High) => Level::Low,
+                (Level::High, Level::High) => !self.state,
+                _ => Level::Undefined,
+            };
+        }
+        self.last_clock = clock;
+        if self.base.inputs().len() > 3 {
+            self.state = reset(self.state, self.base.input_level(nets, 3));
+        }
+
+        self.base.set_output_level(nets, 0, self.state);
+        self.base.set_output_level(nets, 1, !self.state);
+    }
+
+    fn inputs(&self) -> &[NetId] {
+        self.base.inputs()
+    }
+
+    fn outputs(&self) -> &[NetId] {
+        self.base.outputs()
+    }
+
+    fn flatten(self: Box<Self>) -> Vec<Box<dyn Component>> {
+        vec![self]
+    }
+
+    fn delay(&self) -> u64 {
+        self.base.delay()
+    }
+
+    fn set_delay(&mut self, delay: u64) {
+        self.base.set_delay(delay);
+    }
+}
+
+impl fmt::Display for JKFlipFlop {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.base.display(f)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_update_jk_flip_flop() {
+        let mut nets = Nets::new();
+        let j = nets.add();
+        let k = nets.add();
+        let clock = nets.add();
+        let q = nets.add();
+        let q_bar = nets.add();
+        let mut jk_flip_flop = JKFlipFlop::new(j, k, clock, q, q_bar);
+
+        // Every row is a full clock cycle: inputs applied while Low, then a rising edge
+        for (j_level, k_level, expected_q) in [
+            (Level::High, Level::High, Level::Undefined),
+            (Level::High, Level::Low, Level::High),
+            (Level::Low, Level::Low, Level::High),
+            (Level::High, Level::High, Level::Low),
+            (Level::High, Level::High, Level::High),
+            (Level::Low, Level::High, Level::Low),
+            (Level::Undefined, Level::Low, Level::Undefined),
+        ] {
+            nets.set_level(j, j_level);
+            nets.set_level(k, k_level);
+            nets.set_level(clock, Level::Low);
+            jk_flip_flop.update(&mut nets);
+            nets.set_level(clock, Level::High);
+            jk_flip_flop.update(&mut nets);
+            assert_eq!(nets.level(q), expected_q);
+            assert_eq!(nets.level(q_bar), !expected_q);
+        }
+    }
+
+    #[test]
+    fn test_reset() {
+        let mut nets = Nets::new();
+        let [j, k, clock, reset, q, q_bar] = [(); 6].map(|_| nets.add());
+        let mut jk_flip_flop = JKFlipFlop::with_reset(j, k, clock, reset, q, q_bar);
+        nets.set_level(j, Level::High);
+        nets.set_level(k, Level::High);
+
+        // Toggling needs a known state, which the reset provides
+        for (reset_level, expected_q) in [
+            (Level::Low, Level::Undefined),
+            (Level::High, Level::Low),
+            (Level::Low, Level::High),
+            (Level::Low, Level::Low),
+        ] {
+            nets.set_level(reset, reset_level);
+            nets.set_level(clock, Level::Low);
+            jk_flip_flop.update(&mut nets);
+            nets.set_level(clock, Level::High);
+            jk_flip_flop.update(&mut nets);
+            assert_eq!(nets.level(q), expected_q);
+        }
+    }
+}
@@ -0,0 +1,190 @@
+use core::fmt;
+use crate::components::{NetId, Nets, Level, BaseComponent, Component};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum LatchKind {
+    // Cross-coupled NOR gates, set and reset are active high
+    Nor,
+    // Cross-coupled NAND gates, set and reset are active low
+    Nand,
+}
+
+pub struct SRLatch {
+    base: BaseComponent,
+    kind: LatchKind,
+    state: Level,
+    forbidden: bool,
+}
+
+impl SRLatch {
+    pub fn nor(
+        set: NetId,
+        reset: NetId,
+        q: NetId,
+        q_bar: NetId,
+    ) -> SRLatch {
+        SRLatch::new("NOR SR Latch", LatchKind::Nor, set, reset, q, q_bar)
+    }
+
+    pub fn nand(
+        set_n: NetId,
+        reset_n: NetId,
+        q: NetId,
+        q_bar: NetId,
+    ) -> SRLatch {
+        SRLatch::new("NAND SR Latch", LatchKind::Nand, set_n, reset_n, q, q_bar)
+    }
+
+    fn new(
+        name: &str,
+        kind: LatchKind,
+        set: NetId,
+        reset: NetId,
+        q: NetId,
+        q_bar: NetId,
+    ) -> SRLatch {
+        let mut sr_latch = SRLatch {
+            base: BaseComponent::new(name),
+            kind,
+            state: Level::Undefined,
+            forbidden: false,
+        };
+        sr_latch.base.add_input(set);
+        sr_latch.base.add_input(reset);
+        sr_latch.base.add_output(q);
+        sr_latch.base.add_output(q_bar);
+        sr_latch
+    }
+
+    pub fn with_delay(mut self, delay: u64) -> SRLatch {
+        self.base.set_delay(delay);
+        self
+    }
+
+    pub fn kind(&self) -> LatchKind {
+        self.kind
+    }
+
+    // Stored bit
+    pub fn state(&self) -> Level {
+        self.state
+    }
+}
+
+impl Component for SRLatch {
+    fn update(&mut self, nets: &mut Nets) {
+        let (set, reset) = match self.kind {
+            LatchKind::Nor => (self.base.input_level(nets, 0), self.base.input_level(nets, 1)),
+            LatchKind::Nand => (!self.base.input_level(nets, 0), !self.base.input_level(nets, 1)),
+        };
+
+        self.forbidden = false;
+        match (set, reset) {
+            (Level::Low, Level::Low) => {}
+            (Level::High, Level::Low) => self.state = Level::High,
+            (Level::Low, Level::High) => self.state = Level::Low,
+            (Level::High, Level::High) => {
+                // Both gates are forced, the stored bit is lost once the inputs are released
+                self.forbidden = true;
+                self.state = Level::Undefined;
+            }
+            // An undefined input only keeps the state if both of its values would
+            (Level::Undefined, Level::Low) if self.state == Level::High => {}
+            (Level::Low, Level::Undefined) if self.state == Level::Low => {}
+            _ => self.state = Level::Undefined,
+        }
+
+        let (q, q_bar) = match (self.forbidden, self.kind) {
+            (true, LatchKind::Nor) => (Level::Low, Level::Low),
+            (true, LatchKind::Nand) => (Level::High, Level::High),
+            (false, _) => (self.state, !self.state),
+        };
+        self.base.set_output_level(nets, 0, q);
+        self.base.set_output_level(nets, 1, q_bar);
+    }
+
+    fn inputs(&self) -> &[NetId] {
+        self.base.inputs()
+    }
+
+    fn outputs(&self) -> &[NetId] {
+        self.base.outputs()
+    }
+
+    fn flatten(self: Box<Self>) -> Vec<Box<dyn Component>> {
+        vec![self]
+    }
+
+    fn delay(&self) -> u64 {
+        self.base.delay()
+    }
+
+    fn set_delay(&mut self, delay: u64) {
+        self.base.set_delay(delay);
+    }
+}
+
+impl fmt::Display for SRLatch {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.base.display(f)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_update_nor_sr_latch() {
+        let mut nets = Nets::new();
+        let set = nets.add();
+        let reset = nets.add();
+        let q = nets.add();
+        let q_bar = nets.add();
+        let mut sr_latch = SRLatch::nor(set, reset, q, q_bar);
+
+        for (set_level, reset_level, expected_q, expected_q_bar) in [
+            (Level::Low, Level::Low, Level::Undefined, Level::Undefined),
+            (Level::High, Level::Low, Level::High, Level::Low),
+            (Level::Low, Level::Low, Level::High, Level::Low),
+            (Level::Undefined, Level::Low, Level::High, Level::Low),
+            (Level::Low, Level::High, Level::Low, Level::High),
+            (Level::Low, Level::Low, Level::Low, Level::High),
+            (Level::Low, Level::Undefined, Level::Low, Level::High),
+            (Level::Undefined, Level::Low, Level::Undefined, Level::Undefined),
+            (Level::High, Level::High, Level::Low, Level::Low),
+            (Level::Low, Level::Low, Level::Undefined, Level::Undefined),
+        ] {
+            nets.set_level(set, set_level);
+            nets.set_level(reset, reset_level);
+            sr_latch.update(&mut nets);
+            assert_eq!(nets.level(q), expected_q);
+            assert_eq!(nets.level(q_bar), expected_q_bar);
+        }
+    }
+
+    #[test]
+    fn test_update_nand_sr_latch() {
+        let mut nets = Nets::new();
+        let set_n = nets.add();
+        let reset_n = nets.add();
+        let q = nets.add();
+        let q_bar = nets.add();
+        let mut sr_latch = SRLatch::nand(set_n, reset_n, q, q_bar);
+
+        for (set_level, reset_level, expected_q, expected_q_bar) in [
+            (Level::Low, Level::High, Level::High, Level::Low),
+            (Level::High, Level::High, Level::High, Level::Low),
+            (Level::High, Level::Low, Level::Low, Level::High),
+            (Level::High, Level::High, Level::Low, Level::High),
+            (Level::Low, Level::Low, Level::High, Level::High),
+            (Level::High, Level::High, Level::Undefined, Level::Undefined),
+        ] {
+            nets.set_level(set_n, set_level);
+            nets.set_level(reset_n, reset_level);
+            sr_latch.update(&mut nets);
+            assert_eq!(nets.level(q), expected_q);
+            assert_eq!(nets.level(q_bar), expected_q_bar);
+        }
+    }
+}
@@ -1 +1,10 @@
+pub mod alu;
 pub mod arithmetic;
+pub mod counter;
+pub mod register;
+pub mod shift_register;
+
+pub use alu::{ALUFlags, Opcode, ALU};
+pub use counter::{RippleCounter, SynchronousCounter};
+pub use register::Register;
+pub use shift_register::ShiftRegister;
@@ -0,0 +1,340 @@
+use core::fmt;
+
+use crate::circuits::FullAdder;
+use crate::components::gates::{ANDGate, Buffer, NORGate, ORGate};
+use crate::components::{BaseComponent, Component, Level, NetId, Nets};
+use crate::digital_logic::arithmetic::gate_list::{read_operand, set_operand, GateList};
+use crate::simulation::Circuit;
+
+// Operations of the ALU with their codes on the three-bit opcode bus
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Opcode {
+    Add,
+    Sub,
+    And,
+    Or,
+    Xor,
+    // Logical shifts of a by b bits, giving 0 once b reaches the width
+    Shl,
+    Shr,
+    // 1 if a is less than b as two's complement numbers, otherwise 0
+    Slt,
+}
+
+impl Opcode {
+    pub const ALL: [Opcode; 8] =
+        [Opcode::Add, Opcode::Sub, Opcode::And, Opcode::Or, Opcode::Xor, Opcode::Shl, Opcode::Shr, Opcode::Slt];
+
+    pub fn code(self) -> usize {
+        Opcode::ALL.iter().position(|&op| op == self).unwrap()
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Opcode::Add => "add",
+            Opcode::Sub => "sub",
+            Opcode::And => "and",
+            Opcode::Or => "or",
+            Opcode::Xor => "xor",
+            Opcode::Shl => "shl",
+            Opcode::Shr => "shr",
+            Opcode::Slt => "slt",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Opcode> {
+        Opcode::ALL.into_iter().find(|op| op.name() == name)
+    }
+}
+
+// Status of an ALU operation
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct ALUFlags {
+    // Every bit of the result is 0
+    pub zero: bool,
+    // Carry out of an addition, or the borrow of a subtraction or comparison
+    pub carry: bool,
+    // Signed overflow of an addition or subtraction
+    pub overflow: bool,
+    // The most significant bit of the result
+    pub negative: bool,
+}
+
+// Arithmetic logic unit computing one of the operations of `Opcode` on two n-bit operands.
+// Every operation is computed side by side and the decoded opcode selects one result.
+// Carry and overflow are 0 for the logic operations and shifts.
+pub struct ALU {
+    base: BaseComponent,
+    components: Vec<Box<dyn Component>>,
+}
+
+impl ALU {
+    // `opcode` holds the three bits of the operation code, least significant first, and
+    // `flags` the zero, carry, overflow and negative outputs in that order
+    pub fn new(nets: &mut Nets, a: Vec<NetId>, b: Vec<NetId>, opcode: Vec<NetId>, result: Vec<NetId>, flags: [NetId; 4]) -> ALU {
+        let n_bit = a.len();
+        assert!(n_bit > 0 && b.len() == n_bit && result.len() == n_bit, "Operands and result need the same width");
+        assert_eq!(opcode.len(), 3, "The opcode has three bits");
+        let mut base = BaseComponent::new("ALU");
+        for &net in a.iter().chain(&b).chain(&opcode) {
+            base.add_input(net);
+        }
+        for &net in result.iter().chain(&flags) {
+            base.add_output(net);
+        }
+        let [zero, carry, overflow, negative] = flags;
+        let mut components = Vec::new();
+        let mut gates = GateList::new(nets, &mut components);
+
+        // One line per opcode, high when the opcode bus carries its code
+        let inverted: Vec<NetId> = opcode.iter().map(|&bit| gates.not(bit)).collect();
+        let lines: Vec<NetId> = Opcode::ALL
+            .iter()
+            .map(|op| {
+                let bits = (0..3).map(|i| if (op.code() >> i) & 1 == 1 { opcode[i] } else { inverted[i] });
+                gates.and(bits.collect())
+            })
+            .collect();
+        let line = |op: Opcode| lines[op.code()];
+
+        // Adder that subtracts for sub and slt by inverting b and adding 1 as carry in
+        let subtract = gates.or(vec![line(Opcode::Sub), line(Opcode::Slt)]);
+        let mut carries = vec![subtract];
+        let sum = gates.new_nets(n_bit);
+        for i in 0..n_bit {
+            let operand = gates.xor(b[i], subtract);
+            let carry_out = gates.new_net();
+            let full_adder = FullAdder::new(gates.nets(), a[i], operand, carries[i], sum[i], carry_out);
+            gates.add(full_adder);
+            carries.push(carry_out);
+        }
+        let carry_out = carries[n_bit];
+        let signed_overflow = gates.xor(carries[n_bit - 1], carry_out);
+        // a < b exactly when the sign of a - b is wrong because of an overflow
+        let less = gates.xor(sum[n_bit - 1], signed_overflow);
+
+        let shifted_left = shift(&mut gates, &a, &b, true);
+        let shifted_right = shift(&mut gates, &a, &b, false);
+
+        for i in 0..n_bit {
+            let either = gates.or(vec![a[i], b[i]]);
+            let different = gates.xor(a[i], b[i]);
+            let mut terms = vec![
+                gates.and(vec![line(Opcode::Add), sum[i]]),
+                gates.and(vec![line(Opcode::Sub), sum[i]]),
+                gates.and(vec![line(Opcode::And), a[i], b[i]]),
+                gates.and(vec![line(Opcode::Or), either]),
+                gates.and(vec![line(Opcode::Xor), different]),
+                gates.and(vec![line(Opcode::Shl), shifted_left[i]]),
+                gates.and(vec![line(Opcode::Shr), shifted_right[i]]),
+            ];
+            if i == 0 {
+                terms.push(gates.and(vec![line(Opcode::Slt), less]));
+            }
+            gates.add(ORGate::with_inputs(terms, result[i]));
+        }
+
+        gates.add(NORGate::with_inputs(result.clone(), zero));
+        gates.add(Buffer::new(result[n_bit - 1], negative));
+        // The carry flag is the carry out when adding and its inverse, the borrow, when subtracting
+        let adding = gates.and(vec![line(Opcode::Add), carry_out]);
+        let borrow = gates.xor(subtract, carry_out);
+        let borrowing = gates.and(vec![subtract, borrow]);
+        gates.add(ORGate::new(adding, borrowing, carry));
+        let arithmetic = gates.or(vec![line(Opcode::Add), line(Opcode::Sub)]);
+        gates.add(ANDGate::new(arithmetic, signed_overflow, overflow));
+
+        ALU { base, components }
+    }
+
+    // ALU connected to new nets
+    pub fn with_width(nets: &mut Nets, n_bit: usize) -> ALU {
+        let [a, b, opcode, result, flags] = [n_bit, n_bit, 3, n_bit, 4].map(|count| nets.add_many(count));
+        ALU::new(nets, a, b, opcode, result, flags.try_into().unwrap())
+    }
+
+    // ALU in a circuit with inputs a0.., b0.., op0..op2 and outputs result0.., zero, carry,
+    // overflow and negative
+    pub fn circuit(n_bit: usize) -> Circuit {
+        let mut nets = Nets::new();
+        let alu = ALU::with_width(&mut nets, n_bit);
+        let mut inputs: Vec<String> = (0..n_bit).map(|i| format!("a{}", i)).collect();
+        inputs.extend((0..n_bit).map(|i| format!("b{}", i)));
+        inputs.extend((0..3).map(|i| format!("op{}", i)));
+        let mut outputs: Vec<String> = (0..n_bit).map(|i| format!("result{}", i)).collect();
+        outputs.extend(["zero", "carry", "overflow", "negative"].map(String::from));
+        let inputs: Vec<&str> = inputs.iter().map(String::as_str).collect();
+        let outputs: Vec<&str> = outputs.iter().map(String::as_str).collect();
+        Circuit::from_component("alu", nets, Box::new(alu), &inputs, &outputs)
+    }
+
+    // Drive the inputs with the operation and two binary numbers and return the result
+    pub fn calculate(&mut self, nets: &mut Nets, op: Opcode, a: &str, b: &str) -> (String, ALUFlags) {
+        let n_bit = self.base.outputs().len() - 4;
+        let inputs = self.base.inputs();
+        set_operand(nets, &inputs[..n_bit], a);
+        set_operand(nets, &inputs[n_bit..2 * n_bit], b);
+        set_operand(nets, &inputs[2 * n_bit..], &format!("{:b}", op.code()));
+        self.update(nets);
+
+        let outputs = self.base.outputs();
+        let high = |i: usize| nets.level(outputs[n_bit + i]) == Level::High;
+        let flags = ALUFlags { zero: high(0), carry: high(1), overflow: high(2), negative: high(3) };
+        (read_operand(nets, &outputs[..n_bit]), flags)
+    }
+}
+
+// Logarithmic shifter moving a by b bits, towards the most significant bit if `left`.
+// Stage k shifts by 2^k if bit k of b is set; any higher bit of b shifts everything out.
+fn shift(gates: &mut GateList, a: &[NetId], b: &[NetId], left: bool) -> Vec<NetId> {
+    let n_bit = a.len();
+    let mut bits = a.to_vec();
+    let mut k = 0;
+    while 1 << k < n_bit {
+        let distance = 1 << k;
+        let select = b[k];
+        let keep = gates.not(select);
+        bits = (0..n_bit)
+            .map(|i| {
+                let source = if left { i.checked_sub(distance) } else { Some(i + distance).filter(|&j| j < n_bit) };
+                let kept = gates.and(vec![keep, bits[i]]);
+                match source {
+                    Some(j) => {
+                        let moved = gates.and(vec![select, bits[j]]);
+                        gates.or(vec![kept, moved])
+                    }
+                    None => kept,
+                }
+            })
+            .collect();
+        k += 1;
+    }
+    if k < n_bit {
+        let out_of_range = gates.or(b[k..].to_vec());
+        let in_range = gates.not(out_of_range);
+        bits = bits.iter().map(|bit| gates.and(vec![in_range, *bit])).collect();
+    }
+    bits
+}
+
+impl fmt::Display for ALU {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.base.display(f)
+    }
+}
+
+impl Component for ALU {
+    fn update(&mut self, nets: &mut Nets) {
+        for component in self.components.iter_mut() {
+            component.update(nets);
+        }
+    }
+
+    fn inputs(&self) -> &[NetId] {
+        self.base.inputs()
+    }
+
+    fn outputs(&self) -> &[NetId] {
+        self.base.outputs()
+    }
+
+    fn flatten(self: Box<Self>) -> Vec<Box<dyn Component>> {
+        self.components
+            .into_iter()
+            .flat_map(|component| component.flatten())
+            .collect()
+    }
+
+    fn set_delay(&mut self, delay: u64) {
+        for component in self.components.iter_mut() {
+            component.set_delay(delay);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::simulation::Simulator;
+
+    // Result and flags the ALU should give, worked out on integers
+    fn expected(op: Opcode, a: i64, b: i64, n_bit: u32) -> (i64, ALUFlags) {
+        let limit = 1i64 << n_bit;
+        let signed = |value: i64| if value >= limit / 2 { value - limit } else { value };
+        let (exact, signed_exact) = match op {
+            Opcode::Add => (a + b, signed(a) + signed(b)),
+            Opcode::Sub => (a - b, signed(a) - signed(b)),
+            Opcode::And => (a & b, 0),
+            Opcode::Or => (a | b, 0),
+            Opcode::Xor => (a ^ b, 0),
+            Opcode::Shl => (if b < n_bit as i64 { a << b } else { 0 }, 0),
+            Opcode::Shr => (if b < n_bit as i64 { a >> b } else { 0 }, 0),
+            Opcode::Slt => (i64::from(signed(a) < signed(b)), 0),
+        };
+        let result = exact.rem_euclid(limit);
+        let flags = ALUFlags {
+            zero: result == 0,
+            carry: match op {
+                Opcode::Add => exact >= limit,
+                Opcode::Sub | Opcode::Slt => a < b,
+                _ => false,
+            },
+            overflow: matches!(op, Opcode::Add | Opcode::Sub) && signed_exact != signed(result),
+            negative: result >= limit / 2,
+        };
+        (result, flags)
+    }
+
+    #[test]
+    fn test_calculate() {
+        let mut nets = Nets::new();
+        let mut alu = ALU::with_width(&mut nets, 4);
+        assert_eq!(alu.calculate(&mut nets, Opcode::Add, "0111", "0001"), ("1000".to_string(), ALUFlags { overflow: true, negative: true, ..ALUFlags::default() }));
+        assert_eq!(alu.calculate(&mut nets, Opcode::Sub, "0011", "0011"), ("0000".to_string(), ALUFlags { zero: true, ..ALUFlags::default() }));
+        assert_eq!(alu.calculate(&mut nets, Opcode::Xor, "1100", "1010").0, "0110");
+        assert_eq!(alu.calculate(&mut nets, Opcode::Shl, "0011", "10").0, "1100");
+        assert_eq!(alu.calculate(&mut nets, Opcode::Slt, "1111", "0001").0, "0001");
+    }
+
+    #[test]
+    fn test_all_operations() {
+        for n_bit in [1, 3, 4] {
+            let mut nets = Nets::new();
+            let mut alu = ALU::with_width(&mut nets, n_bit);
+            for op in Opcode::ALL {
+                for a in 0..1i64 << n_bit {
+                    for b in 0..1i64 << n_bit {
+                        let (result, flags) = alu.calculate(&mut nets, op, &format!("{:b}", a), &format!("{:b}", b));
+                        let result = i64::from_str_radix(&result, 2).unwrap();
+                        assert_eq!((result, flags), expected(op, a, b, n_bit as u32), "{:?} {} {} on {} bits", op, a, b, n_bit);
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_opcode_names() {
+        for op in Opcode::ALL {
+            assert_eq!(Opcode::from_name(op.name()), Some(op));
+        }
+        assert_eq!(Opcode::Slt.code(), 7);
+        assert_eq!(Opcode::from_name("mul"), None);
+    }
+
+    #[test]
+    fn test_circuit() {
+        let circuit = ALU::circuit(2);
+        assert_eq!(circuit.inputs(), &["a0", "a1", "b0", "b1", "op0", "op1", "op2"]);
+        assert_eq!(circuit.outputs(), &["result0", "result1", "zero", "carry", "overflow", "negative"]);
+
+        // 3 - 1 in the event-driven simulator
+        let mut simulator = Simulator::new(circuit);
+        for (port, level) in [("a0", 1), ("a1", 1), ("b0", 1), ("b1", 0), ("op0", 1), ("op1", 0), ("op2", 0)] {
+            simulator.set_level(port, if level == 1 { Level::High } else { Level::Low }).unwrap();
+        }
+        simulator.settle().unwrap();
+        let level = |port: &str| simulator.level(port).unwrap();
+        assert_eq!([level("result0"), level("result1"), level("zero"), level("carry")], [Level::Low, Level::High, Level::Low, Level::Low]);
+    }
+}
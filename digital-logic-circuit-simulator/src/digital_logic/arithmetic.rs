@@ -1,3 +1,18 @@
+pub mod add_subtractor;
+pub mod array_multiplier;
+pub mod carry_lookahead_adder;
+pub mod carry_save_adder;
+pub mod carry_select_adder;
+pub(crate) mod gate_list;
 pub mod ripple_carry_adder;
+pub mod subtractor;
+pub mod wallace_multiplier;
 
-pub use ripple_carry_adder::RippleCarryAdder;
\ No newline at end of file
+pub use add_subtractor::AddSubtractor;
+pub use array_multiplier::ArrayMultiplier;
+pub use carry_lookahead_adder::CarryLookaheadAdder;
+pub use carry_save_adder::CarrySaveAdder;
+pub use carry_select_adder::CarrySelectAdder;
+pub use ripple_carry_adder::RippleCarryAdder;
+pub use subtractor::{Flags, Subtractor};
+pub use wallace_multiplier::WallaceMultiplier;
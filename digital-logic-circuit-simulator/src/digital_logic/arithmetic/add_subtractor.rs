@@ -0,0 +1,162 @@
+use crate::circuits::FullAdder;
+use crate::components::gates::Buffer;
+use crate::components::{Level, NetId};
+use crate::digital_logic::arithmetic::gate_list::{
+    name_inputs, name_outputs, read_operand, set_operand, Block,
+};
+use crate::digital_logic::arithmetic::Flags;
+use crate::simulation::Circuit;
+
+// Ripple carry adder that also subtracts: with `sub` set every bit of b is inverted by an
+// XOR gate and the carry into bit 0 is 1, which adds the two's complement -b = !b + 1
+pub struct AddSubtractor {
+    input1: Vec<NetId>,
+    input2: Vec<NetId>,
+    subtract: NetId,
+    sum: Vec<NetId>,
+    carry_out: NetId,
+    carry: NetId,
+    borrow: NetId,
+    overflow: NetId,
+    negative: NetId,
+    block: Block,
+}
+
+impl AddSubtractor {
+    pub fn new(n_bit: usize) -> Self {
+        let mut block = Block::default();
+        let mut gates = block.gates();
+        let input1 = gates.new_nets(n_bit);
+        let input2 = gates.new_nets(n_bit);
+        let subtract = gates.new_net();
+        let sum = gates.new_nets(n_bit);
+
+        // Carry into every bit and out of the last one
+        let mut carries = vec![subtract];
+        for i in 0..n_bit {
+            let operand = gates.xor(input2[i], subtract);
+            let carry_out = gates.new_net();
+            let full_adder = FullAdder::new(gates.nets(), input1[i], operand, carries[i], sum[i], carry_out);
+            gates.add(full_adder);
+            carries.push(carry_out);
+        }
+
+        let carry_out = carries[n_bit];
+        // Subtracting, a carry out means that no borrow was needed
+        let adding = gates.not(subtract);
+        let carry = gates.and(vec![adding, carry_out]);
+        let no_carry = gates.not(carry_out);
+        let borrow = gates.and(vec![subtract, no_carry]);
+        // Signed overflow: the carry into the sign bit differs from the one out of it
+        let overflow = gates.xor(carries[n_bit - 1], carry_out);
+        let negative = gates.new_net();
+        gates.add(Buffer::new(sum[n_bit - 1], negative));
+
+        Self { input1, input2, subtract, sum, carry_out, carry, borrow, overflow, negative, block }
+    }
+
+    // Move the unit into a circuit with inputs a0.., b0.. and sub and outputs sum0..,
+    // carry, borrow, overflow and negative. The raw carry out is named cout.
+    pub fn into_circuit(self) -> Circuit {
+        let mut circuit = self.block.into_circuit("add_subtractor");
+        name_inputs(&mut circuit, "a", &self.input1);
+        name_inputs(&mut circuit, "b", &self.input2);
+        circuit.name_input("sub", self.subtract).unwrap();
+        name_outputs(&mut circuit, "sum", &self.sum);
+        circuit.name_net("cout", self.carry_out).unwrap();
+        circuit.name_output("carry", self.carry).unwrap();
+        circuit.name_output("borrow", self.borrow).unwrap();
+        circuit.name_output("overflow", self.overflow).unwrap();
+        circuit.name_output("negative", self.negative).unwrap();
+        circuit
+    }
+
+    // a + b, or a - b with `subtract`
+    pub fn calculate(&mut self, a: &str, b: &str, subtract: bool) -> (String, Flags) {
+        set_operand(&mut self.block.nets, &self.input1, a);
+        set_operand(&mut self.block.nets, &self.input2, b);
+        self.block.nets.set_level(self.subtract, if subtract { Level::High } else { Level::Low });
+        self.block.update();
+        let high = |net: &NetId| self.block.nets.level(*net) == Level::High;
+        let flags = Flags {
+            carry: high(&self.carry),
+            borrow: high(&self.borrow),
+            overflow: high(&self.overflow),
+            negative: high(&self.negative),
+        };
+        (read_operand(&self.block.nets, &self.sum), flags)
+    }
+
+    // Two's complement of b, computed as 0 - b. Overflows for the most negative number,
+    // which has no positive counterpart.
+    pub fn negate(&mut self, b: &str) -> (String, Flags) {
+        self.calculate("0", b, true)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::digital_logic::arithmetic::Subtractor;
+
+    #[test]
+    fn test_add_and_subtract() {
+        let mut unit = AddSubtractor::new(4);
+        assert_eq!(unit.calculate("0011", "0101", false), ("1000".to_string(), Flags { overflow: true, negative: true, ..Flags::default() }));
+        assert_eq!(unit.calculate("1111", "0001", false), ("0000".to_string(), Flags { carry: true, ..Flags::default() }));
+        assert_eq!(unit.calculate("0101", "0011", true), ("0010".to_string(), Flags::default()));
+        assert_eq!(unit.calculate("0011", "0101", true), ("1110".to_string(), Flags { borrow: true, negative: true, ..Flags::default() }));
+    }
+
+    #[test]
+    fn test_negate() {
+        let mut unit = AddSubtractor::new(4);
+        assert_eq!(unit.negate("0011").0, "1101");
+        assert_eq!(unit.negate("1101").0, "0011");
+        assert_eq!(unit.negate("0000"), ("0000".to_string(), Flags::default()));
+        let (result, flags) = unit.negate("1000");
+        assert_eq!(result, "1000");
+        assert!(flags.overflow);
+    }
+
+    #[test]
+    fn test_all_pairs() {
+        let mut unit = AddSubtractor::new(4);
+        let signed = |value: i32| if value >= 8 { value - 16 } else { value };
+        for subtract in [false, true] {
+            for a in 0..16 {
+                for b in 0..16 {
+                    let (result, flags) = unit.calculate(&format!("{:b}", a), &format!("{:b}", b), subtract);
+                    let result = i32::from_str_radix(&result, 2).unwrap();
+                    let (exact, signed_exact) = if subtract { (a - b, signed(a) - signed(b)) } else { (a + b, signed(a) + signed(b)) };
+                    assert_eq!(result, exact.rem_euclid(16));
+                    assert_eq!(flags.carry, exact > 15);
+                    assert_eq!(flags.borrow, exact < 0);
+                    assert_eq!(flags.overflow, signed_exact != signed(result));
+                    assert_eq!(flags.negative, result >= 8);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_matches_subtractor() {
+        for bits in [1, 5] {
+            let mut unit = AddSubtractor::new(bits);
+            let mut subtractor = Subtractor::new(bits);
+            for a in 0..1u32 << bits {
+                for b in 0..1u32 << bits {
+                    let (a, b) = (format!("{:b}", a), format!("{:b}", b));
+                    assert_eq!(unit.calculate(&a, &b, true), subtractor.calculate(&a, &b));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_into_circuit() {
+        let circuit = AddSubtractor::new(2).into_circuit();
+        assert_eq!(circuit.inputs(), &["a0", "a1", "b0", "b1", "sub"]);
+        assert_eq!(circuit.outputs(), &["sum0", "sum1", "carry", "borrow", "overflow", "negative"]);
+    }
+}
@@ -0,0 +1,97 @@
+use crate::components::NetId;
+use crate::digital_logic::arithmetic::gate_list::{
+    name_inputs, name_outputs, read_operand, set_operand, Block,
+};
+use crate::simulation::Circuit;
+
+// Multiplier adding one row of partial products a & b[i] after the other, each row through
+// a ripple carry chain of half and full adders, like long multiplication on paper. The
+// carries of every row pass through the rows below it, so the delay grows with n + m.
+pub struct ArrayMultiplier {
+    input1: Vec<NetId>,
+    input2: Vec<NetId>,
+    product: Vec<NetId>,
+    block: Block,
+}
+
+impl ArrayMultiplier {
+    // Multiplier of an n-bit by an m-bit number, with an (n + m)-bit product
+    pub fn new(n_bit: usize, m_bit: usize) -> Self {
+        let mut block = Block::default();
+        let mut gates = block.gates();
+        let input1 = gates.new_nets(n_bit);
+        let input2 = gates.new_nets(m_bit);
+
+        // Running sum of the rows so far, from the weight of the current row up
+        let mut sum: Vec<NetId> = input1.iter().map(|a| gates.and(vec![*a, input2[0]])).collect();
+        let mut carry: Option<NetId> = None;
+        let mut product = Vec::new();
+        for b in &input2[1..] {
+            // The lowest bit of the running sum is final
+            product.push(sum[0]);
+            let mut row_carry: Option<NetId> = None;
+            let mut row_sum = Vec::new();
+            for (j, a) in input1.iter().enumerate() {
+                let mut bits = vec![gates.and(vec![*a, *b])];
+                // The running sum one bit up has the same weight, above it is the last carry
+                bits.extend(if j + 1 < n_bit { Some(sum[j + 1]) } else { carry });
+                bits.extend(row_carry);
+                let (bit, carry_out) = gates.add_bits(&bits);
+                row_sum.push(bit);
+                row_carry = carry_out;
+            }
+            sum = row_sum;
+            carry = row_carry;
+        }
+        product.extend(sum);
+        product.push(carry.unwrap_or_else(|| gates.low()));
+
+        Self { input1, input2, product, block }
+    }
+
+    // Move the multiplier into a circuit with inputs a0.., b0.. and outputs product0..
+    pub fn into_circuit(self) -> Circuit {
+        let mut circuit = self.block.into_circuit("array_multiplier");
+        name_inputs(&mut circuit, "a", &self.input1);
+        name_inputs(&mut circuit, "b", &self.input2);
+        name_outputs(&mut circuit, "product", &self.product);
+        circuit
+    }
+
+    pub fn calculate(&mut self, a: &str, b: &str) -> String {
+        set_operand(&mut self.block.nets, &self.input1, a);
+        set_operand(&mut self.block.nets, &self.input2, b);
+        self.block.update();
+        read_operand(&self.block.nets, &self.product)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_calculate() {
+        let mut multiplier = ArrayMultiplier::new(4, 4);
+        assert_eq!(multiplier.calculate("0011", "0101"), "00001111");
+        assert_eq!(multiplier.calculate("1111", "1111"), "11100001");
+
+        for (n, m) in [(1, 1), (1, 3), (3, 1), (4, 3), (5, 5)] {
+            let mut multiplier = ArrayMultiplier::new(n, m);
+            for a in 0..1u32 << n {
+                for b in 0..1u32 << m {
+                    let product = multiplier.calculate(&format!("{:b}", a), &format!("{:b}", b));
+                    assert_eq!(product.len(), n + m);
+                    assert_eq!(u32::from_str_radix(&product, 2).unwrap(), a * b, "{} * {}", a, b);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_into_circuit() {
+        let circuit = ArrayMultiplier::new(2, 1).into_circuit();
+        assert_eq!(circuit.inputs(), &["a0", "a1", "b0"]);
+        assert_eq!(circuit.outputs(), &["product0", "product1", "product2"]);
+    }
+}
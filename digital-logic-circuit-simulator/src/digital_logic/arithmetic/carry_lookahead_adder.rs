@@ -0,0 +1,169 @@
+use crate::digital_logic::arithmetic::gate_list::{
+    name_inputs, name_outputs, read_operand, set_operand, Block, GateList,
+};
+use crate::components::{Level, NetId};
+use crate::simulation::Circuit;
+
+// Bits, and at higher levels groups, combined by one lookahead unit
+const GROUP_SIZE: usize = 4;
+
+// Adder computing every carry from generate (a & b) and propagate (a ^ b) signals instead
+// of waiting for the carry of the bit below. Groups of four bits are combined into group
+// signals, which are combined the same way, so the carry path grows with the logarithm
+// of the width rather than with the width.
+pub struct CarryLookaheadAdder {
+    n_bit: usize,
+    input1: Vec<NetId>,
+    input2: Vec<NetId>,
+    sum: Vec<NetId>,
+    // Carry out of every bit
+    carry: Vec<NetId>,
+    block: Block,
+}
+
+impl CarryLookaheadAdder {
+    pub fn new(n_bit: usize) -> Self {
+        let mut block = Block::default();
+        let mut gates = block.gates();
+        let input1 = gates.new_nets(n_bit);
+        let input2 = gates.new_nets(n_bit);
+        let sum = gates.new_nets(n_bit);
+
+        let generate: Vec<NetId> = (0..n_bit).map(|i| gates.and(vec![input1[i], input2[i]])).collect();
+        // Without a carry in, the propagate signal of bit 0 is its sum
+        gates.xor_into(input1[0], input2[0], sum[0]);
+        let mut propagate = vec![sum[0]];
+        propagate.extend((1..n_bit).map(|i| gates.xor(input1[i], input2[i])));
+
+        let carry = carries(&mut gates, &generate, &propagate, None);
+        for i in 1..n_bit {
+            gates.xor_into(propagate[i], carry[i - 1], sum[i]);
+        }
+
+        Self { n_bit, input1, input2, sum, carry, block }
+    }
+
+    // Move the adder into a circuit, with the same ports and carry names as `RippleCarryAdder`
+    pub fn into_circuit(self) -> Circuit {
+        let mut circuit = self.block.into_circuit("carry_lookahead_adder");
+        name_inputs(&mut circuit, "a", &self.input1);
+        name_inputs(&mut circuit, "b", &self.input2);
+        name_outputs(&mut circuit, "sum", &self.sum);
+        for (i, &net) in self.carry.iter().enumerate() {
+            if i + 1 == self.n_bit {
+                circuit.name_output(&format!("carry{}", i), net).unwrap();
+            } else {
+                circuit.name_net(&format!("carry{}", i), net).unwrap();
+            }
+        }
+        circuit
+    }
+
+    pub fn calculate(&mut self, a: &str, b: &str) -> (String, bool) {
+        set_operand(&mut self.block.nets, &self.input1, a);
+        set_operand(&mut self.block.nets, &self.input2, b);
+        self.block.update();
+        let overflow = self.block.nets.level(*self.carry.last().unwrap()) == Level::High;
+        (read_operand(&self.block.nets, &self.sum), overflow)
+    }
+}
+
+// Carry out of every position for the given generate and propagate signals
+fn carries(gates: &mut GateList, generate: &[NetId], propagate: &[NetId], carry_in: Option<NetId>) -> Vec<NetId> {
+    if generate.len() <= GROUP_SIZE {
+        return lookahead(gates, generate, propagate, carry_in.as_ref(), generate.len());
+    }
+
+    // A group generates a carry if some position generates one that all positions above it
+    // propagate, and propagates one if all its positions do
+    let groups: Vec<(usize, usize)> =
+        (0..generate.len()).step_by(GROUP_SIZE).map(|start| (start, (start + GROUP_SIZE).min(generate.len()))).collect();
+    let group_generate: Vec<NetId> = groups
+        .iter()
+        .map(|&(start, end)| carry_out(gates, &generate[start..end], &propagate[start..end], None, end - start - 1))
+        .collect();
+    let group_propagate: Vec<NetId> = groups
+        .iter()
+        .map(|&(start, end)| {
+            if start == 0 && carry_in.is_none() {
+                // Only read together with a carry in, so no gate is needed
+                propagate[0]
+            } else {
+                gates.and(propagate[start..end].to_vec())
+            }
+        })
+        .collect();
+    let group_carries = carries(gates, &group_generate, &group_propagate, carry_in);
+
+    let mut result = Vec::new();
+    for (j, &(start, end)) in groups.iter().enumerate() {
+        let group_carry_in = if j == 0 { carry_in } else { Some(group_carries[j - 1]) };
+        // The carry out of the group's last position is the group carry
+        let inner = lookahead(gates, &generate[start..end], &propagate[start..end], group_carry_in.as_ref(), end - start - 1);
+        result.extend(inner);
+        result.push(group_carries[j]);
+    }
+    result
+}
+
+// Carries out of the first `count` positions
+fn lookahead(gates: &mut GateList, generate: &[NetId], propagate: &[NetId], carry_in: Option<&NetId>, count: usize) -> Vec<NetId> {
+    (0..count).map(|i| carry_out(gates, generate, propagate, carry_in, i)).collect()
+}
+
+// Carry out of position i as one OR of AND terms:
+// c[i] = g[i] | p[i] & g[i - 1] | p[i] & p[i - 1] & g[i - 2] | .. | p[i] & .. & p[0] & carry_in
+fn carry_out(gates: &mut GateList, generate: &[NetId], propagate: &[NetId], carry_in: Option<&NetId>, i: usize) -> NetId {
+    let mut terms = vec![generate[i]];
+    for j in (0..i).rev() {
+        let mut inputs = propagate[j + 1..=i].to_vec();
+        inputs.push(generate[j]);
+        terms.push(gates.and(inputs));
+    }
+    if let Some(carry_in) = carry_in {
+        let mut inputs = propagate[..=i].to_vec();
+        inputs.push(*carry_in);
+        terms.push(gates.and(inputs));
+    }
+    gates.or(terms)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analysis::{check_equivalence_with, logic_depth, Method};
+    use crate::digital_logic::arithmetic::RippleCarryAdder;
+
+    #[test]
+    fn test_calculate() {
+        let mut adder = CarryLookaheadAdder::new(4);
+        assert_eq!(adder.calculate("0011", "0101"), ("1000".to_string(), false));
+        assert_eq!(adder.calculate("1111", "1"), ("0000".to_string(), true));
+
+        let mut adder = CarryLookaheadAdder::new(6);
+        for a in 0..64u32 {
+            for b in 0..64u32 {
+                let (sum, overflow) = adder.calculate(&format!("{:b}", a), &format!("{:b}", b));
+                assert_eq!(u32::from_str_radix(&sum, 2).unwrap(), (a + b) % 64);
+                assert_eq!(overflow, a + b >= 64);
+            }
+        }
+    }
+
+    #[test]
+    fn test_equivalent_to_ripple_adder() {
+        // One, two and three levels of lookahead
+        for bits in [3, 13, 20] {
+            let lookahead = CarryLookaheadAdder::new(bits).into_circuit();
+            let ripple = RippleCarryAdder::new(bits).into_circuit();
+            assert_eq!(check_equivalence_with(lookahead, ripple, Method::Bdd), Ok(None));
+        }
+    }
+
+    #[test]
+    fn test_depth() {
+        let lookahead = logic_depth(&CarryLookaheadAdder::new(32).into_circuit()).unwrap();
+        let ripple = logic_depth(&RippleCarryAdder::new(32).into_circuit()).unwrap();
+        assert!(lookahead * 4 < ripple, "{} against {}", lookahead, ripple);
+    }
+}
@@ -0,0 +1,117 @@
+use crate::circuits::{FullAdder, HalfAdder};
+use crate::digital_logic::arithmetic::gate_list::{
+    name_inputs, name_outputs, read_operand, set_operand, Block,
+};
+use crate::components::{Level, NetId};
+use crate::simulation::Circuit;
+
+// Adder for three operands. A row of full adders reduces them to a sum word and a carry
+// word without passing carries between bits; only the final addition of those two words,
+// done by a ripple carry chain, propagates carries. Chaining such rows is how multipliers
+// sum many partial products with a single carry-propagating addition at the end.
+pub struct CarrySaveAdder {
+    input1: Vec<NetId>,
+    input2: Vec<NetId>,
+    input3: Vec<NetId>,
+    sum: Vec<NetId>,
+    // Set when the result does not fit into the width of the operands
+    overflow: NetId,
+    block: Block,
+}
+
+impl CarrySaveAdder {
+    pub fn new(n_bit: usize) -> Self {
+        let mut block = Block::default();
+        let mut gates = block.gates();
+        let input1 = gates.new_nets(n_bit);
+        let input2 = gates.new_nets(n_bit);
+        let input3 = gates.new_nets(n_bit);
+        let sum = gates.new_nets(n_bit);
+
+        // The partial sum of bit 0 already is the final one
+        let mut partial = vec![sum[0]];
+        partial.extend(gates.new_nets(n_bit - 1));
+        // Carry out of every bit, worth the next bit up
+        let saved = gates.new_nets(n_bit);
+        for i in 0..n_bit {
+            let full_adder = FullAdder::new(gates.nets(), input1[i], input2[i], input3[i], partial[i], saved[i]);
+            gates.add(full_adder);
+        }
+
+        // Add the carry word, shifted up by one bit, to the partial sums
+        let mut carry: Option<NetId> = None;
+        for i in 1..n_bit {
+            let carry_out = gates.new_net();
+            match carry {
+                None => gates.add(HalfAdder::new(partial[i], saved[i - 1], sum[i], carry_out)),
+                Some(carry) => {
+                    let full_adder = FullAdder::new(gates.nets(), partial[i], saved[i - 1], carry, sum[i], carry_out);
+                    gates.add(full_adder);
+                }
+            }
+            carry = Some(carry_out);
+        }
+        // Both the last saved carry and the last carry of the final addition are worth 2^n
+        let last_saved = saved[n_bit - 1];
+        let overflow = match carry {
+            Some(carry) => gates.or(vec![last_saved, carry]),
+            None => last_saved,
+        };
+
+        Self { input1, input2, input3, sum, overflow, block }
+    }
+
+    // Move the adder into a circuit with inputs a0.., b0.., c0.. and outputs sum0.. and overflow
+    pub fn into_circuit(self) -> Circuit {
+        let mut circuit = self.block.into_circuit("carry_save_adder");
+        name_inputs(&mut circuit, "a", &self.input1);
+        name_inputs(&mut circuit, "b", &self.input2);
+        name_inputs(&mut circuit, "c", &self.input3);
+        name_outputs(&mut circuit, "sum", &self.sum);
+        circuit.name_output("overflow", self.overflow).unwrap();
+        circuit
+    }
+
+    // Add three binary numbers, returning the sum and whether it overflowed
+    pub fn calculate(&mut self, a: &str, b: &str, c: &str) -> (String, bool) {
+        set_operand(&mut self.block.nets, &self.input1, a);
+        set_operand(&mut self.block.nets, &self.input2, b);
+        set_operand(&mut self.block.nets, &self.input3, c);
+        self.block.update();
+        let overflow = self.block.nets.level(self.overflow) == Level::High;
+        (read_operand(&self.block.nets, &self.sum), overflow)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_calculate() {
+        let mut adder = CarrySaveAdder::new(4);
+        assert_eq!(adder.calculate("0011", "0101", "0001"), ("1001".to_string(), false));
+        assert_eq!(adder.calculate("1111", "1111", "1111"), ("1101".to_string(), true));
+
+        for bits in [1, 2, 4] {
+            let mut adder = CarrySaveAdder::new(bits);
+            let limit = 1u32 << bits;
+            for a in 0..limit {
+                for b in 0..limit {
+                    for c in 0..limit {
+                        let (sum, overflow) = adder.calculate(&format!("{:b}", a), &format!("{:b}", b), &format!("{:b}", c));
+                        assert_eq!(u32::from_str_radix(&sum, 2).unwrap(), (a + b + c) % limit, "{} + {} + {}", a, b, c);
+                        assert_eq!(overflow, a + b + c >= limit);
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_into_circuit() {
+        let circuit = CarrySaveAdder::new(3).into_circuit();
+        assert_eq!(circuit.inputs(), &["a0", "a1", "a2", "b0", "b1", "b2", "c0", "c1", "c2"]);
+        assert_eq!(circuit.outputs(), &["sum0", "sum1", "sum2", "overflow"]);
+    }
+}
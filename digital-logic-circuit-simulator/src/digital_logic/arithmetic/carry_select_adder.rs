@@ -0,0 +1,139 @@
+use crate::circuits::{FullAdder, HalfAdder};
+use crate::digital_logic::arithmetic::gate_list::{
+    name_inputs, name_outputs, read_operand, set_operand, Block, GateList,
+};
+use crate::components::{Level, NetId};
+use crate::simulation::Circuit;
+
+// Bits added by one pair of ripple carry chains
+const BLOCK_SIZE: usize = 4;
+
+// Adder splitting the operands into blocks of four bits. Every block but the first is added
+// twice, assuming a carry in of 0 and of 1, while the carries ripple; the carry out of the
+// block below then only has to pick one of the results through a multiplexer.
+pub struct CarrySelectAdder {
+    input1: Vec<NetId>,
+    input2: Vec<NetId>,
+    sum: Vec<NetId>,
+    // Carry out of the last bit of every block, with that bit's index
+    carry: Vec<(usize, NetId)>,
+    block: Block,
+}
+
+impl CarrySelectAdder {
+    pub fn new(n_bit: usize) -> Self {
+        let mut block = Block::default();
+        let mut gates = block.gates();
+        let input1 = gates.new_nets(n_bit);
+        let input2 = gates.new_nets(n_bit);
+        let sum = gates.new_nets(n_bit);
+
+        let first = BLOCK_SIZE.min(n_bit);
+        let mut carry = vec![(first - 1, ripple(&mut gates, &input1[..first], &input2[..first], &sum[..first], false))];
+
+        for start in (first..n_bit).step_by(BLOCK_SIZE) {
+            let end = (start + BLOCK_SIZE).min(n_bit);
+            let (a, b) = (&input1[start..end], &input2[start..end]);
+            let low_sum = gates.new_nets(end - start);
+            let high_sum = gates.new_nets(end - start);
+            let low_carry = ripple(&mut gates, a, b, &low_sum, false);
+            let high_carry = ripple(&mut gates, a, b, &high_sum, true);
+
+            let select = carry.last().unwrap().1;
+            let not_select = gates.not(select);
+            for (k, &output) in sum[start..end].iter().enumerate() {
+                gates.mux_into(select, not_select, low_sum[k], high_sum[k], output);
+            }
+            let block_carry = gates.new_net();
+            gates.mux_into(select, not_select, low_carry, high_carry, block_carry);
+            carry.push((end - 1, block_carry));
+        }
+
+        Self { input1, input2, sum, carry, block }
+    }
+
+    // Move the adder into a circuit, with the same ports as `RippleCarryAdder`.
+    // The carries out of the blocks are named after their bit, like carry3.
+    pub fn into_circuit(self) -> Circuit {
+        let mut circuit = self.block.into_circuit("carry_select_adder");
+        name_inputs(&mut circuit, "a", &self.input1);
+        name_inputs(&mut circuit, "b", &self.input2);
+        name_outputs(&mut circuit, "sum", &self.sum);
+        let last = self.carry.len() - 1;
+        for (k, &(bit, net)) in self.carry.iter().enumerate() {
+            if k == last {
+                circuit.name_output(&format!("carry{}", bit), net).unwrap();
+            } else {
+                circuit.name_net(&format!("carry{}", bit), net).unwrap();
+            }
+        }
+        circuit
+    }
+
+    pub fn calculate(&mut self, a: &str, b: &str) -> (String, bool) {
+        set_operand(&mut self.block.nets, &self.input1, a);
+        set_operand(&mut self.block.nets, &self.input2, b);
+        self.block.update();
+        let overflow = self.block.nets.level(self.carry.last().unwrap().1) == Level::High;
+        (read_operand(&self.block.nets, &self.sum), overflow)
+    }
+}
+
+// Ripple carry chain with a fixed carry in driving `sum`, returning the carry out.
+// The carry in is folded into the gates of the first bit.
+fn ripple(gates: &mut GateList, a: &[NetId], b: &[NetId], sum: &[NetId], carry_in: bool) -> NetId {
+    let mut carry = gates.new_net();
+    if carry_in {
+        // a + b + 1: the sum is a XNOR b and a carry comes out if either is 1
+        gates.xnor_into(a[0], b[0], sum[0]);
+        carry = gates.or(vec![a[0], b[0]]);
+    } else {
+        gates.add(HalfAdder::new(a[0], b[0], sum[0], carry));
+    }
+    for i in 1..a.len() {
+        let carry_out = gates.new_net();
+        let full_adder = FullAdder::new(gates.nets(), a[i], b[i], carry, sum[i], carry_out);
+        gates.add(full_adder);
+        carry = carry_out;
+    }
+    carry
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analysis::{check_equivalence_with, logic_depth, Method};
+    use crate::digital_logic::arithmetic::RippleCarryAdder;
+
+    #[test]
+    fn test_calculate() {
+        let mut adder = CarrySelectAdder::new(4);
+        assert_eq!(adder.calculate("0011", "0101"), ("1000".to_string(), false));
+        assert_eq!(adder.calculate("1111", "1"), ("0000".to_string(), true));
+
+        let mut adder = CarrySelectAdder::new(7);
+        for a in 0..128u32 {
+            for b in 0..128u32 {
+                let (sum, overflow) = adder.calculate(&format!("{:b}", a), &format!("{:b}", b));
+                assert_eq!(u32::from_str_radix(&sum, 2).unwrap(), (a + b) % 128);
+                assert_eq!(overflow, a + b >= 128);
+            }
+        }
+    }
+
+    #[test]
+    fn test_equivalent_to_ripple_adder() {
+        for bits in [1, 4, 5, 16] {
+            let select = CarrySelectAdder::new(bits).into_circuit();
+            let ripple = RippleCarryAdder::new(bits).into_circuit();
+            assert_eq!(check_equivalence_with(select, ripple, Method::Bdd), Ok(None));
+        }
+    }
+
+    #[test]
+    fn test_depth() {
+        let select = logic_depth(&CarrySelectAdder::new(32).into_circuit()).unwrap();
+        let ripple = logic_depth(&RippleCarryAdder::new(32).into_circuit()).unwrap();
+        assert!(select * 2 < ripple, "{} against {}", select, ripple);
+    }
+}
@@ -0,0 +1,164 @@
+use crate::circuits::{FullAdder, HalfAdder};
+use crate::components::gates::{ANDGate, NOTGate, ORGate, XNORGate, XORGate};
+use crate::components::{Component, Level, NetId, Nets};
+use crate::simulation::Circuit;
+
+// Drive the nets with a binary number, least significant bit first and padded with zeros
+pub(crate) fn set_operand(nets: &mut Nets, operand: &[NetId], binary: &str) {
+    let bits: Vec<char> = format!("{:0>width$}", binary, width = operand.len()).chars().rev().collect();
+    for (&net, bit) in operand.iter().zip(bits) {
+        nets.set_level(net, if bit == '1' { Level::High } else { Level::Low });
+    }
+}
+
+// Levels of the nets as a binary number, the last net being the most significant bit
+pub(crate) fn read_operand(nets: &Nets, operand: &[NetId]) -> String {
+    operand.iter().rev().map(|&net| if nets.level(net) == Level::High { '1' } else { '0' }).collect()
+}
+
+// Make the nets the input ports prefix0, prefix1..
+pub(crate) fn name_inputs(circuit: &mut Circuit, prefix: &str, nets: &[NetId]) {
+    for (i, &net) in nets.iter().enumerate() {
+        circuit.name_input(&format!("{}{}", prefix, i), net).unwrap();
+    }
+}
+
+// Make the nets the output ports prefix0, prefix1..
+pub(crate) fn name_outputs(circuit: &mut Circuit, prefix: &str, nets: &[NetId]) {
+    for (i, &net) in nets.iter().enumerate() {
+        circuit.name_output(&format!("{}{}", prefix, i), net).unwrap();
+    }
+}
+
+// Nets and components of an arithmetic block in the order they were created. Every component
+// is created after the ones driving its inputs, so updating them in order evaluates the block.
+#[derive(Default)]
+pub(crate) struct Block {
+    pub nets: Nets,
+    components: Vec<Box<dyn Component>>,
+}
+
+impl Block {
+    // Gates added through the list become part of the block
+    pub fn gates(&mut self) -> GateList<'_> {
+        GateList::new(&mut self.nets, &mut self.components)
+    }
+
+    pub fn update(&mut self) {
+        for component in self.components.iter_mut() {
+            component.update(&mut self.nets);
+        }
+    }
+
+    // Circuit over the nets of the block, its ports still to be named
+    pub fn into_circuit(self, name: &str) -> Circuit {
+        let mut circuit = Circuit::from_nets(self.nets).with_name(name);
+        for component in self.components {
+            circuit.add_component(component);
+        }
+        circuit
+    }
+}
+
+// Adds gates to a list of components, creating the nets between them
+pub(crate) struct GateList<'a> {
+    nets: &'a mut Nets,
+    components: &'a mut Vec<Box<dyn Component>>,
+}
+
+impl<'a> GateList<'a> {
+    pub fn new(nets: &'a mut Nets, components: &'a mut Vec<Box<dyn Component>>) -> GateList<'a> {
+        GateList { nets, components }
+    }
+
+    // Nets to wire the inside of composite components to
+    pub fn nets(&mut self) -> &mut Nets {
+        self.nets
+    }
+
+    pub fn new_net(&mut self) -> NetId {
+        self.nets.add()
+    }
+
+    pub fn new_nets(&mut self, count: usize) -> Vec<NetId> {
+        self.nets.add_many(count)
+    }
+
+    // Net nothing drives, held at 0
+    pub fn low(&mut self) -> NetId {
+        let net = self.nets.add();
+        self.nets.set_level(net, Level::Low);
+        net
+    }
+
+    pub fn add(&mut self, component: impl Component + 'static) {
+        self.components.push(Box::new(component));
+    }
+
+    // Output of an AND of the inputs, the input itself if there is only one
+    pub fn and(&mut self, inputs: Vec<NetId>) -> NetId {
+        if inputs.len() == 1 {
+            return inputs[0];
+        }
+        let output = self.nets.add();
+        self.add(ANDGate::with_inputs(inputs, output));
+        output
+    }
+
+    // Output of an OR of the inputs, the input itself if there is only one
+    pub fn or(&mut self, inputs: Vec<NetId>) -> NetId {
+        if inputs.len() == 1 {
+            return inputs[0];
+        }
+        let output = self.nets.add();
+        self.add(ORGate::with_inputs(inputs, output));
+        output
+    }
+
+    pub fn xor_into(&mut self, a: NetId, b: NetId, output: NetId) {
+        self.add(XORGate::new(a, b, output));
+    }
+
+    pub fn xnor_into(&mut self, a: NetId, b: NetId, output: NetId) {
+        self.add(XNORGate::new(a, b, output));
+    }
+
+    pub fn xor(&mut self, a: NetId, b: NetId) -> NetId {
+        let output = self.nets.add();
+        self.xor_into(a, b, output);
+        output
+    }
+
+    pub fn not(&mut self, input: NetId) -> NetId {
+        let output = self.nets.add();
+        self.add(NOTGate::new(input, output));
+        output
+    }
+
+    // Two-input multiplexer driving `output` with `high` when `select` is 1, otherwise with `low`.
+    // `not_select` is the inverted select, so several multiplexers can share one inverter.
+    pub fn mux_into(&mut self, select: NetId, not_select: NetId, low: NetId, high: NetId, output: NetId) {
+        let take_high = self.and(vec![select, high]);
+        let take_low = self.and(vec![not_select, low]);
+        self.add(ORGate::new(take_high, take_low, output));
+    }
+
+    // Sum of one to three bits of the same weight, with the carry worth twice as much.
+    // A single bit is its own sum, two go through a half adder and three through a full adder.
+    pub fn add_bits(&mut self, bits: &[NetId]) -> (NetId, Option<NetId>) {
+        if let [bit] = bits {
+            return (*bit, None);
+        }
+        let sum = self.nets.add();
+        let carry = self.nets.add();
+        match *bits {
+            [a, b] => self.add(HalfAdder::new(a, b, sum, carry)),
+            [a, b, c] => {
+                let full_adder = FullAdder::new(self.nets(), a, b, c, sum, carry);
+                self.add(full_adder);
+            }
+            _ => panic!("Cannot add {} bits at once", bits.len()),
+        }
+        (sum, Some(carry))
+    }
+}
@@ -1,56 +1,39 @@
-use std::cell::RefCell;
-use std::rc::Rc;
-
-use crate::components::{Component, Connection, Level};
+use crate::components::{Component, Level, NetId, Nets};
 use crate::circuits::{FullAdder, HalfAdder};
+use crate::components::gates::GateKind;
+use crate::simulation::{ArenaBuilder, ArenaCircuit, Circuit};
 
 pub struct RippleCarryAdder {
     n_bit: usize,
-    input1: Vec<Rc<RefCell<Connection>>>,
-    input2: Vec<Rc<RefCell<Connection>>>,
-    sum: Vec<Rc<RefCell<Connection>>>,
-    carry: Vec<Rc<RefCell<Connection>>>,
+    nets: Nets,
+    input1: Vec<NetId>,
+    input2: Vec<NetId>,
+    sum: Vec<NetId>,
+    carry: Vec<NetId>,
     half_adder: HalfAdder,
     full_adders: Vec<FullAdder>,
 }
 
 impl RippleCarryAdder {
     pub fn new(n_bit: usize) -> Self {
-        let input1 : Vec<Rc<RefCell<Connection>>> = (0..n_bit)
-            .map(|_| Rc::new(RefCell::new(Connection::new())))
-            .collect();
-        let input2 : Vec<Rc<RefCell<Connection>>> = (0..n_bit)
-            .map(|_| Rc::new(RefCell::new(Connection::new())))
-            .collect();
-        let sum: Vec<_> = (0..n_bit)
-            .map(|_| Rc::new(RefCell::new(Connection::new())))
-            .collect();
-        let carry: Vec<_> = (0..n_bit)
-            .map(|_| Rc::new(RefCell::new(Connection::new())))
-            .collect();
+        let mut nets = Nets::new();
+        let input1 = nets.add_many(n_bit);
+        let input2 = nets.add_many(n_bit);
+        let sum = nets.add_many(n_bit);
+        let carry = nets.add_many(n_bit);
 
-        let half_adder = HalfAdder::new(
-            Rc::clone(&input1[0]),
-            Rc::clone(&input2[0]),
-            Rc::clone(&sum[0]),
-            Rc::clone(&carry[0]),
-        );
+        let half_adder = HalfAdder::new(input1[0], input2[0], sum[0], carry[0]);
 
         // Create full adders for remaining bits
         let mut full_adders = Vec::new();
         for i in 1..n_bit {
-            let full_adder = FullAdder::new(
-                Rc::clone(&input1[i]),
-                Rc::clone(&input2[i]),
-                Rc::clone(&carry[i - 1]),
-                Rc::clone(&sum[i]),
-                Rc::clone(&carry[i]),
-            );
+            let full_adder = FullAdder::new(&mut nets, input1[i], input2[i], carry[i - 1], sum[i], carry[i]);
             full_adders.push(full_adder);
         }
 
         Self {
             n_bit,
+            nets,
             input1,
             input2,
             sum,
@@ -60,44 +43,171 @@ impl RippleCarryAdder {
         }
     }
 
-    fn string_to_connections(&self, binary_str: &str) -> Vec<Rc<RefCell<Connection>>> {
-        binary_str
-            .chars()
-            .rev()
-            .map(|c| {
-                let conn = Rc::new(RefCell::new(Connection::new()));
-                conn.borrow_mut().set_level(if c == '1' { Level::High } else { Level::Low });
-                conn
+    // Move the adder into a circuit for event-driven simulation.
+    // Ports are named a0.., b0.., sum0.. and the final carry, with bit 0 being the least significant.
+    // The carries between stages are named carry0.. as well, and the stages are the instances
+    // ha0, fa1.., so `fa3.ha1.sum` is a net inside the fourth stage.
+    pub fn into_circuit(self) -> Circuit {
+        let mut circuit = Circuit::from_nets(self.nets).with_name("ripple_carry_adder");
+        for (prefix, nets) in [("a", &self.input1), ("b", &self.input2)] {
+            for (i, &net) in nets.iter().enumerate() {
+                circuit.name_input(&format!("{}{}", prefix, i), net).unwrap();
+            }
+        }
+        for (i, &net) in self.sum.iter().enumerate() {
+            circuit.name_output(&format!("sum{}", i), net).unwrap();
+        }
+        for (i, &net) in self.carry.iter().enumerate() {
+            if i + 1 == self.n_bit {
+                circuit.name_output(&format!("carry{}", i), net).unwrap();
+            } else {
+                circuit.name_net(&format!("carry{}", i), net).unwrap();
+            }
+        }
+        circuit.add_instance("ha0", Box::new(self.half_adder));
+        for (i, adder) in self.full_adders.into_iter().enumerate() {
+            circuit.add_instance(&format!("fa{}", i + 1), Box::new(adder));
+        }
+        circuit
+    }
+
+    // Build the same gates and net names as into_circuit directly in the arena engine,
+    // without creating components first
+    pub fn arena_circuit(n_bit: usize) -> ArenaCircuit {
+        assert!(n_bit > 0, "A ripple carry adder needs at least one bit");
+        let mut builder = ArenaBuilder::new("ripple_carry_adder");
+        let a: Vec<_> = (0..n_bit).map(|i| builder.add_input(&format!("a{}", i))).collect();
+        let b: Vec<_> = (0..n_bit).map(|i| builder.add_input(&format!("b{}", i))).collect();
+        let sum: Vec<_> = (0..n_bit).map(|i| builder.add_output(&format!("sum{}", i))).collect();
+        let carry: Vec<_> = (0..n_bit)
+            .map(|i| {
+                let name = format!("carry{}", i);
+                if i + 1 == n_bit { builder.add_output(&name) } else { builder.add_net(&name) }
             })
-            .collect()
+            .collect();
+
+        builder.add_gate(GateKind::Xor, &[a[0], b[0]], sum[0]);
+        builder.add_gate(GateKind::And, &[a[0], b[0]], carry[0]);
+        for i in 1..n_bit {
+            // The nets of FullAdder: s1 and c1 from the first half adder, c2 from the second
+            let [s1, c1, c2] = ["s1", "c1", "c2"].map(|net| builder.add_net(&format!("fa{}.{}", i, net)));
+            builder.add_gate(GateKind::Xor, &[a[i], b[i]], s1);
+            builder.add_gate(GateKind::And, &[a[i], b[i]], c1);
+            builder.add_gate(GateKind::Xor, &[s1, carry[i - 1]], sum[i]);
+            builder.add_gate(GateKind::And, &[s1, carry[i - 1]], c2);
+            builder.add_gate(GateKind::Or, &[c1, c2], carry[i]);
+        }
+        builder.build().unwrap()
     }
 
-    fn connections_to_string(&self, connections: &[Rc<RefCell<Connection>>]) -> String {
+    fn connections_to_string(&self, connections: &[NetId]) -> String {
         connections
             .iter()
             .rev()
-            .map(|conn| if conn.borrow().level() == Level::High { '1' } else { '0' })
+            .map(|&net| if self.nets.level(net) == Level::High { '1' } else { '0' })
             .collect()
     }
 
     pub fn calculate(&mut self, a: &str, b: &str) -> (String, bool) {
-        let input1 = self.string_to_connections(&format!("{:0>width$}", a, width = self.n_bit));
-        let input2 = self.string_to_connections(&format!("{:0>width$}", b, width = self.n_bit));
-        for i in 0..self.n_bit {
-            self.input1[i].borrow_mut().set_level(input1[i].borrow().level());
-            self.input2[i].borrow_mut().set_level(input2[i].borrow().level());
+        // Bit i of the operands is the i-th character from the right
+        for (inputs, operand) in [(&self.input1, a), (&self.input2, b)] {
+            let mut bits = operand.chars().rev();
+            for input in inputs {
+                let level = if bits.next() == Some('1') { Level::High } else { Level::Low };
+                self.nets.set_level(*input, level);
+            }
         }
 
-        self.half_adder.update();
+        self.half_adder.update(&mut self.nets);
         for adder in self.full_adders.iter_mut() {
-            adder.update();
+            adder.update(&mut self.nets);
         }
 
 
         // Collect the sum as a string and determine overflow
         let sum_str = self.connections_to_string(&self.sum);
-        let overflow = self.carry.last().unwrap().borrow().level() == Level::High;
+        let overflow = self.nets.level(*self.carry.last().unwrap()) == Level::High;
 
         (sum_str, overflow)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::simulation::Simulator;
+
+    #[test]
+    fn test_calculate() {
+        let mut adder = RippleCarryAdder::new(4);
+        assert_eq!(adder.calculate("0011", "0101"), ("1000".to_string(), false));
+        assert_eq!(adder.calculate("1111", "1"), ("0000".to_string(), true));
+    }
+
+    #[test]
+    fn test_hierarchical_nets() {
+        let mut simulator = Simulator::new(RippleCarryAdder::new(4).into_circuit());
+        for (i, bits) in [(0, (Level::High, Level::High)), (1, (Level::High, Level::Low))] {
+            simulator.set_level(&format!("a{}", i), bits.0).unwrap();
+            simulator.set_level(&format!("b{}", i), bits.1).unwrap();
+        }
+        simulator.settle().unwrap();
+
+        // 01 + 11: the first stage carries into the second, whose half adders see 1 + 0 and 1 + 1
+        assert_eq!(simulator.level("ha0.carry").unwrap(), Level::High);
+        assert_eq!(simulator.level("fa1.cin").unwrap(), Level::High);
+        assert_eq!(simulator.level("fa1.ha1.sum").unwrap(), Level::High);
+        assert_eq!(simulator.level("fa1.ha2.and.out0").unwrap(), Level::High);
+        assert_eq!(simulator.level("fa1.cout").unwrap(), simulator.level("carry1").unwrap());
+        assert!(simulator.level("fa4.sum").is_err());
+    }
+
+    #[test]
+    fn test_arena_circuit() {
+        let bits = 8;
+        let mut built = RippleCarryAdder::arena_circuit(bits);
+        let mut compiled = ArenaCircuit::new(&RippleCarryAdder::new(bits).into_circuit()).unwrap();
+        assert_eq!(built.component_count(), compiled.component_count());
+        assert!(built.net("fa3.s1").is_some() && compiled.net("fa3.s1").is_some());
+        for (a, b) in [(0u32, 0u32), (255, 1), (0x5a, 0xc3), (200, 100)] {
+            for circuit in [&mut built, &mut compiled] {
+                for i in 0..bits {
+                    let level = |value: u32| if (value >> i) & 1 == 1 { Level::High } else { Level::Low };
+                    let [a_net, b_net] = ["a", "b"].map(|prefix| circuit.net(&format!("{}{}", prefix, i)).unwrap());
+                    circuit.set_level(a_net, level(a));
+                    circuit.set_level(b_net, level(b));
+                }
+                circuit.settle().unwrap();
+                let sum = circuit.outputs().iter().enumerate().filter(|&(_, &net)| circuit.level(net) == Level::High);
+                assert_eq!(sum.map(|(i, _)| 1 << i).sum::<u32>(), a + b, "{} + {}", a, b);
+            }
+        }
+    }
+
+    #[test]
+    fn test_carry_ripple_delay() {
+        let mut circuit = RippleCarryAdder::new(4).into_circuit();
+        circuit.set_delay(1);
+        let mut simulator = Simulator::new(circuit);
+        for i in 0..4 {
+            simulator.set_level(&format!("a{}", i), Level::Low).unwrap();
+            simulator.set_level(&format!("b{}", i), Level::Low).unwrap();
+        }
+        let start = simulator.settle().unwrap();
+
+        // 1111 + 0001 makes the carry ripple through every stage
+        for i in 0..4 {
+            simulator.set_level(&format!("a{}", i), Level::High).unwrap();
+        }
+        simulator.set_level("b0", Level::High).unwrap();
+        let end = simulator.settle().unwrap();
+
+        for i in 0..4 {
+            assert_eq!(simulator.level(&format!("sum{}", i)).unwrap(), Level::Low);
+        }
+        assert_eq!(simulator.level("carry3").unwrap(), Level::High);
+        // Half adder carry after one tick, then two gates per full adder
+        assert_eq!(simulator.last_change("carry3").unwrap() - start, 7);
+        assert_eq!(end - start, 7);
+    }
+}
@@ -0,0 +1,141 @@
+use crate::components::gates::Buffer;
+use crate::components::{Level, NetId};
+use crate::digital_logic::arithmetic::gate_list::{
+    name_inputs, name_outputs, read_operand, set_operand, Block,
+};
+use crate::simulation::Circuit;
+
+// Status of an addition or subtraction
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Flags {
+    // An unsigned addition did not fit into the width
+    pub carry: bool,
+    // An unsigned subtraction went below zero
+    pub borrow: bool,
+    // The result is wrong when the operands are read as two's complement numbers
+    pub overflow: bool,
+    // The most significant bit of the result, its sign in two's complement
+    pub negative: bool,
+}
+
+// Subtractor computing a - b with full subtractors, each passing a borrow to the bit above
+// like an adder passes its carry
+pub struct Subtractor {
+    input1: Vec<NetId>,
+    input2: Vec<NetId>,
+    difference: Vec<NetId>,
+    borrow: NetId,
+    overflow: NetId,
+    negative: NetId,
+    block: Block,
+}
+
+impl Subtractor {
+    pub fn new(n_bit: usize) -> Self {
+        let mut block = Block::default();
+        let mut gates = block.gates();
+        let input1 = gates.new_nets(n_bit);
+        let input2 = gates.new_nets(n_bit);
+        let difference = gates.new_nets(n_bit);
+
+        // Borrow into every bit, none into bit 0
+        let mut borrows: Vec<Option<NetId>> = vec![None];
+        for i in 0..n_bit {
+            let (a, b) = (input1[i], input2[i]);
+            let not_a = gates.not(a);
+            let borrow_out = match borrows[i] {
+                // Half subtractor: a ^ b, borrowing if a is 0 and b is 1
+                None => {
+                    gates.xor_into(a, b, difference[i]);
+                    gates.and(vec![not_a, b])
+                }
+                // Full subtractor: a ^ b ^ borrow, borrowing if b, or the borrow in, exceeds a
+                Some(borrow_in) => {
+                    let partial = gates.xor(a, b);
+                    gates.xor_into(partial, borrow_in, difference[i]);
+                    let not_partial = gates.not(partial);
+                    let from_bits = gates.and(vec![not_a, b]);
+                    let from_below = gates.and(vec![not_partial, borrow_in]);
+                    gates.or(vec![from_bits, from_below])
+                }
+            };
+            borrows.push(Some(borrow_out));
+        }
+
+        let borrow = borrows[n_bit].unwrap();
+        // Signed overflow: the borrow into the sign bit differs from the one out of it
+        let overflow = match borrows[n_bit - 1] {
+            Some(borrow_in) => gates.xor(borrow_in, borrow),
+            None => borrow,
+        };
+        let negative = gates.new_net();
+        gates.add(Buffer::new(difference[n_bit - 1], negative));
+
+        Self { input1, input2, difference, borrow, overflow, negative, block }
+    }
+
+    // Move the subtractor into a circuit with inputs a0.., b0.. and outputs diff0..,
+    // borrow, overflow and negative
+    pub fn into_circuit(self) -> Circuit {
+        let mut circuit = self.block.into_circuit("subtractor");
+        name_inputs(&mut circuit, "a", &self.input1);
+        name_inputs(&mut circuit, "b", &self.input2);
+        name_outputs(&mut circuit, "diff", &self.difference);
+        circuit.name_output("borrow", self.borrow).unwrap();
+        circuit.name_output("overflow", self.overflow).unwrap();
+        circuit.name_output("negative", self.negative).unwrap();
+        circuit
+    }
+
+    pub fn calculate(&mut self, a: &str, b: &str) -> (String, Flags) {
+        set_operand(&mut self.block.nets, &self.input1, a);
+        set_operand(&mut self.block.nets, &self.input2, b);
+        self.block.update();
+        let high = |net: &NetId| self.block.nets.level(*net) == Level::High;
+        let flags = Flags { carry: false, borrow: high(&self.borrow), overflow: high(&self.overflow), negative: high(&self.negative) };
+        (read_operand(&self.block.nets, &self.difference), flags)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_calculate() {
+        let mut subtractor = Subtractor::new(4);
+        let (difference, flags) = subtractor.calculate("0101", "0011");
+        assert_eq!(difference, "0010");
+        assert_eq!(flags, Flags::default());
+
+        // 3 - 5 borrows and is -2 in two's complement
+        let (difference, flags) = subtractor.calculate("0011", "0101");
+        assert_eq!(difference, "1110");
+        assert_eq!(flags, Flags { carry: false, borrow: true, overflow: false, negative: true });
+
+        // -8 - 1 does not fit into four bits
+        let (difference, flags) = subtractor.calculate("1000", "0001");
+        assert_eq!(difference, "0111");
+        assert!(flags.overflow && !flags.borrow);
+    }
+
+    #[test]
+    fn test_all_pairs() {
+        for bits in [1, 2, 5] {
+            let mut subtractor = Subtractor::new(bits);
+            let limit = 1i32 << bits;
+            let signed = |value: i32| if value >= limit / 2 { value - limit } else { value };
+            for a in 0..limit {
+                for b in 0..limit {
+                    let (difference, flags) = subtractor.calculate(&format!("{:b}", a), &format!("{:b}", b));
+                    let difference = i32::from_str_radix(&difference, 2).unwrap();
+                    assert_eq!(difference, (a - b).rem_euclid(limit));
+                    assert_eq!(flags.borrow, a < b);
+                    let exact = signed(a) - signed(b);
+                    assert_eq!(flags.overflow, exact != signed(difference), "{} - {}", a, b);
+                    assert_eq!(flags.negative, signed(difference) < 0);
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,141 @@
+use crate::components::NetId;
+use crate::digital_logic::arithmetic::gate_list::{
+    name_inputs, name_outputs, read_operand, set_operand, Block,
+};
+use crate::simulation::Circuit;
+
+// Multiplier sorting the partial products a[j] & b[i] into columns by weight and reducing
+// every column in parallel: each stage turns three bits of a column into a sum bit and a
+// carry into the next column with a full adder, and two bits with a half adder. After a
+// logarithmic number of stages at most two bits are left per column, which one ripple
+// carry chain adds into the product.
+pub struct WallaceMultiplier {
+    input1: Vec<NetId>,
+    input2: Vec<NetId>,
+    product: Vec<NetId>,
+    // Number of reduction stages
+    stages: usize,
+    block: Block,
+}
+
+impl WallaceMultiplier {
+    // Multiplier of an n-bit by an m-bit number, with an (n + m)-bit product
+    pub fn new(n_bit: usize, m_bit: usize) -> Self {
+        let mut block = Block::default();
+        let mut gates = block.gates();
+        let input1 = gates.new_nets(n_bit);
+        let input2 = gates.new_nets(m_bit);
+        let width = n_bit + m_bit;
+
+        let mut columns: Vec<Vec<NetId>> = vec![Vec::new(); width];
+        for (i, b) in input2.iter().enumerate() {
+            for (j, a) in input1.iter().enumerate() {
+                columns[i + j].push(gates.and(vec![*a, *b]));
+            }
+        }
+
+        let mut stages = 0;
+        while columns.iter().any(|column| column.len() > 2) {
+            // Carries only join the next column in the following stage
+            let mut reduced: Vec<Vec<NetId>> = vec![Vec::new(); width];
+            for (w, column) in columns.iter().enumerate() {
+                for bits in column.chunks(3) {
+                    let (sum, carry) = gates.add_bits(bits);
+                    reduced[w].push(sum);
+                    // The product fits into its width, so a carry out of the top column is 0
+                    if let (Some(carry), true) = (carry, w + 1 < width) {
+                        reduced[w + 1].push(carry);
+                    }
+                }
+            }
+            columns = reduced;
+            stages += 1;
+        }
+
+        let mut product = Vec::new();
+        let mut carry: Option<NetId> = None;
+        for column in columns {
+            let mut bits = column;
+            bits.extend(carry);
+            if bits.is_empty() {
+                product.push(gates.low());
+                carry = None;
+            } else {
+                let (sum, carry_out) = gates.add_bits(&bits);
+                product.push(sum);
+                carry = carry_out;
+            }
+        }
+
+        Self { input1, input2, product, stages, block }
+    }
+
+    // Move the multiplier into a circuit with inputs a0.., b0.. and outputs product0..
+    pub fn into_circuit(self) -> Circuit {
+        let mut circuit = self.block.into_circuit("wallace_multiplier");
+        name_inputs(&mut circuit, "a", &self.input1);
+        name_inputs(&mut circuit, "b", &self.input2);
+        name_outputs(&mut circuit, "product", &self.product);
+        circuit
+    }
+
+    pub fn stages(&self) -> usize {
+        self.stages
+    }
+
+    pub fn calculate(&mut self, a: &str, b: &str) -> String {
+        set_operand(&mut self.block.nets, &self.input1, a);
+        set_operand(&mut self.block.nets, &self.input2, b);
+        self.block.update();
+        read_operand(&self.block.nets, &self.product)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analysis::{check_equivalence_with, logic_depth, Method};
+    use crate::digital_logic::arithmetic::ArrayMultiplier;
+
+    #[test]
+    fn test_calculate() {
+        let mut multiplier = WallaceMultiplier::new(4, 4);
+        assert_eq!(multiplier.calculate("0011", "0101"), "00001111");
+        assert_eq!(multiplier.calculate("1111", "1111"), "11100001");
+
+        for (n, m) in [(1, 1), (1, 3), (3, 1), (4, 3), (5, 5)] {
+            let mut multiplier = WallaceMultiplier::new(n, m);
+            for a in 0..1u32 << n {
+                for b in 0..1u32 << m {
+                    let product = multiplier.calculate(&format!("{:b}", a), &format!("{:b}", b));
+                    assert_eq!(product.len(), n + m);
+                    assert_eq!(u32::from_str_radix(&product, 2).unwrap(), a * b, "{} * {}", a, b);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_stages() {
+        // Every stage reduces the tallest column to about two thirds of its height
+        for (bits, stages) in [(2, 0), (3, 1), (4, 2), (8, 4), (16, 6)] {
+            assert_eq!(WallaceMultiplier::new(bits, bits).stages(), stages, "{} bits", bits);
+        }
+    }
+
+    #[test]
+    fn test_equivalent_to_array_multiplier() {
+        for (n, m) in [(3, 5), (6, 6)] {
+            let wallace = WallaceMultiplier::new(n, m).into_circuit();
+            let array = ArrayMultiplier::new(n, m).into_circuit();
+            assert_eq!(check_equivalence_with(wallace, array, Method::Sat), Ok(None));
+        }
+    }
+
+    #[test]
+    fn test_depth() {
+        let wallace = logic_depth(&WallaceMultiplier::new(16, 16).into_circuit()).unwrap();
+        let array = logic_depth(&ArrayMultiplier::new(16, 16).into_circuit()).unwrap();
+        assert!(wallace < array, "{} against {}", wallace, array);
+    }
+}
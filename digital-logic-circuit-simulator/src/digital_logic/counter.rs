@@ -0,0 +1,266 @@
+use core::fmt;
+
+use crate::components::gates::ANDGate;
+use crate::components::sequential::{DFlipFlop, JKFlipFlop};
+use crate::components::{BaseComponent, Component, NetId, Nets};
+use crate::simulation::Circuit;
+
+// Synchronous binary counter counting up on every rising clock edge while `enable` is 1.
+// All JK flip-flops share the clock; bit i toggles when every bit below it is 1, which a
+// chain of AND gates works out before the edge.
+pub struct SynchronousCounter {
+    base: BaseComponent,
+    components: Vec<Box<dyn Component>>,
+}
+
+impl SynchronousCounter {
+    pub fn new(nets: &mut Nets, enable: NetId, clock: NetId, reset: NetId, q: Vec<NetId>) -> SynchronousCounter {
+        assert!(!q.is_empty(), "A counter needs at least one bit");
+        let mut base = BaseComponent::new("Synchronous Counter");
+        for input in [enable, clock, reset] {
+            base.add_input(input);
+        }
+        for &output in &q {
+            base.add_output(output);
+        }
+
+        // The gates come first so that updating the components in order evaluates them
+        // before any flip-flop changes
+        let mut components: Vec<Box<dyn Component>> = Vec::new();
+        let mut toggles = vec![enable];
+        for &q in &q[..q.len() - 1] {
+            let next = nets.add();
+            components.push(Box::new(ANDGate::new(*toggles.last().unwrap(), q, next)));
+            toggles.push(next);
+        }
+        for (toggle, q) in toggles.into_iter().zip(q) {
+            let q_bar = nets.add();
+            components.push(Box::new(JKFlipFlop::with_reset(toggle, toggle, clock, reset, q, q_bar)));
+        }
+
+        SynchronousCounter { base, components }
+    }
+
+    // Counter in a circuit with inputs enable, clk and reset and outputs q0..
+    pub fn circuit(n_bit: usize) -> Circuit {
+        let mut nets = Nets::new();
+        let [enable, clock, reset] = [(); 3].map(|_| nets.add());
+        let q = nets.add_many(n_bit);
+        let counter = SynchronousCounter::new(&mut nets, enable, clock, reset, q);
+        let outputs: Vec<String> = (0..n_bit).map(|i| format!("q{}", i)).collect();
+        let outputs: Vec<&str> = outputs.iter().map(String::as_str).collect();
+        Circuit::from_component("counter", nets, Box::new(counter), &["enable", "clk", "reset"], &outputs)
+    }
+}
+
+// Asynchronous (ripple) binary counter counting up on every rising clock edge. Each D
+// flip-flop toggles by storing its own inverted output, and is clocked by the inverted
+// output of the bit below, which rises when that bit falls from 1 to 0. The bits settle one
+// after another, so the count is briefly wrong while a carry ripples through.
+pub struct RippleCounter {
+    base: BaseComponent,
+    components: Vec<Box<dyn Component>>,
+}
+
+impl RippleCounter {
+    pub fn new(nets: &mut Nets, clock: NetId, reset: NetId, q: Vec<NetId>) -> RippleCounter {
+        assert!(!q.is_empty(), "A counter needs at least one bit");
+        let mut base = BaseComponent::new("Ripple Counter");
+        base.add_input(clock);
+        base.add_input(reset);
+        for &output in &q {
+            base.add_output(output);
+        }
+
+        let mut components: Vec<Box<dyn Component>> = Vec::new();
+        let mut stage_clock = clock;
+        for q in q {
+            let q_bar = nets.add();
+            components.push(Box::new(DFlipFlop::with_reset(q_bar, stage_clock, reset, q, q_bar)));
+            stage_clock = q_bar;
+        }
+
+        RippleCounter { base, components }
+    }
+
+    // Counter in a circuit with inputs clk and reset and outputs q0..
+    pub fn circuit(n_bit: usize) -> Circuit {
+        let mut nets = Nets::new();
+        let [clock, reset] = [(); 2].map(|_| nets.add());
+        let q = nets.add_many(n_bit);
+        let counter = RippleCounter::new(&mut nets, clock, reset, q);
+        let outputs: Vec<String> = (0..n_bit).map(|i| format!("q{}", i)).collect();
+        let outputs: Vec<&str> = outputs.iter().map(String::as_str).collect();
+        Circuit::from_component("ripple_counter", nets, Box::new(counter), &["clk", "reset"], &outputs)
+    }
+}
+
+impl fmt::Display for SynchronousCounter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, component) in self.components.iter().enumerate() {
+            writeln!(f, "Component {}:", i + 1)?;
+            writeln!(f, "{}", component)?;
+        }
+        Ok(())
+    }
+}
+
+impl Component for SynchronousCounter {
+    fn update(&mut self, nets: &mut Nets) {
+        for component in self.components.iter_mut() {
+            component.update(nets);
+        }
+    }
+
+    fn inputs(&self) -> &[NetId] {
+        self.base.inputs()
+    }
+
+    fn outputs(&self) -> &[NetId] {
+        self.base.outputs()
+    }
+
+    fn flatten(self: Box<Self>) -> Vec<Box<dyn Component>> {
+        self.components
+            .into_iter()
+            .flat_map(|component| component.flatten())
+            .collect()
+    }
+
+    fn set_delay(&mut self, delay: u64) {
+        for component in self.components.iter_mut() {
+            component.set_delay(delay);
+        }
+    }
+}
+
+impl fmt::Display for RippleCounter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, component) in self.components.iter().enumerate() {
+            writeln!(f, "Component {}:", i + 1)?;
+            writeln!(f, "{}", component)?;
+        }
+        Ok(())
+    }
+}
+
+impl Component for RippleCounter {
+    fn update(&mut self, nets: &mut Nets) {
+        for component in self.components.iter_mut() {
+            component.update(nets);
+        }
+    }
+
+    fn inputs(&self) -> &[NetId] {
+        self.base.inputs()
+    }
+
+    fn outputs(&self) -> &[NetId] {
+        self.base.outputs()
+    }
+
+    fn flatten(self: Box<Self>) -> Vec<Box<dyn Component>> {
+        self.components
+            .into_iter()
+            .flat_map(|component| component.flatten())
+            .collect()
+    }
+
+    fn set_delay(&mut self, delay: u64) {
+        for component in self.components.iter_mut() {
+            component.set_delay(delay);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::components::Level;
+    use crate::simulation::Simulator;
+
+    fn set(simulator: &mut Simulator, name: &str, high: bool) {
+        simulator.set_level(name, if high { Level::High } else { Level::Low }).unwrap();
+    }
+
+    fn tick(simulator: &mut Simulator) {
+        set(simulator, "clk", false);
+        simulator.settle().unwrap();
+        set(simulator, "clk", true);
+        simulator.settle().unwrap();
+    }
+
+    fn count(simulator: &Simulator, n_bit: usize) -> usize {
+        (0..n_bit).filter(|i| simulator.level(&format!("q{}", i)).unwrap() == Level::High).map(|i| 1 << i).sum()
+    }
+
+    #[test]
+    fn test_synchronous_counter() {
+        let mut simulator = Simulator::new(SynchronousCounter::circuit(3));
+        set(&mut simulator, "enable", true);
+        set(&mut simulator, "reset", true);
+        tick(&mut simulator);
+        assert_eq!(count(&simulator, 3), 0);
+
+        set(&mut simulator, "reset", false);
+        for expected in [1, 2, 3, 4, 5, 6, 7, 0, 1] {
+            tick(&mut simulator);
+            assert_eq!(count(&simulator, 3), expected);
+        }
+
+        // Disabled, the count holds
+        set(&mut simulator, "enable", false);
+        tick(&mut simulator);
+        assert_eq!(count(&simulator, 3), 1);
+    }
+
+    #[test]
+    fn test_ripple_counter() {
+        let mut simulator = Simulator::new(RippleCounter::circuit(4));
+        set(&mut simulator, "reset", true);
+        simulator.settle().unwrap();
+        set(&mut simulator, "reset", false);
+        for expected in (1..20).map(|value| value % 16) {
+            tick(&mut simulator);
+            assert_eq!(count(&simulator, 4), expected);
+        }
+    }
+
+    #[test]
+    fn test_ripple_delay() {
+        // With a delay of one tick per flip-flop, 0111 -> 1000 takes four ticks to settle
+        let mut circuit = RippleCounter::circuit(4);
+        circuit.set_delay(1);
+        let mut simulator = Simulator::new(circuit);
+        set(&mut simulator, "reset", true);
+        simulator.settle().unwrap();
+        set(&mut simulator, "reset", false);
+        for _ in 0..7 {
+            tick(&mut simulator);
+        }
+        set(&mut simulator, "clk", false);
+        let start = simulator.settle().unwrap();
+        set(&mut simulator, "clk", true);
+        assert_eq!(simulator.settle().unwrap() - start, 4);
+        assert_eq!(count(&simulator, 4), 8);
+
+        // All bits of the synchronous counter change together
+        let mut circuit = SynchronousCounter::circuit(4);
+        circuit.set_delay(1);
+        let mut simulator = Simulator::new(circuit);
+        set(&mut simulator, "enable", true);
+        set(&mut simulator, "reset", true);
+        simulator.settle().unwrap();
+        set(&mut simulator, "reset", false);
+        for _ in 0..7 {
+            tick(&mut simulator);
+        }
+        set(&mut simulator, "clk", false);
+        let start = simulator.settle().unwrap();
+        set(&mut simulator, "clk", true);
+        let end = simulator.settle().unwrap();
+        assert_eq!(count(&simulator, 4), 8);
+        assert!((0..4).all(|i| simulator.last_change(&format!("q{}", i)).unwrap() == start + 1));
+        assert!(end - start <= 2);
+    }
+}
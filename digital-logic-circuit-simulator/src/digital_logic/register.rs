@@ -0,0 +1,141 @@
+use core::fmt;
+
+use crate::circuits::Multiplexer;
+use crate::components::sequential::DFlipFlop;
+use crate::components::{BaseComponent, Component, NetId, Nets};
+use crate::simulation::Circuit;
+
+// n-bit register of D flip-flops. On a rising clock edge every bit takes its data input if
+// `load` is 1 and otherwise keeps its value, which a multiplexer in front of each flip-flop
+// feeds back. `reset` clears all bits at once.
+pub struct Register {
+    base: BaseComponent,
+    components: Vec<Box<dyn Component>>,
+}
+
+impl Register {
+    pub fn new(nets: &mut Nets, data: Vec<NetId>, load: NetId, clock: NetId, reset: NetId, q: Vec<NetId>) -> Register {
+        assert!(!data.is_empty() && data.len() == q.len(), "Data and outputs need the same width");
+        let mut base = BaseComponent::new("Register");
+        for &input in data.iter().chain([&load, &clock, &reset]) {
+            base.add_input(input);
+        }
+        for &output in &q {
+            base.add_output(output);
+        }
+
+        let mut components: Vec<Box<dyn Component>> = Vec::new();
+        for (data, q) in data.into_iter().zip(q) {
+            let next = nets.add();
+            components.push(Box::new(Multiplexer::two_to_one(nets, q, data, load, next)));
+            let q_bar = nets.add();
+            components.push(Box::new(DFlipFlop::with_reset(next, clock, reset, q, q_bar)));
+        }
+
+        Register { base, components }
+    }
+
+    // Register in a circuit with inputs d0.., load, clk and reset and outputs q0..
+    pub fn circuit(n_bit: usize) -> Circuit {
+        let mut nets = Nets::new();
+        let data = nets.add_many(n_bit);
+        let [load, clock, reset] = [(); 3].map(|_| nets.add());
+        let q = nets.add_many(n_bit);
+        let register = Register::new(&mut nets, data, load, clock, reset, q);
+        let mut inputs: Vec<String> = (0..n_bit).map(|i| format!("d{}", i)).collect();
+        inputs.extend(["load", "clk", "reset"].map(String::from));
+        let outputs: Vec<String> = (0..n_bit).map(|i| format!("q{}", i)).collect();
+        let inputs: Vec<&str> = inputs.iter().map(String::as_str).collect();
+        let outputs: Vec<&str> = outputs.iter().map(String::as_str).collect();
+        Circuit::from_component("register", nets, Box::new(register), &inputs, &outputs)
+    }
+}
+
+impl fmt::Display for Register {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, component) in self.components.iter().enumerate() {
+            writeln!(f, "Component {}:", i + 1)?;
+            writeln!(f, "{}", component)?;
+        }
+        Ok(())
+    }
+}
+
+impl Component for Register {
+    fn update(&mut self, nets: &mut Nets) {
+        for component in self.components.iter_mut() {
+            component.update(nets);
+        }
+    }
+
+    fn inputs(&self) -> &[NetId] {
+        self.base.inputs()
+    }
+
+    fn outputs(&self) -> &[NetId] {
+        self.base.outputs()
+    }
+
+    fn flatten(self: Box<Self>) -> Vec<Box<dyn Component>> {
+        self.components
+            .into_iter()
+            .flat_map(|component| component.flatten())
+            .collect()
+    }
+
+    fn set_delay(&mut self, delay: u64) {
+        for component in self.components.iter_mut() {
+            component.set_delay(delay);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::components::Level;
+    use crate::simulation::Simulator;
+
+    fn set(simulator: &mut Simulator, name: &str, high: bool) {
+        simulator.set_level(name, if high { Level::High } else { Level::Low }).unwrap();
+    }
+
+    fn tick(simulator: &mut Simulator) {
+        set(simulator, "clk", false);
+        simulator.settle().unwrap();
+        set(simulator, "clk", true);
+        simulator.settle().unwrap();
+    }
+
+    fn contents(simulator: &Simulator, n_bit: usize) -> String {
+        (0..n_bit).rev().map(|i| simulator.level(&format!("q{}", i)).unwrap().symbol()).collect()
+    }
+
+    #[test]
+    fn test_load_and_hold() {
+        let mut simulator = Simulator::new(Register::circuit(4));
+        for (i, bit) in [true, false, true, true].into_iter().enumerate() {
+            set(&mut simulator, &format!("d{}", i), bit);
+        }
+        set(&mut simulator, "load", false);
+        set(&mut simulator, "reset", false);
+        tick(&mut simulator);
+        assert_eq!(contents(&simulator, 4), "xxxx");
+
+        set(&mut simulator, "load", true);
+        simulator.settle().unwrap();
+        assert_eq!(contents(&simulator, 4), "xxxx", "loads only on a clock edge");
+        tick(&mut simulator);
+        assert_eq!(contents(&simulator, 4), "1101");
+
+        // New data is ignored while load is 0
+        set(&mut simulator, "d1", true);
+        set(&mut simulator, "load", false);
+        tick(&mut simulator);
+        assert_eq!(contents(&simulator, 4), "1101");
+
+        set(&mut simulator, "reset", true);
+        simulator.settle().unwrap();
+        assert_eq!(contents(&simulator, 4), "0000");
+    }
+}
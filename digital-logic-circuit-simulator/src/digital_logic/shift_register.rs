@@ -0,0 +1,138 @@
+use core::fmt;
+
+use crate::components::sequential::DFlipFlop;
+use crate::components::{BaseComponent, Component, NetId, Nets};
+use crate::simulation::Circuit;
+
+// Serial-in, parallel-out shift register. On every rising clock edge the serial input moves
+// into q0 and every bit moves one place up, the last one being dropped.
+pub struct ShiftRegister {
+    base: BaseComponent,
+    components: Vec<Box<dyn Component>>,
+}
+
+impl ShiftRegister {
+    pub fn new(nets: &mut Nets, serial: NetId, clock: NetId, reset: NetId, q: Vec<NetId>) -> ShiftRegister {
+        assert!(!q.is_empty(), "A shift register needs at least one bit");
+        let mut base = BaseComponent::new("Shift Register");
+        for input in [serial, clock, reset] {
+            base.add_input(input);
+        }
+        for &output in &q {
+            base.add_output(output);
+        }
+
+        let mut components: Vec<Box<dyn Component>> = Vec::new();
+        let mut data = serial;
+        for q in q {
+            let q_bar = nets.add();
+            components.push(Box::new(DFlipFlop::with_reset(data, clock, reset, q, q_bar)));
+            data = q;
+        }
+
+        ShiftRegister { base, components }
+    }
+
+    // Shift register in a circuit with inputs serial, clk and reset and outputs q0..
+    pub fn circuit(n_bit: usize) -> Circuit {
+        let mut nets = Nets::new();
+        let [serial, clock, reset] = [(); 3].map(|_| nets.add());
+        let q = nets.add_many(n_bit);
+        let register = ShiftRegister::new(&mut nets, serial, clock, reset, q);
+        let outputs: Vec<String> = (0..n_bit).map(|i| format!("q{}", i)).collect();
+        let outputs: Vec<&str> = outputs.iter().map(String::as_str).collect();
+        Circuit::from_component("shift_register", nets, Box::new(register), &["serial", "clk", "reset"], &outputs)
+    }
+}
+
+impl fmt::Display for ShiftRegister {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, component) in self.components.iter().enumerate() {
+            writeln!(f, "Component {}:", i + 1)?;
+            writeln!(f, "{}", component)?;
+        }
+        Ok(())
+    }
+}
+
+impl Component for ShiftRegister {
+    // Flip-flops are updated from the last to the first so each one still reads the old
+    // value of the one below it
+    fn update(&mut self, nets: &mut Nets) {
+        for component in self.components.iter_mut().rev() {
+            component.update(nets);
+        }
+    }
+
+    fn inputs(&self) -> &[NetId] {
+        self.base.inputs()
+    }
+
+    fn outputs(&self) -> &[NetId] {
+        self.base.outputs()
+    }
+
+    fn flatten(self: Box<Self>) -> Vec<Box<dyn Component>> {
+        self.components
+            .into_iter()
+            .flat_map(|component| component.flatten())
+            .collect()
+    }
+
+    fn set_delay(&mut self, delay: u64) {
+        for component in self.components.iter_mut() {
+            component.set_delay(delay);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::components::Level;
+    use crate::simulation::Simulator;
+
+    fn set(simulator: &mut Simulator, name: &str, high: bool) {
+        simulator.set_level(name, if high { Level::High } else { Level::Low }).unwrap();
+    }
+
+    fn contents(simulator: &Simulator, n_bit: usize) -> String {
+        (0..n_bit).rev().map(|i| simulator.level(&format!("q{}", i)).unwrap().symbol()).collect()
+    }
+
+    #[test]
+    fn test_shift_in() {
+        let mut simulator = Simulator::new(ShiftRegister::circuit(4));
+        set(&mut simulator, "reset", true);
+        simulator.settle().unwrap();
+        set(&mut simulator, "reset", false);
+        for (bit, expected) in [(true, "0001"), (false, "0010"), (true, "0101"), (true, "1011"), (false, "0110")] {
+            set(&mut simulator, "serial", bit);
+            set(&mut simulator, "clk", false);
+            simulator.settle().unwrap();
+            set(&mut simulator, "clk", true);
+            simulator.settle().unwrap();
+            assert_eq!(contents(&simulator, 4), expected);
+        }
+    }
+
+    #[test]
+    fn test_update_without_simulator() {
+        let mut nets = Nets::new();
+        let [serial, clock, reset] = [(); 3].map(|_| nets.add());
+        let q = nets.add_many(3);
+        let mut register = ShiftRegister::new(&mut nets, serial, clock, reset, q.clone());
+        nets.set_level(reset, Level::High);
+        register.update(&mut nets);
+        nets.set_level(reset, Level::Low);
+        nets.set_level(serial, Level::High);
+        for _ in 0..2 {
+            nets.set_level(clock, Level::Low);
+            register.update(&mut nets);
+            nets.set_level(clock, Level::High);
+            register.update(&mut nets);
+        }
+        let levels: Vec<Level> = q.iter().map(|&net| nets.level(net)).collect();
+        assert_eq!(levels, [Level::High, Level::High, Level::Low]);
+    }
+}
@@ -0,0 +1,32 @@
+// Boolean expressions such as `(a & b) | !c`, compiled into gate circuits.
+//
+// Operators by increasing precedence are `|` (or), `^` (xor), `&` (and) and `!` or `~` (not).
+// Variables are identifiers and `0` and `1` are constants.
+
+pub mod ast;
+pub mod parser;
+pub mod lowering;
+
+use core::fmt;
+
+pub use ast::Expr;
+pub use lowering::{compile, compile_outputs};
+pub use parser::parse;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExpressionError {
+    pub column: usize,
+    pub message: String,
+}
+
+impl ExpressionError {
+    pub fn new(column: usize, message: impl Into<String>) -> ExpressionError {
+        ExpressionError { column, message: message.into() }
+    }
+}
+
+impl fmt::Display for ExpressionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "column {}: {}", self.column, self.message)
+    }
+}
@@ -0,0 +1,135 @@
+use core::fmt;
+use std::ops::Not;
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Expr {
+    Const(bool),
+    Var(String),
+    Not(Box<Expr>),
+    And(Box<Expr>, Box<Expr>),
+    Or(Box<Expr>, Box<Expr>),
+    Xor(Box<Expr>, Box<Expr>),
+}
+
+impl Expr {
+    pub fn var(name: &str) -> Expr {
+        Expr::Var(name.to_string())
+    }
+
+    pub fn and(left: Expr, right: Expr) -> Expr {
+        Expr::And(Box::new(left), Box::new(right))
+    }
+
+    pub fn or(left: Expr, right: Expr) -> Expr {
+        Expr::Or(Box::new(left), Box::new(right))
+    }
+
+    pub fn xor(left: Expr, right: Expr) -> Expr {
+        Expr::Xor(Box::new(left), Box::new(right))
+    }
+
+    // Variables in order of their first appearance
+    pub fn variables(&self) -> Vec<String> {
+        let mut variables = Vec::new();
+        self.collect_variables(&mut variables);
+        variables
+    }
+
+    fn collect_variables(&self, variables: &mut Vec<String>) {
+        match self {
+            Expr::Const(_) => {}
+            Expr::Var(name) => {
+                if !variables.contains(name) {
+                    variables.push(name.clone());
+                }
+            }
+            Expr::Not(expr) => expr.collect_variables(variables),
+            Expr::And(left, right) | Expr::Or(left, right) | Expr::Xor(left, right) => {
+                left.collect_variables(variables);
+                right.collect_variables(variables);
+            }
+        }
+    }
+
+    // Value of the expression with the given values of its variables
+    pub fn evaluate(&self, value: &dyn Fn(&str) -> bool) -> bool {
+        match self {
+            Expr::Const(constant) => *constant,
+            Expr::Var(name) => value(name),
+            Expr::Not(expr) => !expr.evaluate(value),
+            Expr::And(left, right) => left.evaluate(value) && right.evaluate(value),
+            Expr::Or(left, right) => left.evaluate(value) || right.evaluate(value),
+            Expr::Xor(left, right) => left.evaluate(value) != right.evaluate(value),
+        }
+    }
+
+    // Binding strength, used to only print the parentheses that are needed
+    fn precedence(&self) -> u8 {
+        match self {
+            Expr::Or(_, _) => 1,
+            Expr::Xor(_, _) => 2,
+            Expr::And(_, _) => 3,
+            Expr::Not(_) => 4,
+            Expr::Const(_) | Expr::Var(_) => 5,
+        }
+    }
+}
+
+impl Not for Expr {
+    type Output = Expr;
+
+    fn not(self) -> Expr {
+        Expr::Not(Box::new(self))
+    }
+}
+
+impl fmt::Display for Expr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let operand = |f: &mut fmt::Formatter<'_>, expr: &Expr| {
+            if expr.precedence() < self.precedence() {
+                write!(f, "({})", expr)
+            } else {
+                write!(f, "{}", expr)
+            }
+        };
+        match self {
+            Expr::Const(constant) => write!(f, "{}", if *constant { 1 } else { 0 }),
+            Expr::Var(name) => write!(f, "{}", name),
+            Expr::Not(expr) => {
+                write!(f, "!")?;
+                operand(f, expr)
+            }
+            Expr::And(left, right) | Expr::Or(left, right) | Expr::Xor(left, right) => {
+                let operator = match self {
+                    Expr::And(_, _) => "&",
+                    Expr::Or(_, _) => "|",
+                    _ => "^",
+                };
+                operand(f, left)?;
+                write!(f, " {} ", operator)?;
+                operand(f, right)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_display() {
+        let expr = Expr::or(Expr::and(Expr::var("a"), Expr::var("b")), !Expr::var("c"));
+        assert_eq!(expr.to_string(), "a & b | !c");
+        let expr = Expr::and(Expr::or(Expr::var("a"), Expr::Const(true)), !Expr::xor(Expr::var("a"), Expr::var("b")));
+        assert_eq!(expr.to_string(), "(a | 1) & !(a ^ b)");
+    }
+
+    #[test]
+    fn test_variables_and_evaluate() {
+        let expr = Expr::xor(Expr::var("b"), Expr::and(Expr::var("a"), Expr::var("b")));
+        assert_eq!(expr.variables(), vec!["b".to_string(), "a".to_string()]);
+        assert!(expr.evaluate(&|name| name == "b"));
+        assert!(!expr.evaluate(&|_| true));
+    }
+}
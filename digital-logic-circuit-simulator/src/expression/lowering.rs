@@ -0,0 +1,193 @@
+use std::collections::HashMap;
+
+use crate::components::gates::{
+    ANDGate, Buffer, NANDGate, NORGate, NOTGate, ORGate, XNORGate, XORGate,
+};
+use crate::components::{Component, Level, NetId};
+use crate::expression::Expr;
+use crate::simulation::Circuit;
+
+type Net = NetId;
+
+// Build a gate circuit computing the expression. Every variable becomes an input port and the
+// result drives the output port `y` (`y1`, `y2`.. if a variable is already called `y`).
+pub fn compile(expr: &Expr) -> Circuit {
+    let variables = expr.variables();
+    let output = (0..)
+        .map(|i| if i == 0 { String::from("y") } else { format!("y{}", i) })
+        .find(|name| !variables.contains(name))
+        .unwrap();
+    // The output name is not one of the variables, so the names are distinct
+    compile_outputs("expr", &variables, &[(output, expr.clone())]).unwrap()
+}
+
+// Build one circuit computing several named expressions over the given input ports.
+// Chains of the same operator become one gate with several inputs, a negated AND/OR/XOR becomes
+// a NAND/NOR/XNOR and identical subexpressions share their gates, also between outputs.
+// Constants are folded away; an output that is constant as a whole is forced to that level.
+// Fails if two ports share a name.
+pub fn compile_outputs(name: &str, inputs: &[String], outputs: &[(String, Expr)]) -> Result<Circuit, String> {
+    let mut circuit = Circuit::new().with_name(name);
+    let mut nets = HashMap::new();
+    for input in inputs {
+        nets.insert(Expr::Var(input.clone()), circuit.add_input(input)?);
+    }
+    let output_nets = outputs.iter().map(|(output, _)| circuit.add_output(output)).collect::<Result<Vec<Net>, String>>()?;
+
+    let mut lowering = Lowering { circuit, nets };
+    for ((_, expr), net) in outputs.iter().zip(output_nets) {
+        lowering.lower(&fold(expr), Some(net));
+    }
+    Ok(lowering.circuit)
+}
+
+struct Lowering {
+    circuit: Circuit,
+    // Net carrying the value of every expression built so far
+    nets: HashMap<Expr, Net>,
+}
+
+impl Lowering {
+    // Net with the value of the expression, driving `output` if one is given
+    fn lower(&mut self, expr: &Expr, output: Option<Net>) -> Net {
+        if output.is_none() {
+            if let Some(net) = self.nets.get(expr) {
+                return *net;
+            }
+        }
+        let net = output.unwrap_or_else(|| self.circuit.new_net());
+
+        let component: Option<Box<dyn Component>> = match expr {
+            Expr::Const(constant) => {
+                self.circuit.nets_mut().set_level(net, if *constant { Level::High } else { Level::Low });
+                None
+            }
+            Expr::Var(_) => Some(Box::new(Buffer::new(self.nets[expr], net))),
+            Expr::Not(inner) => match inner.as_ref() {
+                Expr::And(_, _) => Some(Box::new(NANDGate::with_inputs(self.operands(inner), net))),
+                Expr::Or(_, _) => Some(Box::new(NORGate::with_inputs(self.operands(inner), net))),
+                Expr::Xor(_, _) => Some(Box::new(XNORGate::with_inputs(self.operands(inner), net))),
+                _ => Some(Box::new(NOTGate::new(self.lower(inner, None), net))),
+            },
+            Expr::And(_, _) => Some(Box::new(ANDGate::with_inputs(self.operands(expr), net))),
+            Expr::Or(_, _) => Some(Box::new(ORGate::with_inputs(self.operands(expr), net))),
+            Expr::Xor(_, _) => Some(Box::new(XORGate::with_inputs(self.operands(expr), net))),
+        };
+        if let Some(component) = component {
+            self.circuit.add_component(component);
+        }
+        self.nets.entry(expr.clone()).or_insert_with(|| net);
+        net
+    }
+
+    // Nets of the operands of a chain of the same operator, e.g. a, b and c for `a & b & c`
+    fn operands(&mut self, expr: &Expr) -> Vec<Net> {
+        let mut operands = Vec::new();
+        collect_operands(expr, expr, &mut operands);
+        operands.into_iter().map(|operand| self.lower(operand, None)).collect()
+    }
+}
+
+fn collect_operands<'a>(chain: &Expr, expr: &'a Expr, operands: &mut Vec<&'a Expr>) {
+    match (chain, expr) {
+        (Expr::And(_, _), Expr::And(left, right))
+        | (Expr::Or(_, _), Expr::Or(left, right))
+        | (Expr::Xor(_, _), Expr::Xor(left, right)) => {
+            collect_operands(chain, left, operands);
+            collect_operands(chain, right, operands);
+        }
+        _ => operands.push(expr),
+    }
+}
+
+// Remove constants and double negations
+fn fold(expr: &Expr) -> Expr {
+    match expr {
+        Expr::Const(_) | Expr::Var(_) => expr.clone(),
+        Expr::Not(inner) => match fold(inner) {
+            Expr::Const(constant) => Expr::Const(!constant),
+            Expr::Not(inner) => *inner,
+            inner => !inner,
+        },
+        Expr::And(left, right) => match (fold(left), fold(right)) {
+            (Expr::Const(false), _) | (_, Expr::Const(false)) => Expr::Const(false),
+            (Expr::Const(true), other) | (other, Expr::Const(true)) => other,
+            (left, right) => Expr::and(left, right),
+        },
+        Expr::Or(left, right) => match (fold(left), fold(right)) {
+            (Expr::Const(true), _) | (_, Expr::Const(true)) => Expr::Const(true),
+            (Expr::Const(false), other) | (other, Expr::Const(false)) => other,
+            (left, right) => Expr::or(left, right),
+        },
+        Expr::Xor(left, right) => match (fold(left), fold(right)) {
+            (Expr::Const(false), other) | (other, Expr::Const(false)) => other,
+            (Expr::Const(true), other) | (other, Expr::Const(true)) => fold(&!other),
+            (left, right) => Expr::xor(left, right),
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analysis::TruthTable;
+    use crate::expression::parse;
+
+    // Compare the simulated circuit with the expression for every input combination
+    fn assert_computes(source: &str) {
+        let expr = parse(source).unwrap();
+        let variables = expr.variables();
+        let table = TruthTable::from_circuit(compile(&expr), false).unwrap();
+        assert_eq!(table.rows().len(), 1 << variables.len());
+        for (inputs, outputs) in table.rows() {
+            let value = |name: &str| inputs[variables.iter().position(|v| v == name).unwrap()] == Level::High;
+            let expected = if expr.evaluate(&value) { Level::High } else { Level::Low };
+            assert_eq!(outputs, &vec![expected], "{} for {:?}", source, inputs);
+        }
+    }
+
+    #[test]
+    fn test_compile_matches_evaluation() {
+        for source in ["(a & b) | !c", "a ^ b ^ c", "!(a | b) & (a ^ c)", "a", "!a", "a & 1 | b & 0", "a ^ 1"] {
+            assert_computes(source);
+        }
+    }
+
+    #[test]
+    fn test_gate_structure() {
+        let circuit = compile(&parse("(a & b) | !c").unwrap());
+        assert_eq!(circuit.inputs(), &["a", "b", "c"]);
+        assert_eq!(circuit.outputs(), &["y"]);
+        assert_eq!(circuit.component_count(), 3);
+
+        // One three-input AND, one NAND and shared subexpressions
+        assert_eq!(compile(&parse("a & b & c").unwrap()).component_count(), 1);
+        assert_eq!(compile(&parse("!(a & b)").unwrap()).component_count(), 1);
+        assert_eq!(compile(&parse("(a ^ b) & (a ^ b | c)").unwrap()).component_count(), 3);
+    }
+
+    #[test]
+    fn test_compile_outputs_shares_gates() {
+        let inputs = vec!["a".to_string(), "b".to_string(), "c".to_string()];
+        let outputs = vec![
+            ("sum".to_string(), parse("a ^ b ^ c").unwrap()),
+            ("carry".to_string(), parse("a & b | (a ^ b) & c").unwrap()),
+        ];
+        let circuit = compile_outputs("adder", &inputs, &outputs).unwrap();
+        assert_eq!(circuit.outputs(), &["sum", "carry"]);
+        // The three-input XOR for the sum, a ^ b, two ANDs and the OR
+        assert_eq!(circuit.component_count(), 5);
+    }
+
+    #[test]
+    fn test_output_name_and_constants() {
+        let circuit = compile(&parse("y & y1").unwrap());
+        assert_eq!(circuit.outputs(), &["y2"]);
+
+        let circuit = compile(&parse("a & 0").unwrap());
+        assert_eq!(circuit.inputs(), &["a"]);
+        assert_eq!(circuit.component_count(), 0);
+        let table = TruthTable::from_circuit(circuit, false).unwrap();
+        assert!(table.rows().iter().all(|(_, outputs)| outputs == &vec![Level::Low]));
+    }
+}
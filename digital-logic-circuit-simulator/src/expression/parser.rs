@@ -0,0 +1,144 @@
+use crate::expression::{Expr, ExpressionError};
+
+pub fn parse(source: &str) -> Result<Expr, ExpressionError> {
+    let mut parser = Parser { chars: source.chars().collect(), position: 0 };
+    let expr = parser.or()?;
+    parser.skip_whitespace();
+    match parser.peek() {
+        None => Ok(expr),
+        Some(c) => Err(parser.error(format!("unexpected '{}'", c))),
+    }
+}
+
+// Recursive descent, one method per precedence level
+struct Parser {
+    chars: Vec<char>,
+    position: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.position).copied()
+    }
+
+    fn skip_whitespace(&mut self) {
+        while self.peek().is_some_and(|c| c.is_whitespace()) {
+            self.position += 1;
+        }
+    }
+
+    // Consume the operator if it is next
+    fn accept(&mut self, operators: &[char]) -> bool {
+        self.skip_whitespace();
+        if self.peek().is_some_and(|c| operators.contains(&c)) {
+            self.position += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn error(&self, message: impl Into<String>) -> ExpressionError {
+        ExpressionError::new(self.position + 1, message)
+    }
+
+    fn or(&mut self) -> Result<Expr, ExpressionError> {
+        let mut expr = self.xor()?;
+        while self.accept(&['|']) {
+            expr = Expr::or(expr, self.xor()?);
+        }
+        Ok(expr)
+    }
+
+    fn xor(&mut self) -> Result<Expr, ExpressionError> {
+        let mut expr = self.and()?;
+        while self.accept(&['^']) {
+            expr = Expr::xor(expr, self.and()?);
+        }
+        Ok(expr)
+    }
+
+    fn and(&mut self) -> Result<Expr, ExpressionError> {
+        let mut expr = self.not()?;
+        while self.accept(&['&']) {
+            expr = Expr::and(expr, self.not()?);
+        }
+        Ok(expr)
+    }
+
+    fn not(&mut self) -> Result<Expr, ExpressionError> {
+        if self.accept(&['!', '~']) {
+            return Ok(!self.not()?);
+        }
+        self.atom()
+    }
+
+    fn atom(&mut self) -> Result<Expr, ExpressionError> {
+        self.skip_whitespace();
+        match self.peek() {
+            Some('(') => {
+                self.position += 1;
+                let expr = self.or()?;
+                if !self.accept(&[')']) {
+                    return Err(self.error(self.found("')'")));
+                }
+                Ok(expr)
+            }
+            Some('0') => {
+                self.position += 1;
+                Ok(Expr::Const(false))
+            }
+            Some('1') => {
+                self.position += 1;
+                Ok(Expr::Const(true))
+            }
+            Some(c) if c.is_ascii_alphabetic() || c == '_' => {
+                let start = self.position;
+                while self.peek().is_some_and(|c| c.is_ascii_alphanumeric() || c == '_') {
+                    self.position += 1;
+                }
+                Ok(Expr::Var(self.chars[start..self.position].iter().collect()))
+            }
+            _ => Err(self.error(self.found("a variable, a constant or '('"))),
+        }
+    }
+
+    fn found(&self, expected: &str) -> String {
+        match self.peek() {
+            Some(c) => format!("expected {}, found '{}'", expected, c),
+            None => format!("expected {}, found end of input", expected),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_precedence() {
+        assert_eq!(
+            parse("a | b ^ c & !d").unwrap(),
+            Expr::or(Expr::var("a"), Expr::xor(Expr::var("b"), Expr::and(Expr::var("c"), !Expr::var("d"))))
+        );
+        assert_eq!(
+            parse("(a | b) & ~c").unwrap(),
+            Expr::and(Expr::or(Expr::var("a"), Expr::var("b")), !Expr::var("c"))
+        );
+        assert_eq!(parse("a & 1").unwrap(), Expr::and(Expr::var("a"), Expr::Const(true)));
+    }
+
+    #[test]
+    fn test_display_round_trip() {
+        for source in ["a & b | !c", "(a | b) & !(c ^ d)", "!!x_1 ^ 0"] {
+            assert_eq!(parse(source).unwrap().to_string(), source);
+        }
+    }
+
+    #[test]
+    fn test_errors() {
+        assert_eq!(parse("(a & b"), Err(ExpressionError::new(7, "expected ')', found end of input")));
+        assert_eq!(parse("a & | b"), Err(ExpressionError::new(5, "expected a variable, a constant or '(', found '|'")));
+        assert_eq!(parse("a b"), Err(ExpressionError::new(3, "unexpected 'b'")));
+    }
+}
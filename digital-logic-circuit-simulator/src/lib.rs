@@ -0,0 +1,8 @@
+pub mod components;
+pub mod circuits;
+pub mod digital_logic;
+pub mod simulation;
+pub mod netlist;
+pub mod verilog;
+pub mod analysis;
+pub mod expression;
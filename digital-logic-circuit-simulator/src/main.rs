@@ -1,6 +1,3 @@
-mod components;
-mod circuits;
-mod digital_logic;
 mod cli;
 
 use cli::{
@@ -12,4 +9,3 @@ fn main() {
     let mut cli = Cli::new(commands);
     cli.run();
 }
-
@@ -0,0 +1,51 @@
+// Textual netlist format.
+//
+//     # Comments run to the end of the line
+//     circuit half(a, b) -> (sum, carry) {
+//         xor x1(a, b) -> (sum);
+//         and a1(a, b) -> (carry);
+//     }
+//
+//     circuit adder(a, b, cin) -> (sum, cout) {
+//         wire s1, c1, c2;
+//         half ha1(a, b) -> (s1, c1);
+//         half ha2(s1, cin) -> (sum, c2);
+//         or #2 o1(c1, c2) -> (cout);
+//     }
+//
+// Every instance names its type, an instance name, the nets it reads and the nets it drives.
+// `#n` sets the propagation delay (the half period for clocks) and `_` leaves an output
+// unconnected. `tie0 t() -> (n);` and `tie1` hold a net at 0 or 1 without adding a component.
+// Nets are the ports of the enclosing circuit and its declared wires.
+// Wires inside a subcircuit are named by their instance path, e.g. `ha1.s1`. The built-in types
+// are listed in `BUILTINS`. The last circuit in a file is the top level unless another one is requested.
+
+pub mod lexer;
+pub mod parser;
+pub mod loader;
+pub mod writer;
+
+use core::fmt;
+
+pub use loader::{load, load_builtin, BUILTINS};
+pub use parser::{parse, Definition, Instance, NetRef};
+pub use writer::write;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct NetlistError {
+    pub line: usize,
+    pub column: usize,
+    pub message: String,
+}
+
+impl NetlistError {
+    pub fn new(line: usize, column: usize, message: impl Into<String>) -> NetlistError {
+        NetlistError { line, column, message: message.into() }
+    }
+}
+
+impl fmt::Display for NetlistError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}, column {}: {}", self.line, self.column, self.message)
+    }
+}
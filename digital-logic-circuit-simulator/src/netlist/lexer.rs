@@ -0,0 +1,173 @@
+use crate::netlist::NetlistError;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum TokenKind {
+    Ident(String),
+    Number(u64),
+    LParen,
+    RParen,
+    LBrace,
+    RBrace,
+    Comma,
+    Semicolon,
+    Arrow,
+    Hash,
+    Dot,
+    Eof,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Token {
+    pub kind: TokenKind,
+    pub line: usize,
+    pub column: usize,
+}
+
+impl TokenKind {
+    pub fn describe(&self) -> String {
+        match self {
+            TokenKind::Ident(name) => format!("'{}'", name),
+            TokenKind::Number(value) => format!("'{}'", value),
+            TokenKind::LParen => "'('".to_string(),
+            TokenKind::RParen => "')'".to_string(),
+            TokenKind::LBrace => "'{'".to_string(),
+            TokenKind::RBrace => "'}'".to_string(),
+            TokenKind::Comma => "','".to_string(),
+            TokenKind::Semicolon => "';'".to_string(),
+            TokenKind::Arrow => "'->'".to_string(),
+            TokenKind::Hash => "'#'".to_string(),
+            TokenKind::Dot => "'.'".to_string(),
+            TokenKind::Eof => "end of file".to_string(),
+        }
+    }
+}
+
+pub fn tokenize(source: &str) -> Result<Vec<Token>, NetlistError> {
+    let mut tokens = Vec::new();
+    let mut chars = source.chars().peekable();
+    let mut line = 1;
+    let mut column = 1;
+
+    while let Some(&c) = chars.peek() {
+        let (start_line, start_column) = (line, column);
+        let mut push = |kind| tokens.push(Token { kind, line: start_line, column: start_column });
+
+        if c == '\n' {
+            chars.next();
+            line += 1;
+            column = 1;
+            continue;
+        }
+        if c.is_whitespace() {
+            chars.next();
+            column += 1;
+            continue;
+        }
+        if c == '#' {
+            chars.next();
+            column += 1;
+            // A hash followed by a digit is a parameter, anything else starts a comment
+            if chars.peek().is_some_and(|c| c.is_ascii_digit()) {
+                push(TokenKind::Hash);
+            } else {
+                while chars.peek().is_some_and(|&c| c != '\n') {
+                    chars.next();
+                }
+            }
+            continue;
+        }
+        if c.is_ascii_alphabetic() || c == '_' {
+            let mut name = String::new();
+            // Dots appear in the hierarchical names of flattened subcircuits, e.g. `ha1.partial`
+            while let Some(&c) = chars.peek().filter(|c| c.is_ascii_alphanumeric() || **c == '_' || **c == '.') {
+                name.push(c);
+                chars.next();
+                column += 1;
+            }
+            push(TokenKind::Ident(name));
+            continue;
+        }
+        if c.is_ascii_digit() {
+            let mut digits = String::new();
+            while let Some(&c) = chars.peek().filter(|c| c.is_ascii_digit()) {
+                digits.push(c);
+                chars.next();
+                column += 1;
+            }
+            let value = digits
+                .parse()
+                .map_err(|_| NetlistError::new(start_line, start_column, format!("number {} is too large", digits)))?;
+            push(TokenKind::Number(value));
+            continue;
+        }
+
+        chars.next();
+        column += 1;
+        let kind = match c {
+            '(' => TokenKind::LParen,
+            ')' => TokenKind::RParen,
+            '{' => TokenKind::LBrace,
+            '}' => TokenKind::RBrace,
+            ',' => TokenKind::Comma,
+            ';' => TokenKind::Semicolon,
+            '-' if chars.peek() == Some(&'>') => {
+                chars.next();
+                column += 1;
+                TokenKind::Arrow
+            }
+            _ => return Err(NetlistError::new(start_line, start_column, format!("unexpected character '{}'", c))),
+        };
+        push(kind);
+    }
+
+    tokens.push(Token { kind: TokenKind::Eof, line, column });
+    Ok(tokens)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn kinds(source: &str) -> Vec<TokenKind> {
+        tokenize(source).unwrap().into_iter().map(|token| token.kind).collect()
+    }
+
+    #[test]
+    fn test_tokenize() {
+        assert_eq!(
+            kinds("and #2 a1(x, y) -> (z); # comment"),
+            vec![
+                TokenKind::Ident("and".to_string()),
+                TokenKind::Hash,
+                TokenKind::Number(2),
+                TokenKind::Ident("a1".to_string()),
+                TokenKind::LParen,
+                TokenKind::Ident("x".to_string()),
+                TokenKind::Comma,
+                TokenKind::Ident("y".to_string()),
+                TokenKind::RParen,
+                TokenKind::Arrow,
+                TokenKind::LParen,
+                TokenKind::Ident("z".to_string()),
+                TokenKind::RParen,
+                TokenKind::Semicolon,
+                TokenKind::Eof,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_positions() {
+        let tokens = tokenize("circuit\n  top {").unwrap();
+        assert_eq!((tokens[1].line, tokens[1].column), (2, 3));
+        assert_eq!((tokens[2].line, tokens[2].column), (2, 7));
+    }
+
+    #[test]
+    fn test_unexpected_character() {
+        assert_eq!(
+            tokenize("circuit\n top $"),
+            Err(NetlistError::new(2, 6, "unexpected character '$'"))
+        );
+    }
+}
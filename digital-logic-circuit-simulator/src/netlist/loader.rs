@@ -0,0 +1,437 @@
+use std::collections::HashMap;
+
+use crate::circuits::{FullAdder, HalfAdder};
+use crate::components::gates::{
+    ANDGate, Buffer, NANDGate, NORGate, NOTGate, ORGate, TriStateBuffer, XNORGate, XORGate,
+};
+use crate::components::sequential::{Clock, DFlipFlop, DLatch, JKFlipFlop, SRLatch};
+use crate::components::{Component, Level, NetId, Nets};
+use crate::netlist::parser::{parse, Definition, Instance, NetRef};
+use crate::netlist::NetlistError;
+use crate::simulation::Circuit;
+
+// Instance types that are always available, with their inputs and outputs.
+// A `*` input list takes one or more inputs.
+pub const BUILTINS: &[(&str, &str, &str)] = &[
+    ("and", "*", "y"),
+    ("or", "*", "y"),
+    ("xor", "*", "y"),
+    ("nand", "*", "y"),
+    ("nor", "*", "y"),
+    ("xnor", "*", "y"),
+    ("not", "a", "y"),
+    ("buf", "a", "y"),
+    ("tri", "a, enable", "y"),
+    ("half_adder", "a, b", "sum, carry"),
+    ("full_adder", "a, b, cin", "sum, cout"),
+    ("srlatch", "s, r", "q, q_bar"),
+    ("dlatch", "d, enable", "q, q_bar"),
+    ("dff", "d, clk", "q, q_bar"),
+    ("dffr", "d, clk, reset", "q, q_bar"),
+    ("jkff", "j, k, clk", "q, q_bar"),
+    ("clock", "", "clk"),
+    ("tie0", "", "y"),
+    ("tie1", "", "y"),
+];
+
+type Net = NetId;
+
+// Build a circuit from netlist source. Without a name the last circuit in the source is the top level.
+pub fn load(source: &str, top: Option<&str>) -> Result<Circuit, NetlistError> {
+    let builtins: Vec<&str> = BUILTINS.iter().map(|(name, _, _)| *name).collect();
+    build(&parse(source)?, top, &builtins)
+}
+
+// A circuit made of a single built-in instance, with the built-in's port names.
+// Gates that take any number of inputs get the two inputs `a` and `b`.
+pub fn load_builtin(kind: &str) -> Result<Circuit, NetlistError> {
+    let (_, inputs, outputs) = BUILTINS
+        .iter()
+        .find(|(builtin, _, _)| *builtin == kind)
+        .ok_or_else(|| NetlistError::new(1, 1, format!("unknown circuit '{}'", kind)))?;
+    let inputs = if *inputs == "*" { "a, b" } else { inputs };
+    let source = format!("circuit top({}) -> ({}) {{ {} u({}) -> ({}); }}", inputs, outputs, kind, inputs, outputs);
+    Ok(load(&source, None)?.with_name(kind))
+}
+
+// Build a circuit from parsed definitions, allowing only the given built-in types
+pub(crate) fn build(definitions: &[Definition], top: Option<&str>, builtins: &[&str]) -> Result<Circuit, NetlistError> {
+    let mut lookup: HashMap<&str, &Definition> = HashMap::new();
+    for definition in definitions {
+        let name = &definition.name;
+        if builtins.contains(&name.name.as_str()) {
+            return Err(error(name, format!("circuit '{}' has the name of a built-in", name.name)));
+        }
+        if lookup.insert(&name.name, definition).is_some() {
+            return Err(error(name, format!("circuit '{}' is defined twice", name.name)));
+        }
+    }
+
+    let definition = match top {
+        Some(top) => *lookup
+            .get(top)
+            .ok_or_else(|| NetlistError::new(1, 1, format!("no circuit named '{}'", top)))?,
+        None => definitions
+            .last()
+            .ok_or_else(|| NetlistError::new(1, 1, "the netlist does not define a circuit"))?,
+    };
+
+    let mut circuit = Circuit::new().with_name(&definition.name.name);
+    let mut scope = HashMap::new();
+    for port in &definition.inputs {
+        declare(&mut scope, port, || circuit.add_input(&port.name))?;
+    }
+    for port in &definition.outputs {
+        declare(&mut scope, port, || circuit.add_output(&port.name))?;
+    }
+
+    let mut loader = Loader { definitions: lookup, builtins, stack: vec![definition.name.name.clone()] };
+    loader.instantiate(definition, "", scope, &mut circuit)?;
+    Ok(circuit)
+}
+
+struct Loader<'a> {
+    definitions: HashMap<&'a str, &'a Definition>,
+    builtins: &'a [&'a str],
+    // Circuits currently being instantiated, to catch recursion
+    stack: Vec<String>,
+}
+
+impl Loader<'_> {
+    // Add the contents of a definition whose ports are already bound to nets.
+    // Wires get the instance path as prefix, e.g. `fa1.ha2.carry`.
+    fn instantiate(
+        &mut self,
+        definition: &Definition,
+        prefix: &str,
+        mut scope: HashMap<String, Net>,
+        circuit: &mut Circuit,
+    ) -> Result<(), NetlistError> {
+        for wire in &definition.wires {
+            declare(&mut scope, wire, || circuit.add_net(&format!("{}{}", prefix, wire.name)))?;
+        }
+
+        let mut instance_names: Vec<&str> = Vec::new();
+        for instance in &definition.instances {
+            if instance_names.contains(&instance.name.name.as_str()) {
+                return Err(error(&instance.name, format!("instance '{}' is declared twice", instance.name.name)));
+            }
+            instance_names.push(&instance.name.name);
+
+            let inputs = instance
+                .inputs
+                .iter()
+                .map(|net| match scope.get(&net.name) {
+                    Some(&connection) => Ok(connection),
+                    None if net.name == "_" => Err(error(net, "inputs have to be connected")),
+                    None => Err(error(net, format!("unknown net '{}'", net.name))),
+                })
+                .collect::<Result<Vec<Net>, NetlistError>>()?;
+            let outputs = instance
+                .outputs
+                .iter()
+                .map(|net| match scope.get(&net.name) {
+                    Some(&connection) => Ok(connection),
+                    None if net.name == "_" => Ok(circuit.new_net()),
+                    None => Err(error(net, format!("unknown net '{}'", net.name))),
+                })
+                .collect::<Result<Vec<Net>, NetlistError>>()?;
+
+            if let Some(&child) = self.definitions.get(instance.kind.name.as_str()) {
+                self.subcircuit(instance, child, prefix, inputs, outputs, circuit)?;
+            } else if self.builtins.contains(&instance.kind.name.as_str()) {
+                let path = format!("{}{}", prefix, instance.name.name);
+                let (_, input_ports, output_ports) = BUILTINS.iter().find(|(builtin, _, _)| *builtin == instance.kind.name).unwrap();
+                let ports = port_names(input_ports, inputs.len()).into_iter().zip(&inputs);
+                for (port, net) in ports.chain(port_names(output_ports, outputs.len()).into_iter().zip(&outputs)) {
+                    circuit.alias_net(&format!("{}.{}", path, port), *net);
+                }
+                match instance.kind.name.as_str() {
+                    "tie0" | "tie1" => tie_off(instance, &outputs, circuit.nets_mut())?,
+                    _ => {
+                        let component = builtin(instance, inputs, outputs, circuit.nets_mut())?;
+                        circuit.add_instance(&path, component);
+                    }
+                }
+            } else {
+                return Err(error(&instance.kind, format!("unknown circuit '{}'", instance.kind.name)));
+            }
+        }
+        Ok(())
+    }
+
+    fn subcircuit(
+        &mut self,
+        instance: &Instance,
+        child: &Definition,
+        prefix: &str,
+        inputs: Vec<Net>,
+        outputs: Vec<Net>,
+        circuit: &mut Circuit,
+    ) -> Result<(), NetlistError> {
+        let kind = &instance.kind.name;
+        if self.stack.contains(kind) {
+            return Err(error(&instance.kind, format!("circuit '{}' instantiates itself", kind)));
+        }
+        if instance.parameter.is_some() {
+            return Err(error(&instance.kind, format!("circuit '{}' does not take a parameter", kind)));
+        }
+        check_arity(instance, child.inputs.len(), child.outputs.len())?;
+
+        // Ports are reachable under the instance path as well, e.g. `fa1.cin`
+        let prefix = format!("{}{}.", prefix, instance.name.name);
+        let mut scope = HashMap::new();
+        for (port, net) in child.inputs.iter().zip(inputs).chain(child.outputs.iter().zip(outputs)) {
+            circuit.alias_net(&format!("{}{}", prefix, port.name), net);
+            declare(&mut scope, port, || Ok(net))?;
+        }
+
+        self.stack.push(kind.clone());
+        self.instantiate(child, &prefix, scope, circuit)?;
+        self.stack.pop();
+        Ok(())
+    }
+}
+
+fn builtin(instance: &Instance, inputs: Vec<Net>, outputs: Vec<Net>, nets: &mut Nets) -> Result<Box<dyn Component>, NetlistError> {
+    let kind = instance.kind.name.as_str();
+    let (_, input_ports, output_ports) = BUILTINS.iter().find(|(builtin, _, _)| *builtin == kind).unwrap();
+
+    if *input_ports == "*" {
+        if inputs.is_empty() {
+            return Err(error(&instance.name, format!("'{}' needs at least one input", kind)));
+        }
+        check_arity(instance, inputs.len(), 1)?;
+    } else {
+        let count = |ports: &str| ports.split(',').filter(|port| !port.trim().is_empty()).count();
+        check_arity(instance, count(input_ports), count(output_ports))?;
+    }
+
+    let mut inputs = inputs.into_iter();
+    let mut outputs = outputs.into_iter();
+    let mut input = || inputs.next().unwrap();
+    let mut output = || outputs.next().unwrap();
+
+    let mut component: Box<dyn Component> = match kind {
+        "and" => Box::new(ANDGate::with_inputs(input_list(&mut input, instance), output())),
+        "or" => Box::new(ORGate::with_inputs(input_list(&mut input, instance), output())),
+        "xor" => Box::new(XORGate::with_inputs(input_list(&mut input, instance), output())),
+        "nand" => Box::new(NANDGate::with_inputs(input_list(&mut input, instance), output())),
+        "nor" => Box::new(NORGate::with_inputs(input_list(&mut input, instance), output())),
+        "xnor" => Box::new(XNORGate::with_inputs(input_list(&mut input, instance), output())),
+        "not" => Box::new(NOTGate::new(input(), output())),
+        "buf" => Box::new(Buffer::new(input(), output())),
+        "tri" => Box::new(TriStateBuffer::new(input(), input(), output())),
+        "half_adder" => Box::new(HalfAdder::new(input(), input(), output(), output())),
+        "full_adder" => Box::new(FullAdder::new(nets, input(), input(), input(), output(), output())),
+        "srlatch" => Box::new(SRLatch::nor(input(), input(), output(), output())),
+        "dlatch" => Box::new(DLatch::new(input(), input(), output(), output())),
+        "dff" => Box::new(DFlipFlop::new(input(), input(), output(), output())),
+        "dffr" => Box::new(DFlipFlop::with_reset(input(), input(), input(), output(), output())),
+        "jkff" => Box::new(JKFlipFlop::new(input(), input(), input(), output(), output())),
+        "clock" => return Ok(Box::new(Clock::new(nets, output(), instance.parameter.unwrap_or(1)))),
+        _ => unreachable!(),
+    };
+    if let Some(delay) = instance.parameter {
+        component.set_delay(delay);
+    }
+    Ok(component)
+}
+
+// Hold the output of a tie0 or tie1 at its level. Nothing drives the net, like a constant
+// compiled from an expression.
+fn tie_off(instance: &Instance, outputs: &[Net], nets: &mut Nets) -> Result<(), NetlistError> {
+    check_arity(instance, 0, 1)?;
+    if instance.parameter.is_some() {
+        return Err(error(&instance.kind, format!("'{}' does not take a parameter", instance.kind.name)));
+    }
+    let level = if instance.kind.name == "tie1" { Level::High } else { Level::Low };
+    nets.set_level(outputs[0], level);
+    Ok(())
+}
+
+// Port names of a built-in, in0.. for gates that take any number of inputs
+fn port_names(ports: &str, count: usize) -> Vec<String> {
+    if ports == "*" {
+        return (0..count).map(|i| format!("in{}", i)).collect();
+    }
+    ports.split(',').map(|port| port.trim().to_string()).filter(|port| !port.is_empty()).collect()
+}
+
+fn input_list(input: &mut impl FnMut() -> Net, instance: &Instance) -> Vec<Net> {
+    instance.inputs.iter().map(|_| input()).collect()
+}
+
+fn check_arity(instance: &Instance, inputs: usize, outputs: usize) -> Result<(), NetlistError> {
+    let kind = &instance.kind.name;
+    if instance.inputs.len() != inputs {
+        return Err(error(
+            &instance.name,
+            format!("'{}' takes {} inputs, found {}", kind, inputs, instance.inputs.len()),
+        ));
+    }
+    if instance.outputs.len() != outputs {
+        return Err(error(
+            &instance.name,
+            format!("'{}' has {} outputs, found {}", kind, outputs, instance.outputs.len()),
+        ));
+    }
+    Ok(())
+}
+
+fn declare(
+    scope: &mut HashMap<String, Net>,
+    name: &NetRef,
+    net: impl FnOnce() -> Result<Net, String>,
+) -> Result<(), NetlistError> {
+    if name.name == "_" || scope.contains_key(&name.name) {
+        return Err(error(name, format!("net '{}' is already declared", name.name)));
+    }
+    scope.insert(name.name.clone(), net().map_err(|message| error(name, message))?);
+    Ok(())
+}
+
+fn error(at: &NetRef, message: impl Into<String>) -> NetlistError {
+    NetlistError::new(at.line, at.column, message)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::simulation::Simulator;
+
+    const FULL_ADDER: &str = include_str!("../../netlists/full_adder.net");
+
+    #[test]
+    fn test_load_full_adder() {
+        let circuit = load(FULL_ADDER, None).unwrap();
+        assert_eq!(circuit.name(), "adder");
+        assert_eq!(circuit.inputs(), &["a", "b", "cin"]);
+        assert_eq!(circuit.outputs(), &["sum", "cout"]);
+        assert!(circuit.net("ha1.partial").is_some());
+
+        let mut simulator = Simulator::new(circuit);
+        for (a, b, cin, expected_sum, expected_cout) in [
+            (Level::Low, Level::Low, Level::Low, Level::Low, Level::Low),
+            (Level::High, Level::Low, Level::High, Level::Low, Level::High),
+            (Level::High, Level::High, Level::High, Level::High, Level::High),
+            (Level::Low, Level::High, Level::Low, Level::High, Level::Low),
+        ] {
+            simulator.set_level("a", a).unwrap();
+            simulator.set_level("b", b).unwrap();
+            simulator.set_level("cin", cin).unwrap();
+            simulator.settle().unwrap();
+            assert_eq!(simulator.level("sum").unwrap(), expected_sum);
+            assert_eq!(simulator.level("cout").unwrap(), expected_cout);
+        }
+    }
+
+    #[test]
+    fn test_hierarchical_names() {
+        let circuit = load(FULL_ADDER, None).unwrap();
+        // Ports of instances are aliases of the nets wired to them
+        let net = |name: &str| circuit.net(name).unwrap();
+        assert_eq!(net("ha1.sum"), net("s1"));
+        assert_eq!(net("ha2.b"), net("cin"));
+        assert_eq!(net("ha2.x1.y"), net("ha2.partial"));
+        assert_eq!(net("o1.in1"), net("c2"));
+        assert_eq!(circuit.nets_under("ha1.x1"), ["ha1.x1.in0", "ha1.x1.in1", "ha1.x1.y"]);
+
+        let circuit = load_builtin("full_adder").unwrap();
+        assert_eq!(circuit.net("u.ha2.carry").unwrap(), circuit.net("u.or.in1").unwrap());
+    }
+
+    #[test]
+    fn test_load_named_circuit() {
+        let circuit = load(FULL_ADDER, Some("half_adder_gates")).unwrap();
+        assert_eq!(circuit.inputs(), &["a", "b"]);
+        assert!(load(FULL_ADDER, Some("missing")).is_err());
+    }
+
+    #[test]
+    fn test_builtins_and_parameters() {
+        let circuit = load(
+            "circuit top(d) -> (q) {\n\
+             \x20   wire clk;\n\
+             \x20   clock #2 c(  ) -> (clk);\n\
+             \x20   dff #1 ff(d, clk) -> (q, _);\n\
+             }",
+            None,
+        )
+        .unwrap();
+
+        let mut simulator = Simulator::new(circuit);
+        simulator.set_level("d", Level::High).unwrap();
+        simulator.run_until(2).unwrap();
+        assert_eq!(simulator.level("q").unwrap(), Level::Undefined);
+        simulator.run_until(3).unwrap();
+        assert_eq!(simulator.level("q").unwrap(), Level::High);
+    }
+
+    #[test]
+    fn test_load_builtin() {
+        let circuit = load_builtin("full_adder").unwrap();
+        assert_eq!(circuit.name(), "full_adder");
+        assert_eq!(circuit.inputs(), &["a", "b", "cin"]);
+        assert_eq!(circuit.outputs(), &["sum", "cout"]);
+        assert_eq!(load_builtin("xor").unwrap().inputs(), &["a", "b"]);
+        assert!(load_builtin("mux").is_err());
+    }
+
+    #[test]
+    fn test_load_errors() {
+        for (source, expected) in [
+            (
+                "circuit top(a) -> (y) {\n  and g(a, b) -> (y);\n}",
+                NetlistError::new(2, 12, "unknown net 'b'"),
+            ),
+            (
+                "circuit top(a) -> (y) {\n  not g(a, a) -> (y);\n}",
+                NetlistError::new(2, 7, "'not' takes 1 inputs, found 2"),
+            ),
+            (
+                "circuit top(a) -> (y) {\n  mux g(a) -> (y);\n}",
+                NetlistError::new(2, 3, "unknown circuit 'mux'"),
+            ),
+            (
+                "circuit top(a) -> (y) {\n  wire a;\n}",
+                NetlistError::new(2, 8, "net 'a' is already declared"),
+            ),
+            (
+                "circuit top(a) -> (y) {\n  top t(a) -> (y);\n}",
+                NetlistError::new(2, 3, "circuit 'top' instantiates itself"),
+            ),
+            (
+                "circuit and(a) -> (y) {\n}",
+                NetlistError::new(1, 9, "circuit 'and' has the name of a built-in"),
+            ),
+            (
+                "circuit top(a) -> (y, z) {\n  not g1(a) -> (y);\n  not g1(a) -> (z);\n}",
+                NetlistError::new(3, 7, "instance 'g1' is declared twice"),
+            ),
+            (
+                "circuit inner(a) -> (y) {\n  wire w;\n  not g(a) -> (w);\n  not h(w) -> (y);\n}\n\
+                 circuit top(a) -> (y) {\n  wire u.w;\n  inner u(a) -> (y);\n}",
+                NetlistError::new(2, 8, "Net name 'u.w' is already taken in top"),
+            ),
+        ] {
+            assert_eq!(load(source, None).err(), Some(expected));
+        }
+    }
+
+    #[test]
+    fn test_declared_name_like_automatic_one() {
+        // The unconnected flip-flop output is named automatically, and must not take over net3
+        let source = "circuit top(a) -> (y) {\n  wire net3;\n  not g1(a) -> (net3);\n  dff f(net3, a) -> (y, _);\n}";
+        let circuit = load(source, None).unwrap();
+        let mut names = circuit.net_names().to_vec();
+        names.sort();
+        names.dedup();
+        assert_eq!(names.len(), circuit.net_count());
+
+        let mut simulator = Simulator::new(circuit);
+        simulator.set_level("a", Level::Low).unwrap();
+        simulator.settle().unwrap();
+        assert_eq!(simulator.level("net3").unwrap(), Level::High);
+    }
+}
@@ -0,0 +1,206 @@
+use crate::netlist::lexer::{tokenize, Token, TokenKind};
+use crate::netlist::NetlistError;
+
+// A name together with where it appears in the source
+#[derive(Debug, Clone, PartialEq)]
+pub struct NetRef {
+    pub name: String,
+    pub line: usize,
+    pub column: usize,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Instance {
+    pub kind: NetRef,
+    pub name: NetRef,
+    pub parameter: Option<u64>,
+    pub inputs: Vec<NetRef>,
+    pub outputs: Vec<NetRef>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Definition {
+    pub name: NetRef,
+    pub inputs: Vec<NetRef>,
+    pub outputs: Vec<NetRef>,
+    pub wires: Vec<NetRef>,
+    pub instances: Vec<Instance>,
+}
+
+pub fn parse(source: &str) -> Result<Vec<Definition>, NetlistError> {
+    let mut parser = Parser::new(tokenize(source)?);
+    let mut definitions = Vec::new();
+    while parser.peek().kind != TokenKind::Eof {
+        definitions.push(parser.definition()?);
+    }
+    Ok(definitions)
+}
+
+// Token cursor shared with the Verilog reader
+pub(crate) struct Parser {
+    tokens: Vec<Token>,
+    position: usize,
+}
+
+impl Parser {
+    pub(crate) fn new(tokens: Vec<Token>) -> Parser {
+        Parser { tokens, position: 0 }
+    }
+
+    pub(crate) fn peek(&self) -> &Token {
+        &self.tokens[self.position]
+    }
+
+    pub(crate) fn next(&mut self) -> Token {
+        let token = self.tokens[self.position].clone();
+        if token.kind != TokenKind::Eof {
+            self.position += 1;
+        }
+        token
+    }
+
+    pub(crate) fn error(token: &Token, expected: &str) -> NetlistError {
+        NetlistError::new(
+            token.line,
+            token.column,
+            format!("expected {}, found {}", expected, token.kind.describe()),
+        )
+    }
+
+    pub(crate) fn expect(&mut self, kind: TokenKind) -> Result<Token, NetlistError> {
+        let token = self.next();
+        if token.kind == kind {
+            Ok(token)
+        } else {
+            Err(Parser::error(&token, &kind.describe()))
+        }
+    }
+
+    pub(crate) fn ident(&mut self, expected: &str) -> Result<NetRef, NetlistError> {
+        let token = self.next();
+        match token.kind {
+            TokenKind::Ident(name) => Ok(NetRef { name, line: token.line, column: token.column }),
+            _ => Err(Parser::error(&token, expected)),
+        }
+    }
+
+    // `(a, b, c)`, possibly empty
+    fn name_list(&mut self) -> Result<Vec<NetRef>, NetlistError> {
+        self.expect(TokenKind::LParen)?;
+        let mut names = Vec::new();
+        if self.peek().kind == TokenKind::RParen {
+            self.next();
+            return Ok(names);
+        }
+        loop {
+            names.push(self.ident("a net name")?);
+            let token = self.next();
+            match token.kind {
+                TokenKind::Comma => continue,
+                TokenKind::RParen => return Ok(names),
+                _ => return Err(Parser::error(&token, "',' or ')'")),
+            }
+        }
+    }
+
+    fn definition(&mut self) -> Result<Definition, NetlistError> {
+        let keyword = self.ident("'circuit'")?;
+        if keyword.name != "circuit" {
+            return Err(NetlistError::new(
+                keyword.line,
+                keyword.column,
+                format!("expected 'circuit', found '{}'", keyword.name),
+            ));
+        }
+        let name = self.ident("a circuit name")?;
+        let inputs = self.name_list()?;
+        self.expect(TokenKind::Arrow)?;
+        let outputs = self.name_list()?;
+        self.expect(TokenKind::LBrace)?;
+
+        let mut definition = Definition { name, inputs, outputs, wires: Vec::new(), instances: Vec::new() };
+        loop {
+            if self.peek().kind == TokenKind::RBrace {
+                self.next();
+                return Ok(definition);
+            }
+            let kind = self.ident("'wire', an instance or '}'")?;
+            if kind.name == "wire" {
+                loop {
+                    definition.wires.push(self.ident("a wire name")?);
+                    let token = self.next();
+                    match token.kind {
+                        TokenKind::Comma => continue,
+                        TokenKind::Semicolon => break,
+                        _ => return Err(Parser::error(&token, "',' or ';'")),
+                    }
+                }
+            } else {
+                definition.instances.push(self.instance(kind)?);
+            }
+        }
+    }
+
+    fn instance(&mut self, kind: NetRef) -> Result<Instance, NetlistError> {
+        let parameter = if self.peek().kind == TokenKind::Hash {
+            self.next();
+            let token = self.next();
+            match token.kind {
+                TokenKind::Number(value) => Some(value),
+                _ => return Err(Parser::error(&token, "a number")),
+            }
+        } else {
+            None
+        };
+        let name = self.ident("an instance name")?;
+        let inputs = self.name_list()?;
+        self.expect(TokenKind::Arrow)?;
+        let outputs = self.name_list()?;
+        self.expect(TokenKind::Semicolon)?;
+        Ok(Instance { kind, name, parameter, inputs, outputs })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_definition() {
+        let definitions = parse(
+            "circuit top(a, b) -> (y) {\n\
+             \x20   wire w;\n\
+             \x20   and #3 g1(a, b) -> (w);\n\
+             \x20   not g2(w) -> (y);\n\
+             }",
+        )
+        .unwrap();
+
+        assert_eq!(definitions.len(), 1);
+        let top = &definitions[0];
+        assert_eq!(top.name.name, "top");
+        assert_eq!(top.inputs.iter().map(|n| n.name.as_str()).collect::<Vec<_>>(), vec!["a", "b"]);
+        assert_eq!(top.outputs[0].name, "y");
+        assert_eq!(top.wires[0].name, "w");
+        assert_eq!(top.instances.len(), 2);
+        assert_eq!(top.instances[0].kind.name, "and");
+        assert_eq!(top.instances[0].parameter, Some(3));
+        assert_eq!((top.instances[1].name.line, top.instances[1].name.column), (4, 9));
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert_eq!(
+            parse("circuit top(a) -> (y) {\n  and g1(a a) -> (y);\n}"),
+            Err(NetlistError::new(2, 12, "expected ',' or ')', found 'a'"))
+        );
+        assert_eq!(
+            parse("module top"),
+            Err(NetlistError::new(1, 1, "expected 'circuit', found 'module'"))
+        );
+        assert_eq!(
+            parse("circuit top() -> (y) {\n  not g(a) -> (y)\n"),
+            Err(NetlistError::new(3, 1, "expected ';', found end of file"))
+        );
+    }
+}
@@ -0,0 +1,160 @@
+use std::fmt::Write;
+
+use crate::components::gates::GateKind;
+use crate::components::{Level, NetId};
+use crate::netlist::BUILTINS;
+use crate::simulation::Circuit;
+
+// Write a circuit of logic gates in the netlist format, so it can be loaded again.
+// Components other than logic gates cannot be written. Nets nothing drives that are held at
+// 0 or 1, like constants compiled from expressions, are written as tie0 and tie1. A circuit
+// named like a built-in, e.g. `and` from load_builtin, is written as `and_circuit`.
+pub fn write(circuit: &Circuit) -> Result<String, String> {
+    let mut name = circuit.name().to_string();
+    if BUILTINS.iter().any(|(builtin, _, _)| *builtin == name) {
+        name.push_str("_circuit");
+    }
+    let mut netlist = String::new();
+    writeln!(
+        netlist,
+        "circuit {}({}) -> ({}) {{",
+        name,
+        circuit.inputs().join(", "),
+        circuit.outputs().join(", ")
+    )
+    .unwrap();
+
+    let wires: Vec<&str> = circuit
+        .net_names()
+        .iter()
+        .filter(|name| !circuit.inputs().contains(name) && !circuit.outputs().contains(name))
+        .map(|name| name.as_str())
+        .collect();
+    if !wires.is_empty() {
+        writeln!(netlist, "    wire {};", wires.join(", ")).unwrap();
+    }
+
+    for id in circuit.component_ids() {
+        let component = circuit.component(id);
+        let kind = match component.gate_kind() {
+            Some(GateKind::And) => "and",
+            Some(GateKind::Or) => "or",
+            Some(GateKind::Xor) => "xor",
+            Some(GateKind::Nand) => "nand",
+            Some(GateKind::Nor) => "nor",
+            Some(GateKind::Xnor) => "xnor",
+            Some(GateKind::Not) => "not",
+            Some(GateKind::Buffer) => "buf",
+            Some(GateKind::TriState) => "tri",
+            None => return Err(format!("component {} is not a logic gate", id.index())),
+        };
+        let delay = match component.delay() {
+            0 => String::new(),
+            delay => format!(" #{}", delay),
+        };
+        let names = |nets: &[NetId]| nets.iter().map(|&net| circuit.net_name(net)).collect::<Vec<&str>>().join(", ");
+        writeln!(
+            netlist,
+            "    {}{} g{}({}) -> ({});",
+            kind,
+            delay,
+            id.index(),
+            names(circuit.component_inputs(id)),
+            names(circuit.component_outputs(id))
+        )
+        .unwrap();
+    }
+
+    let inputs: Vec<NetId> = circuit.inputs().iter().filter_map(|name| circuit.net(name)).collect();
+    for (index, (net, high)) in constant_nets(circuit, &inputs).into_iter().enumerate() {
+        let kind = if high { "tie1" } else { "tie0" };
+        writeln!(netlist, "    {} t{}() -> ({});", kind, index, circuit.net_name(net)).unwrap();
+    }
+
+    netlist.push_str("}\n");
+    Ok(netlist)
+}
+
+// Nets other than the given inputs that nothing drives and that are held at 0 or 1,
+// with whether they are held at 1
+pub(crate) fn constant_nets(circuit: &Circuit, inputs: &[NetId]) -> Vec<(NetId, bool)> {
+    let mut driven = vec![false; circuit.net_count()];
+    for component in circuit.component_ids() {
+        for &net in circuit.component_outputs(component) {
+            driven[net.index()] = true;
+        }
+    }
+    circuit
+        .net_ids()
+        .filter(|&net| !driven[net.index()] && !inputs.contains(&net))
+        .filter_map(|net| match circuit.net_at(net).level() {
+            Level::Low => Some((net, false)),
+            Level::High => Some((net, true)),
+            _ => None,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::netlist::load;
+
+    #[test]
+    fn test_write() {
+        let circuit = load(
+            "circuit half(a, b) -> (s, c) { xor x(a, b) -> (s); and #2 y(a, b) -> (c); }\n\
+             circuit top(a, b) -> (s) { wire c; half h(a, b) -> (s, c); }",
+            None,
+        )
+        .unwrap();
+        let netlist = write(&circuit).unwrap();
+        assert_eq!(
+            netlist,
+            "circuit top(a, b) -> (s) {\n\
+             \x20   wire c;\n\
+             \x20   xor g0(a, b) -> (s);\n\
+             \x20   and #2 g1(a, b) -> (c);\n\
+             }\n"
+        );
+        assert_eq!(write(&load(&netlist, None).unwrap()).unwrap(), netlist);
+    }
+
+    #[test]
+    fn test_write_constants() {
+        let circuit = crate::expression::compile(&crate::expression::parse("1").unwrap());
+        let netlist = write(&circuit).unwrap();
+        assert_eq!(netlist, "circuit expr() -> (y) {\n    tie1 t0() -> (y);\n}\n");
+        let mut simulator = crate::simulation::Simulator::new(load(&netlist, None).unwrap());
+        simulator.settle().unwrap();
+        assert_eq!(simulator.level("y"), Ok(Level::High));
+
+        let circuit = load("circuit c(a) -> (y) { wire zero; tie0 t(a) -> (zero); }", None);
+        assert!(circuit.is_err(), "a tie-off takes no inputs");
+        let circuit = load("circuit c(a) -> (y) { wire zero; tie0 t() -> (zero); or g(a, zero) -> (y); }", None).unwrap();
+        assert_eq!(write(&load(&write(&circuit).unwrap(), None).unwrap()).unwrap(), write(&circuit).unwrap());
+    }
+
+    #[test]
+    fn test_write_builtin() {
+        let circuit = crate::netlist::load_builtin("and").unwrap();
+        let netlist = write(&circuit).unwrap();
+        assert!(netlist.starts_with("circuit and_circuit(a, b) -> (y) {\n"));
+        let loaded = load(&netlist, None).unwrap();
+        assert_eq!(loaded.name(), "and_circuit");
+        assert_eq!(write(&loaded).unwrap(), netlist);
+    }
+
+    #[test]
+    fn test_write_hierarchical_names() {
+        let circuit = load(
+            "circuit inner(a) -> (y) { wire n; not g(a) -> (n); not h(n) -> (y); }\n\
+             circuit top(a) -> (y) { inner i(a) -> (y); }",
+            None,
+        )
+        .unwrap();
+        let netlist = write(&circuit).unwrap();
+        assert!(netlist.contains("wire i.n;"));
+        assert_eq!(write(&load(&netlist, None).unwrap()).unwrap(), netlist);
+    }
+}
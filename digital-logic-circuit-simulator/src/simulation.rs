@@ -0,0 +1,12 @@
+pub mod arena;
+pub mod circuit;
+pub mod simulator;
+pub mod threaded;
+pub mod trace;
+
+pub use arena::{ArenaBuilder, ArenaCircuit};
+pub use crate::components::{ComponentId, NetId};
+pub use circuit::{Circuit, Contention};
+pub use simulator::{SimulationError, Simulator};
+pub use threaded::ThreadedCircuit;
+pub use trace::Trace;
@@ -0,0 +1,442 @@
+use std::collections::HashMap;
+use std::mem;
+
+use crate::components::gates::GateKind;
+use crate::components::nets::{component_id, net_id};
+use crate::components::{ComponentId, Level, NetId};
+use crate::simulation::simulator::DEFAULT_MAX_ITERATIONS;
+use crate::simulation::{Circuit, SimulationError};
+
+struct Gate {
+    kind: GateKind,
+    // Range of the gate's inputs in `pins`
+    start: u32,
+    end: u32,
+    output: NetId,
+}
+
+// A gate-level circuit compiled into flat arrays indexed by NetId and ComponentId.
+// Levels live in one vector instead of connections tracking their drivers, the inputs of
+// all gates in another, and the readers of every net are stored back to back, so
+// evaluation does no dynamic dispatch or allocation. Every net has at most one driver, and
+// nets nobody drives keep the level they had in the circuit, so constants work.
+//
+// Like the zero-delay part of Simulator, gates are evaluated in rounds: every gate whose
+// inputs changed reads the levels of the previous round, so feedback such as latches
+// built from gates behaves the same. Propagation delays are ignored.
+//
+// This engine complements Circuit and Simulator, which share its NetId indices, rather
+// than replacing them: it only takes logic gates, so flip-flops, clocks, tri-state buses
+// with several drivers and timing stay with the event-driven simulator. Circuits are either built directly with ArenaBuilder or
+// compiled from a Circuit.
+pub struct ArenaCircuit {
+    name: String,
+    net_names: Vec<String>,
+    name_lookup: HashMap<String, NetId>,
+    inputs: Vec<NetId>,
+    outputs: Vec<NetId>,
+    levels: Vec<Level>,
+    gates: Vec<Gate>,
+    pins: Vec<NetId>,
+    // Readers of net i are fanout[fanout_start[i]..fanout_start[i + 1]]
+    fanout_start: Vec<u32>,
+    fanout: Vec<ComponentId>,
+    pending: Vec<ComponentId>,
+    scheduled: Vec<bool>,
+    evaluations: usize,
+    max_iterations: usize,
+}
+
+// Builds an ArenaCircuit net by net and gate by gate, without going through Circuit
+pub struct ArenaBuilder {
+    name: String,
+    net_names: Vec<String>,
+    name_lookup: HashMap<String, NetId>,
+    inputs: Vec<NetId>,
+    outputs: Vec<NetId>,
+    levels: Vec<Level>,
+    gates: Vec<Gate>,
+    pins: Vec<NetId>,
+}
+
+impl ArenaBuilder {
+    pub fn new(name: &str) -> ArenaBuilder {
+        ArenaBuilder {
+            name: name.to_string(),
+            net_names: Vec::new(),
+            name_lookup: HashMap::new(),
+            inputs: Vec::new(),
+            outputs: Vec::new(),
+            levels: Vec::new(),
+            gates: Vec::new(),
+            pins: Vec::new(),
+        }
+    }
+
+    // Create a named net that is driven from outside the circuit
+    pub fn add_input(&mut self, name: &str) -> NetId {
+        let net = self.add_net(name);
+        self.inputs.push(net);
+        net
+    }
+
+    // Create a named net that is observed from outside the circuit
+    pub fn add_output(&mut self, name: &str) -> NetId {
+        let net = self.add_net(name);
+        self.outputs.push(net);
+        net
+    }
+
+    // Create a new named net, initially undefined
+    pub fn add_net(&mut self, name: &str) -> NetId {
+        assert!(!self.name_lookup.contains_key(name), "Net name '{}' is already taken in {}", name, self.name);
+        let net = self.push_net(name.to_string());
+        self.name_lookup.insert(name.to_string(), net);
+        net
+    }
+
+    // Hold a net nobody drives at a level, e.g. a constant
+    pub fn set_level(&mut self, net: NetId, level: Level) {
+        self.levels[net.index()] = level;
+    }
+
+    pub fn add_gate(&mut self, kind: GateKind, inputs: &[NetId], output: NetId) -> ComponentId {
+        let start = self.pins.len() as u32;
+        self.pins.extend_from_slice(inputs);
+        self.gates.push(Gate { kind, start, end: self.pins.len() as u32, output });
+        component_id(self.gates.len() - 1)
+    }
+
+    // Fails for nets with several drivers, counting input ports as driven from outside
+    pub fn build(self) -> Result<ArenaCircuit, String> {
+        let net_count = self.levels.len();
+        let mut driven = vec![false; net_count];
+        for input in &self.inputs {
+            driven[input.index()] = true;
+        }
+        for gate in &self.gates {
+            if driven[gate.output.index()] {
+                return Err(format!("Net '{}' of {} has several drivers", self.net_names[gate.output.index()], self.name));
+            }
+            driven[gate.output.index()] = true;
+        }
+
+        // Count the readers of every net, then place them. A gate reading a net twice is
+        // one reader.
+        let readers = |gate: &Gate| {
+            let pins = &self.pins[gate.start as usize..gate.end as usize];
+            pins.iter().enumerate().filter(|&(i, net)| !pins[..i].contains(net)).map(|(_, net)| net.index())
+        };
+        let mut fanout_start = vec![0u32; net_count + 1];
+        for gate in &self.gates {
+            for net in readers(gate) {
+                fanout_start[net + 1] += 1;
+            }
+        }
+        for net in 0..net_count {
+            fanout_start[net + 1] += fanout_start[net];
+        }
+        let mut placed = fanout_start.clone();
+        let mut fanout = vec![component_id(0); fanout_start[net_count] as usize];
+        for (index, gate) in self.gates.iter().enumerate() {
+            for net in readers(gate) {
+                fanout[placed[net] as usize] = component_id(index);
+                placed[net] += 1;
+            }
+        }
+
+        let gate_count = self.gates.len();
+        Ok(ArenaCircuit {
+            name: self.name,
+            net_names: self.net_names,
+            name_lookup: self.name_lookup,
+            inputs: self.inputs,
+            outputs: self.outputs,
+            levels: self.levels,
+            // Every gate has to be evaluated once to drive its output
+            pending: (0..gate_count).map(component_id).collect(),
+            scheduled: vec![true; gate_count],
+            gates: self.gates,
+            pins: self.pins,
+            fanout_start,
+            fanout,
+            evaluations: 0,
+            max_iterations: DEFAULT_MAX_ITERATIONS,
+        })
+    }
+
+    fn push_net(&mut self, name: String) -> NetId {
+        self.net_names.push(name);
+        self.levels.push(Level::Undefined);
+        net_id(self.levels.len() - 1)
+    }
+}
+
+impl ArenaCircuit {
+    // Compile the gates of a circuit, keeping its net names and levels.
+    // Fails for components that are not logic gates and for nets with several drivers.
+    pub fn new(circuit: &Circuit) -> Result<ArenaCircuit, String> {
+        let mut builder = ArenaBuilder::new(circuit.name());
+        for net in circuit.net_ids() {
+            builder.push_net(circuit.net_name(net).to_string());
+            builder.set_level(net, circuit.net_at(net).level());
+        }
+        // Every name a net is known by, including hierarchical aliases
+        builder.name_lookup = circuit.net_lookup().map(|(name, net)| (name.to_string(), net)).collect();
+        builder.inputs = circuit.inputs().iter().map(|input| circuit.net(input).unwrap()).collect();
+        builder.outputs = circuit.outputs().iter().map(|output| circuit.net(output).unwrap()).collect();
+
+        for component in circuit.component_ids() {
+            let outputs = circuit.component_outputs(component);
+            let (Some(kind), [output]) = (circuit.component(component).gate_kind(), outputs) else {
+                let outputs: Vec<&str> = outputs.iter().map(|&net| circuit.net_name(net)).collect();
+                return Err(format!("{} drives {} with something other than a logic gate", circuit.name(), outputs.join(", ")));
+            };
+            builder.add_gate(kind, circuit.component_inputs(component), *output);
+        }
+        builder.build()
+    }
+
+    pub fn with_max_iterations(mut self, max_iterations: usize) -> ArenaCircuit {
+        self.max_iterations = max_iterations;
+        self
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn net(&self, name: &str) -> Option<NetId> {
+        self.name_lookup.get(name).copied()
+    }
+
+    pub fn net_name(&self, net: NetId) -> &str {
+        &self.net_names[net.index()]
+    }
+
+    // Nets of the input ports in declaration order
+    pub fn inputs(&self) -> &[NetId] {
+        &self.inputs
+    }
+
+    // Nets of the output ports in declaration order
+    pub fn outputs(&self) -> &[NetId] {
+        &self.outputs
+    }
+
+    pub fn net_count(&self) -> usize {
+        self.levels.len()
+    }
+
+    pub fn component_count(&self) -> usize {
+        self.gates.len()
+    }
+
+    pub fn kind(&self, component: ComponentId) -> GateKind {
+        self.gates[component.index()].kind
+    }
+
+    // Nets read by the given gate
+    pub fn component_inputs(&self, component: ComponentId) -> &[NetId] {
+        let gate = &self.gates[component.index()];
+        &self.pins[gate.start as usize..gate.end as usize]
+    }
+
+    // Net driven by the given gate
+    pub fn component_output(&self, component: ComponentId) -> NetId {
+        self.gates[component.index()].output
+    }
+
+    // Gates reading the given net
+    pub fn fanout(&self, net: NetId) -> &[ComponentId] {
+        &self.fanout[self.fanout_start[net.index()] as usize..self.fanout_start[net.index() + 1] as usize]
+    }
+
+    // Total number of gate evaluations so far
+    pub fn evaluations(&self) -> usize {
+        self.evaluations
+    }
+
+    pub fn level(&self, net: NetId) -> Level {
+        self.levels[net.index()]
+    }
+
+    // Force a net to a level; the gates reading it are evaluated by the next settle
+    pub fn set_level(&mut self, net: NetId, level: Level) {
+        if self.levels[net.index()] != level {
+            self.levels[net.index()] = level;
+            self.schedule_fanout(net);
+        }
+    }
+
+    // Evaluate gates until no level changes any more.
+    // Returns the number of rounds that took.
+    pub fn settle(&mut self) -> Result<usize, SimulationError> {
+        let mut rounds = 0;
+        let mut round = Vec::new();
+        let mut changes = Vec::new();
+        let mut levels = Vec::new();
+        while !self.pending.is_empty() {
+            if rounds == self.max_iterations {
+                return Err(self.oscillation(rounds));
+            }
+            rounds += 1;
+
+            mem::swap(&mut round, &mut self.pending);
+            for &component in &round {
+                self.scheduled[component.index()] = false;
+                let gate = &self.gates[component.index()];
+                levels.clear();
+                levels.extend(self.pins[gate.start as usize..gate.end as usize].iter().map(|net| self.levels[net.index()]));
+                let level = gate.kind.evaluate(&levels);
+                if level != self.levels[gate.output.index()] {
+                    changes.push((gate.output, level));
+                }
+            }
+            self.evaluations += round.len();
+            round.clear();
+            for (net, level) in changes.drain(..) {
+                self.set_level(net, level);
+            }
+        }
+        Ok(rounds)
+    }
+
+    fn schedule_fanout(&mut self, net: NetId) {
+        let readers = self.fanout_start[net.index()] as usize..self.fanout_start[net.index() + 1] as usize;
+        for &component in &self.fanout[readers] {
+            if !self.scheduled[component.index()] {
+                self.scheduled[component.index()] = true;
+                self.pending.push(component);
+            }
+        }
+    }
+
+    fn oscillation(&self, iterations: usize) -> SimulationError {
+        let mut nets: Vec<String> = Vec::new();
+        for &component in &self.pending {
+            let name = self.net_name(self.component_output(component)).to_string();
+            if !nets.contains(&name) {
+                nets.push(name);
+            }
+        }
+        SimulationError::Oscillation { iterations, nets }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::digital_logic::arithmetic::RippleCarryAdder;
+    use crate::netlist::{load, load_builtin};
+    use crate::simulation::Simulator;
+
+    fn set_operand(circuit: &mut ArenaCircuit, prefix: &str, value: u64, bits: usize) {
+        for i in 0..bits {
+            let net = circuit.net(&format!("{}{}", prefix, i)).unwrap();
+            circuit.set_level(net, if (value >> i) & 1 == 1 { Level::High } else { Level::Low });
+        }
+    }
+
+    #[test]
+    fn test_layout() {
+        let circuit = ArenaCircuit::new(&load_builtin("full_adder").unwrap()).unwrap();
+        assert_eq!(circuit.component_count(), 5);
+        assert_eq!(circuit.inputs().len(), 3);
+        let a = circuit.net("a").unwrap();
+        assert_eq!(circuit.net_name(a), "a");
+        // a feeds the AND and XOR gate of the first half adder
+        let readers = circuit.fanout(a);
+        assert_eq!(readers.len(), 2);
+        assert!(readers.iter().all(|&gate| circuit.component_inputs(gate).contains(&a)));
+        assert!(circuit.net("u.ha1.sum").is_some());
+    }
+
+    #[test]
+    fn test_matches_simulator() {
+        let bits = 16;
+        let mut arena = ArenaCircuit::new(&RippleCarryAdder::new(bits).into_circuit()).unwrap();
+        let mut simulator = Simulator::new(RippleCarryAdder::new(bits).into_circuit());
+        for (a, b) in [(0, 0), (0xffff, 1), (0x1234, 0xedcc), (40_000, 30_000)] {
+            set_operand(&mut arena, "a", a, bits);
+            set_operand(&mut arena, "b", b, bits);
+            arena.settle().unwrap();
+            for (prefix, value) in [("a", a), ("b", b)] {
+                for i in 0..bits {
+                    let level = if (value >> i) & 1 == 1 { Level::High } else { Level::Low };
+                    simulator.set_level(&format!("{}{}", prefix, i), level).unwrap();
+                }
+            }
+            simulator.settle().unwrap();
+
+            for name in simulator.circuit().outputs() {
+                let net = arena.net(name).unwrap();
+                assert_eq!(arena.level(net), simulator.level(name).unwrap(), "{} for {} + {}", name, a, b);
+            }
+        }
+    }
+
+    #[test]
+    fn test_latch_and_oscillation() {
+        let mut latch = ArenaCircuit::new(
+            &load("circuit latch(s, r) -> (q, q_bar) { nor n1(r, q_bar) -> (q); nor n2(s, q) -> (q_bar); }", None).unwrap(),
+        )
+        .unwrap();
+        let [s, r, q] = ["s", "r", "q"].map(|name| latch.net(name).unwrap());
+        latch.set_level(s, Level::High);
+        latch.set_level(r, Level::Low);
+        latch.settle().unwrap();
+        assert_eq!(latch.level(q), Level::High);
+        latch.set_level(s, Level::Low);
+        latch.settle().unwrap();
+        assert_eq!(latch.level(q), Level::High);
+
+        let mut ring = ArenaCircuit::new(&load("circuit ring() -> (y) { not n(y) -> (y); }", None).unwrap())
+            .unwrap()
+            .with_max_iterations(10);
+        let y = ring.net("y").unwrap();
+        ring.set_level(y, Level::Low);
+        assert_eq!(
+            ring.settle(),
+            Err(SimulationError::Oscillation { iterations: 10, nets: vec!["y".to_string()] })
+        );
+    }
+
+    #[test]
+    fn test_rejected_circuits() {
+        assert!(ArenaCircuit::new(&load_builtin("dff").unwrap()).is_err());
+        let shorted = load("circuit c(a) -> (y) { not n1(a) -> (y); buf b1(a) -> (y); }", None).unwrap();
+        assert_eq!(ArenaCircuit::new(&shorted).err(), Some("Net 'y' of c has several drivers".to_string()));
+    }
+
+    #[test]
+    fn test_builder() {
+        // y = !(a & b), built without a Circuit
+        let mut builder = ArenaBuilder::new("nand");
+        let [a, b] = ["a", "b"].map(|name| builder.add_input(name));
+        let y = builder.add_output("y");
+        let t = builder.add_net("t");
+        let and = builder.add_gate(GateKind::And, &[a, b], t);
+        builder.add_gate(GateKind::Not, &[t], y);
+        let mut circuit = builder.build().unwrap();
+        assert_eq!(circuit.fanout(a), [and]);
+        assert_eq!(circuit.component_output(and), t);
+        for (first, second, expected) in [(Level::High, Level::High, Level::Low), (Level::High, Level::Low, Level::High)] {
+            circuit.set_level(a, first);
+            circuit.set_level(b, second);
+            circuit.settle().unwrap();
+            assert_eq!(circuit.level(y), expected);
+        }
+
+        let mut builder = ArenaBuilder::new("c");
+        let a = builder.add_input("a");
+        builder.add_gate(GateKind::Not, &[a], a);
+        assert_eq!(builder.build().err(), Some("Net 'a' of c has several drivers".to_string()));
+    }
+
+    #[test]
+    fn test_send_and_sync() {
+        fn shareable<T: Send + Sync>() {}
+        shareable::<ArenaCircuit>();
+    }
+}
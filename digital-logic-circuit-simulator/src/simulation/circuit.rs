@@ -0,0 +1,497 @@
+use core::fmt;
+use std::collections::HashMap;
+
+use crate::components::nets::{component_id, net_id};
+use crate::components::{Component, ComponentId, Connection, Level, NetId, Nets};
+
+// Opposing drivers on an exclusive net
+#[derive(Debug, Clone, PartialEq)]
+pub struct Contention {
+    pub net: String,
+    pub drivers: Vec<Level>,
+}
+
+impl fmt::Display for Contention {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let levels: Vec<String> = self.drivers.iter().map(|level| level.to_string()).collect();
+        write!(f, "net '{}' is driven by {}", self.net, levels.join(", "))
+    }
+}
+
+// A flat netlist: every primitive component together with the nets that wire them.
+// Nets live in a Nets arena addressed by NetId and components in a vector addressed by
+// ComponentId. Composite components are broken down into their primitives when they are
+// added, so the order in which parts are added does not matter.
+pub struct Circuit {
+    name: String,
+    inputs: Vec<String>,
+    outputs: Vec<String>,
+    nets: Nets,
+    net_names: Vec<String>,
+    // Whether a net still has the name it was given automatically
+    automatic: Vec<bool>,
+    name_lookup: HashMap<String, usize>,
+    components: Vec<Box<dyn Component>>,
+    fanout: Vec<Vec<ComponentId>>,
+    component_inputs: Vec<Vec<NetId>>,
+    component_outputs: Vec<Vec<NetId>>,
+}
+
+impl Circuit {
+    pub fn new() -> Circuit {
+        Circuit::from_nets(Nets::new())
+    }
+
+    // Circuit over nets that were already created, e.g. for a component built outside of it.
+    // Every net gets an automatic name.
+    pub fn from_nets(nets: Nets) -> Circuit {
+        let mut circuit = Circuit {
+            name: String::from("circuit"),
+            inputs: Vec::new(),
+            outputs: Vec::new(),
+            nets,
+            net_names: Vec::new(),
+            automatic: Vec::new(),
+            name_lookup: HashMap::new(),
+            components: Vec::new(),
+            fanout: Vec::new(),
+            component_inputs: Vec::new(),
+            component_outputs: Vec::new(),
+        };
+        circuit.register_nets();
+        circuit
+    }
+
+    // Wrap a component in a circuit, naming its input and output nets as ports.
+    // Nets inside the component can also be found by their path, e.g. `ha1.sum`.
+    // The port names must be distinct.
+    pub fn from_component(name: &str, nets: Nets, component: Box<dyn Component>, inputs: &[&str], outputs: &[&str]) -> Circuit {
+        let mut circuit = Circuit::from_nets(nets).with_name(name);
+        for (port, &net) in inputs.iter().zip(component.inputs()) {
+            circuit.name_input(port, net).unwrap();
+        }
+        for (port, &net) in outputs.iter().zip(component.outputs()) {
+            circuit.name_output(port, net).unwrap();
+        }
+        for (path, net) in component.named_nets() {
+            circuit.alias_net(&path, net);
+        }
+        circuit.add_component(component);
+        circuit
+    }
+
+    pub fn with_name(mut self, name: &str) -> Circuit {
+        self.name = name.to_string();
+        self
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    // Create a named net that is driven from outside the circuit
+    pub fn add_input(&mut self, name: &str) -> Result<NetId, String> {
+        let net = self.add_net(name)?;
+        self.inputs.push(name.to_string());
+        Ok(net)
+    }
+
+    // Create a named net that is observed from outside the circuit
+    pub fn add_output(&mut self, name: &str) -> Result<NetId, String> {
+        let net = self.add_net(name)?;
+        self.outputs.push(name.to_string());
+        Ok(net)
+    }
+
+    // Make an existing net an input port
+    pub fn name_input(&mut self, name: &str, net: NetId) -> Result<(), String> {
+        self.name_net(name, net)?;
+        self.inputs.push(name.to_string());
+        Ok(())
+    }
+
+    // Make an existing net an output port
+    pub fn name_output(&mut self, name: &str, net: NetId) -> Result<(), String> {
+        self.name_net(name, net)?;
+        self.outputs.push(name.to_string());
+        Ok(())
+    }
+
+    // Names of the input ports in declaration order
+    pub fn inputs(&self) -> &[String] {
+        &self.inputs
+    }
+
+    // Names of the output ports in declaration order
+    pub fn outputs(&self) -> &[String] {
+        &self.outputs
+    }
+
+    // Create a new named net. The name must not belong to another net.
+    pub fn add_net(&mut self, name: &str) -> Result<NetId, String> {
+        self.check_name(name, None)?;
+        let net = self.new_net();
+        self.name_net(name, net)?;
+        Ok(net)
+    }
+
+    // Create a new net with an automatic name, e.g. for the inside of a component
+    pub fn new_net(&mut self) -> NetId {
+        let net = self.nets.add();
+        self.register_nets();
+        net
+    }
+
+    // Give a net a name. The name must not belong to another net.
+    pub fn name_net(&mut self, name: &str, net: NetId) -> Result<(), String> {
+        self.register_nets();
+        let index = net.index();
+        self.check_name(name, Some(index))?;
+        // A net that was only named automatically makes way
+        if let Some(other) = self.name_lookup.get(name).copied().filter(|&other| other != index) {
+            let fresh = self.fresh_name(other);
+            self.net_names[other] = fresh.clone();
+            self.name_lookup.insert(fresh, other);
+        }
+        self.name_lookup.remove(&self.net_names[index]);
+        self.net_names[index] = name.to_string();
+        self.automatic[index] = false;
+        self.name_lookup.insert(name.to_string(), index);
+        Ok(())
+    }
+
+    // Give a net another name it can be looked up by, keeping its current one.
+    // A name that is already taken is left alone.
+    pub fn alias_net(&mut self, name: &str, net: NetId) {
+        self.register_nets();
+        self.name_lookup.entry(name.to_string()).or_insert(net.index());
+    }
+
+    // Add a component under an instance name, so its nets are found as `name.path`
+    pub fn add_instance(&mut self, name: &str, component: Box<dyn Component>) {
+        for (path, net) in component.named_nets() {
+            self.alias_net(&format!("{}.{}", name, path), net);
+        }
+        self.add_component(component);
+    }
+
+    // Names of all nets below an instance path, sorted
+    pub fn nets_under(&self, path: &str) -> Vec<&str> {
+        let prefix = format!("{}.", path);
+        let mut names: Vec<&str> = self
+            .name_lookup
+            .keys()
+            .filter(|name| name.starts_with(&prefix))
+            .map(String::as_str)
+            .collect();
+        names.sort();
+        names
+    }
+
+    // Components are wired to nets of this circuit, created with new_net or through nets_mut
+    pub fn add_component(&mut self, component: Box<dyn Component>) {
+        self.register_nets();
+        for primitive in component.flatten() {
+            let id = component_id(self.components.len());
+            for &net in primitive.inputs() {
+                if !self.fanout[net.index()].contains(&id) {
+                    self.fanout[net.index()].push(id);
+                }
+            }
+            self.component_inputs.push(primitive.inputs().to_vec());
+            self.component_outputs.push(primitive.outputs().to_vec());
+            self.components.push(primitive);
+        }
+    }
+
+    // Give every component the same propagation delay
+    pub fn set_delay(&mut self, delay: u64) {
+        for component in self.components.iter_mut() {
+            component.set_delay(delay);
+        }
+    }
+
+    pub fn net(&self, name: &str) -> Option<NetId> {
+        self.name_lookup.get(name).map(|&index| net_id(index))
+    }
+
+    pub fn nets(&self) -> &Nets {
+        &self.nets
+    }
+
+    // Nets to build components in. Nets created here are named automatically once they
+    // are named, aliased or wired to a component that is added.
+    pub fn nets_mut(&mut self) -> &mut Nets {
+        &mut self.nets
+    }
+
+    pub fn level(&self, net: NetId) -> Level {
+        self.nets.level(net)
+    }
+
+    // Nets whose drivers currently disagree
+    pub fn contentions(&self) -> Vec<Contention> {
+        self.nets
+            .iter()
+            .zip(self.net_names.iter())
+            .filter(|((_, connection), _)| connection.has_contention())
+            .map(|((_, connection), name)| Contention {
+                net: name.clone(),
+                drivers: connection.drivers().iter().map(|&(_, level)| level).collect(),
+            })
+            .collect()
+    }
+
+    pub fn net_names(&self) -> &[String] {
+        &self.net_names
+    }
+
+    pub fn net_count(&self) -> usize {
+        self.net_names.len()
+    }
+
+    pub fn component_count(&self) -> usize {
+        self.components.len()
+    }
+
+
+    // Every name a net can be looked up by, aliases included
+    pub(crate) fn net_lookup(&self) -> impl Iterator<Item = (&str, NetId)> {
+        self.name_lookup.iter().map(|(name, &index)| (name.as_str(), net_id(index)))
+    }
+
+    pub(crate) fn net_at(&self, net: NetId) -> &Connection {
+        &self.nets[net]
+    }
+
+    pub(crate) fn net_at_mut(&mut self, net: NetId) -> &mut Connection {
+        &mut self.nets[net]
+    }
+
+    pub(crate) fn net_name(&self, net: NetId) -> &str {
+        &self.net_names[net.index()]
+    }
+
+    // Every net in creation order
+    pub(crate) fn net_ids(&self) -> impl Iterator<Item = NetId> {
+        (0..self.net_names.len()).map(net_id)
+    }
+
+    // Every component in the order it was added
+    pub(crate) fn component_ids(&self) -> impl Iterator<Item = ComponentId> {
+        (0..self.components.len()).map(component_id)
+    }
+
+    pub(crate) fn component(&self, component: ComponentId) -> &dyn Component {
+        self.components[component.index()].as_ref()
+    }
+
+    // Update a component against the nets of the circuit
+    pub(crate) fn update_component(&mut self, component: ComponentId) {
+        self.components[component.index()].update(&mut self.nets);
+    }
+
+    // Components reading the given net
+    pub(crate) fn fanout(&self, net: NetId) -> &[ComponentId] {
+        &self.fanout[net.index()]
+    }
+
+    // Nets read by the given component
+    pub(crate) fn component_inputs(&self, component: ComponentId) -> &[NetId] {
+        &self.component_inputs[component.index()]
+    }
+
+    // Nets driven by the given component
+    pub(crate) fn component_outputs(&self, component: ComponentId) -> &[NetId] {
+        &self.component_outputs[component.index()]
+    }
+
+    // Give every net created in the arena since the last call an automatic name
+    fn register_nets(&mut self) {
+        for index in self.net_names.len()..self.nets.len() {
+            let name = self.fresh_name(index);
+            self.name_lookup.insert(name.clone(), index);
+            self.net_names.push(name);
+            self.automatic.push(true);
+            self.fanout.push(Vec::new());
+        }
+    }
+
+    // An automatic name for the net at `index`, e.g. net3, with a suffix if a net
+    // was already given that name
+    fn fresh_name(&self, index: usize) -> String {
+        let name = format!("net{}", index);
+        if !self.name_lookup.contains_key(&name) {
+            return name;
+        }
+        (1..).map(|suffix| format!("{}_{}", name, suffix)).find(|name| !self.name_lookup.contains_key(name)).unwrap()
+    }
+
+    // Fail if the name belongs to a net other than `index` and was not given automatically
+    fn check_name(&self, name: &str, index: Option<usize>) -> Result<(), String> {
+        match self.name_lookup.get(name) {
+            Some(&other) if Some(other) != index && !(self.automatic[other] && self.net_names[other] == name) => {
+                Err(format!("Net name '{}' is already taken in {}", name, self.name))
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+impl Default for Circuit {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl fmt::Display for Circuit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (name, (_, net)) in self.net_names.iter().zip(self.nets.iter()) {
+            writeln!(f, "{}: {}", name, net.level())?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::circuits::FullAdder;
+    use crate::components::gates::{ANDGate, NOTGate, XORGate};
+
+    #[test]
+    fn test_add_named_net() {
+        let mut circuit = Circuit::new();
+        let a = circuit.add_net("a").unwrap();
+        assert_eq!(circuit.net("a"), Some(a));
+        assert!(circuit.net("b").is_none());
+    }
+
+    #[test]
+    fn test_ports() {
+        let mut circuit = Circuit::new().with_name("adder");
+        let a = circuit.add_input("a").unwrap();
+        circuit.add_output("sum").unwrap();
+        assert_eq!(circuit.name(), "adder");
+        assert_eq!(circuit.inputs(), &["a".to_string()]);
+        assert_eq!(circuit.outputs(), &["sum".to_string()]);
+        assert_eq!(circuit.net("a"), Some(a));
+    }
+
+    #[test]
+    fn test_from_component() {
+        let mut nets = Nets::new();
+        let [a, b, out] = [(); 3].map(|_| nets.add());
+        let circuit = Circuit::from_component("and", nets, Box::new(ANDGate::new(a, b, out)), &["x", "y"], &["z"]);
+        assert_eq!(circuit.inputs(), &["x".to_string(), "y".to_string()]);
+        assert_eq!(circuit.outputs(), &["z".to_string()]);
+        assert_eq!(circuit.net("z"), Some(out));
+        assert_eq!(circuit.component_inputs(component_id(0)), &[a, b]);
+    }
+
+    #[test]
+    fn test_add_component_registers_nets() {
+        let mut circuit = Circuit::new();
+        let a = circuit.add_net("a").unwrap();
+        let b = circuit.add_net("b").unwrap();
+        let out = circuit.add_net("out").unwrap();
+        circuit.add_component(Box::new(ANDGate::new(a, b, out)));
+        circuit.add_component(Box::new(XORGate::new(a, b, out)));
+
+        assert_eq!(circuit.net_count(), 3);
+        assert_eq!(circuit.component_count(), 2);
+        assert_eq!(circuit.fanout(a), &[component_id(0), component_id(1)]);
+        assert_eq!(circuit.component_outputs(component_id(1)), &[out]);
+    }
+
+    #[test]
+    fn test_add_component_flattens_composites() {
+        let mut circuit = Circuit::new();
+        let a = circuit.add_net("a").unwrap();
+        let b = circuit.add_net("b").unwrap();
+        let cin = circuit.add_net("cin").unwrap();
+        let sum = circuit.add_net("sum").unwrap();
+        let cout = circuit.add_net("cout").unwrap();
+        let full_adder = FullAdder::new(circuit.nets_mut(), a, b, cin, sum, cout);
+        circuit.add_component(Box::new(full_adder));
+
+        // Two half adders with two gates each plus the carry OR gate
+        assert_eq!(circuit.component_count(), 5);
+        // Five ports plus three internal nets
+        assert_eq!(circuit.net_count(), 8);
+    }
+
+    #[test]
+    fn test_set_delay() {
+        let mut circuit = Circuit::new();
+        let a = circuit.add_net("a").unwrap();
+        let b = circuit.add_net("b").unwrap();
+        let out = circuit.add_net("out").unwrap();
+        circuit.add_component(Box::new(ANDGate::new(a, b, out)));
+        circuit.set_delay(4);
+        assert_eq!(circuit.component(component_id(0)).delay(), 4);
+    }
+
+    #[test]
+    fn test_contentions() {
+        let mut circuit = Circuit::new();
+        let bus = circuit.add_net("bus").unwrap();
+        circuit.add_net("other").unwrap();
+        circuit.nets_mut()[bus].drive(1, Level::High);
+        circuit.nets_mut()[bus].drive(2, Level::Low);
+        assert_eq!(
+            circuit.contentions(),
+            vec![Contention { net: "bus".to_string(), drivers: vec![Level::High, Level::Low] }]
+        );
+    }
+
+    #[test]
+    fn test_name_net() {
+        let mut circuit = Circuit::new();
+        let net = circuit.new_net();
+        assert_eq!(circuit.net_names(), ["net0"]);
+        circuit.name_net("carry", net).unwrap();
+        assert_eq!(circuit.net("carry"), Some(net));
+        assert!(circuit.net("net0").is_none());
+        // Naming a net again with its own name changes nothing
+        circuit.name_net("carry", net).unwrap();
+        assert_eq!(circuit.net_names(), ["carry"]);
+    }
+
+    #[test]
+    fn test_name_taken() {
+        let mut circuit = Circuit::new().with_name("adder");
+        let carry = circuit.add_net("carry").unwrap();
+        let other = circuit.new_net();
+        assert_eq!(circuit.name_net("carry", other), Err("Net name 'carry' is already taken in adder".to_string()));
+        assert!(circuit.add_net("carry").is_err());
+        assert!(circuit.add_input("carry").is_err());
+        assert!(circuit.inputs().is_empty());
+        assert_eq!(circuit.net("carry"), Some(carry));
+    }
+
+    #[test]
+    fn test_automatic_names_make_way() {
+        let mut circuit = Circuit::new();
+        let a = circuit.add_net("a").unwrap();
+        let unnamed = circuit.nets_mut().add();
+        circuit.add_component(Box::new(NOTGate::new(a, unnamed)));
+        assert_eq!(circuit.net_names(), ["a", "net1"]);
+
+        // A declared name takes over an automatic one, which moves aside
+        let declared = circuit.add_net("net1").unwrap();
+        assert_eq!(circuit.net("net1"), Some(declared));
+        assert_eq!(circuit.net("net1_1"), Some(unnamed));
+        // and an automatic name skips declared ones: the fifth net would be net4
+        circuit.add_net("net4").unwrap();
+        let output = circuit.new_net();
+        circuit.add_component(Box::new(NOTGate::new(declared, output)));
+        assert_eq!(circuit.net_names(), ["a", "net1_1", "net1", "net4", "net4_1"]);
+    }
+
+    #[test]
+    fn test_send() {
+        fn sendable<T: Send>() {}
+        sendable::<Circuit>();
+    }
+}
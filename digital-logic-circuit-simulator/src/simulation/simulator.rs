@@ -0,0 +1,578 @@
+use core::fmt;
+use std::collections::{BTreeMap, HashMap};
+use std::io;
+use std::mem;
+
+use crate::components::{ComponentId, Connection, Level, NetId};
+use crate::simulation::{Circuit, Contention, Trace};
+
+// Upper bound on evaluation rounds before a circuit is considered oscillating
+pub const DEFAULT_MAX_ITERATIONS: usize = 10_000;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum SimulationError {
+    UnknownNet(String),
+    Oscillation {
+        iterations: usize,
+        nets: Vec<String>,
+    },
+    Contention(Vec<Contention>),
+    // Enumerating every input combination would take too many rows
+    TooManyInputs {
+        inputs: usize,
+        limit: usize,
+    },
+}
+
+impl fmt::Display for SimulationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SimulationError::UnknownNet(name) => write!(f, "Unknown net '{}'", name),
+            SimulationError::Oscillation { iterations, nets } => write!(
+                f,
+                "Circuit did not settle after {} iterations, still changing: {}",
+                iterations,
+                nets.join(", ")
+            ),
+            SimulationError::Contention(contentions) => {
+                write!(f, "Bus contention:")?;
+                for contention in contentions {
+                    write!(f, " {};", contention)?;
+                }
+                Ok(())
+            }
+            SimulationError::TooManyInputs { inputs, limit } => {
+                write!(f, "{} inputs are too many to enumerate, the limit is {}", inputs, limit)
+            }
+        }
+    }
+}
+
+// Event-driven evaluation of a circuit in simulated time.
+// Level changes are queued in time order. Whenever a net changes, only the components
+// it feeds are re-evaluated and their new output levels are scheduled after the
+// component's propagation delay. Zero-delay components resolve within the same tick.
+// Events carry what a single driver puts onto a net, so nets with several drivers
+// are resolved by the connection itself.
+pub struct Simulator {
+    circuit: Circuit,
+    time: u64,
+    // Per tick: net, driver (None when forced from outside) and level
+    events: BTreeMap<u64, Vec<(NetId, Option<usize>, Level)>>,
+    pending: Vec<ComponentId>,
+    scheduled: Vec<bool>,
+    // Level each driver will put onto a net once all queued events are applied,
+    // together with the component the driver belongs to
+    projected: HashMap<(NetId, usize), (Level, ComponentId)>,
+    last_change: Vec<u64>,
+    evaluations: usize,
+    max_iterations: usize,
+    trace: Trace,
+}
+
+impl Simulator {
+    pub fn new(circuit: Circuit) -> Simulator {
+        // Every component has to be evaluated once to drive its outputs
+        let component_count = circuit.component_count();
+        Simulator {
+            time: 0,
+            events: BTreeMap::new(),
+            pending: circuit.component_ids().collect(),
+            scheduled: vec![true; component_count],
+            projected: HashMap::new(),
+            last_change: vec![0; circuit.net_count()],
+            evaluations: 0,
+            max_iterations: DEFAULT_MAX_ITERATIONS,
+            trace: Trace::new(),
+            circuit,
+        }
+    }
+
+    pub fn with_max_iterations(mut self, max_iterations: usize) -> Simulator {
+        self.max_iterations = max_iterations;
+        self
+    }
+
+    pub fn circuit(&self) -> &Circuit {
+        &self.circuit
+    }
+
+    // Current simulated time in ticks
+    pub fn time(&self) -> u64 {
+        self.time
+    }
+
+    // Total number of component evaluations so far
+    pub fn evaluations(&self) -> usize {
+        self.evaluations
+    }
+
+    // Drive a net to the given level at the current time
+    pub fn set_level(&mut self, name: &str, level: Level) -> Result<(), SimulationError> {
+        self.schedule_level(name, level, 0)
+    }
+
+    // Drive a net to the given level after `delay` ticks
+    pub fn schedule_level(&mut self, name: &str, level: Level, delay: u64) -> Result<(), SimulationError> {
+        let net = self.lookup(name)?;
+        self.events.entry(self.time + delay).or_default().push((net, None, level));
+        Ok(())
+    }
+
+    pub fn level(&self, name: &str) -> Result<Level, SimulationError> {
+        let net = self.lookup(name)?;
+        Ok(self.circuit.net_at(net).level())
+    }
+
+    // Time at which the net last changed its level
+    pub fn last_change(&self, name: &str) -> Result<u64, SimulationError> {
+        let net = self.lookup(name)?;
+        Ok(self.last_change[net.index()])
+    }
+
+    // Record every level change of the net from now on
+    pub fn trace(&mut self, name: &str) -> Result<(), SimulationError> {
+        let net = self.lookup(name)?;
+        let level = self.circuit.net_at(net).level();
+        self.trace.add(name, net, self.time, level);
+        Ok(())
+    }
+
+    // Record the level changes of every named net
+    pub fn trace_all(&mut self) {
+        for net in self.circuit.net_ids() {
+            let level = self.circuit.net_at(net).level();
+            self.trace.add(self.circuit.net_name(net), net, self.time, level);
+        }
+    }
+
+    pub fn traced(&self) -> &Trace {
+        &self.trace
+    }
+
+    // Write the recorded level changes as a Value Change Dump, e.g. for GTKWave
+    pub fn write_vcd(&self, writer: &mut impl io::Write) -> io::Result<()> {
+        self.trace.write_vcd(self.circuit.name(), writer)
+    }
+
+    // Process events until the circuit is stable.
+    // Returns the simulated time at which that happened.
+    // Fails if the stable circuit still has opposing drivers on an exclusive net.
+    pub fn settle(&mut self) -> Result<u64, SimulationError> {
+        self.run(None)?;
+        let contentions = self.contentions();
+        if !contentions.is_empty() {
+            return Err(SimulationError::Contention(contentions));
+        }
+        Ok(self.time)
+    }
+
+    pub fn contentions(&self) -> Vec<Contention> {
+        self.circuit.contentions()
+    }
+
+    // Process all events up to and including `time`, then advance the clock to it
+    pub fn run_until(&mut self, time: u64) -> Result<(), SimulationError> {
+        self.run(Some(time))?;
+        self.time = self.time.max(time);
+        Ok(())
+    }
+
+    // Without a time limit every evaluation round counts towards the iteration limit,
+    // so circuits that keep changing (like clocks) are reported as oscillating.
+    // With a time limit only rounds within the same tick are counted.
+    fn run(&mut self, limit: Option<u64>) -> Result<(), SimulationError> {
+        let mut iterations = 0;
+        let mut round_time = self.time;
+
+        loop {
+            // Events of the current tick are applied before anything is evaluated,
+            // later ones only once the current tick is stable
+            let next = self.events.keys().next().copied();
+            if let Some(time) = next {
+                let due = time <= self.time
+                    || (self.pending.is_empty() && limit.is_none_or(|limit| time <= limit));
+                if due {
+                    self.time = time;
+                    for (net, driver, level) in self.events.remove(&time).unwrap_or_default() {
+                        self.apply(net, driver, level);
+                    }
+                    continue;
+                }
+            }
+            if self.pending.is_empty() {
+                return Ok(());
+            }
+
+            if limit.is_some() && round_time != self.time {
+                round_time = self.time;
+                iterations = 0;
+            }
+            if iterations == self.max_iterations {
+                return Err(self.oscillation(iterations));
+            }
+            iterations += 1;
+
+            for component in mem::take(&mut self.pending) {
+                self.scheduled[component.index()] = false;
+                self.evaluate(component);
+            }
+        }
+    }
+
+    fn apply(&mut self, net: NetId, driver: Option<usize>, level: Level) {
+        let changed = {
+            let connection = self.circuit.net_at_mut(net);
+            let before = connection.level();
+            match driver {
+                Some(driver) => connection.drive(driver, level),
+                None => {
+                    connection.set_level(level);
+                    self.projected.retain(|&(projected_net, _), _| projected_net != net);
+                }
+            }
+            connection.level() != before
+        };
+        if changed {
+            self.last_change[net.index()] = self.time;
+            self.trace.record(self.time, net, self.circuit.net_at(net).level());
+            for &component in self.circuit.fanout(net) {
+                if !self.scheduled[component.index()] {
+                    self.scheduled[component.index()] = true;
+                    self.pending.push(component);
+                }
+            }
+        }
+    }
+
+    // Update a single component and schedule what it now drives after its delay.
+    // The outputs are restored right away so the change only becomes visible once
+    // the scheduled event is applied.
+    fn evaluate(&mut self, component: ComponentId) {
+        self.evaluations += 1;
+        let outputs = self.circuit.component_outputs(component).to_vec();
+        let before: Vec<Connection> = outputs
+            .iter()
+            .map(|&net| self.circuit.net_at(net).clone())
+            .collect();
+
+        self.circuit.update_component(component);
+
+        let at = self.time + self.circuit.component(component).delay();
+        for (&net, before) in outputs.iter().zip(before) {
+            let after = mem::replace(self.circuit.net_at_mut(net), before.clone());
+            for &(driver, level) in after.drivers() {
+                // Entries of other drivers are untouched, unless they belong to this component
+                let projected = match self.projected.get(&(net, driver)) {
+                    Some(&(projected, owner)) if owner == component => Some(projected),
+                    _ => before.driver_level(driver),
+                };
+                if projected != Some(level) {
+                    self.projected.insert((net, driver), (level, component));
+                    self.events.entry(at).or_default().push((net, Some(driver), level));
+                }
+            }
+        }
+    }
+
+    fn lookup(&self, name: &str) -> Result<NetId, SimulationError> {
+        self.circuit
+            .net(name)
+            .ok_or_else(|| SimulationError::UnknownNet(name.to_string()))
+    }
+
+    fn oscillation(&mut self, iterations: usize) -> SimulationError {
+        let mut nets: Vec<String> = Vec::new();
+        for &component in &self.pending {
+            for &net in self.circuit.component_outputs(component) {
+                let name = self.circuit.net_name(net).to_string();
+                if !nets.contains(&name) {
+                    nets.push(name);
+                }
+            }
+        }
+        SimulationError::Oscillation { iterations, nets }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::circuits::FullAdder;
+    use crate::components::Resolution;
+    use crate::components::gates::{ANDGate, NOTGate, ORGate, TriStateBuffer, XORGate};
+
+    fn full_adder_circuit() -> Circuit {
+        let mut circuit = Circuit::new();
+        let a = circuit.add_net("a").unwrap();
+        let b = circuit.add_net("b").unwrap();
+        let cin = circuit.add_net("cin").unwrap();
+        let sum = circuit.add_net("sum").unwrap();
+        let cout = circuit.add_net("cout").unwrap();
+        let full_adder = FullAdder::new(circuit.nets_mut(), a, b, cin, sum, cout);
+        circuit.add_component(Box::new(full_adder));
+        circuit
+    }
+
+    #[test]
+    fn test_settle_full_adder() {
+        let mut simulator = Simulator::new(full_adder_circuit());
+        simulator.settle().unwrap();
+        assert_eq!(simulator.level("sum").unwrap(), Level::Undefined);
+
+        for (a, b, cin, expected_sum, expected_cout) in [
+            (Level::Low, Level::Low, Level::Low, Level::Low, Level::Low),
+            (Level::Low, Level::High, Level::High, Level::Low, Level::High),
+            (Level::High, Level::Low, Level::Low, Level::High, Level::Low),
+            (Level::High, Level::High, Level::High, Level::High, Level::High),
+        ] {
+            simulator.set_level("a", a).unwrap();
+            simulator.set_level("b", b).unwrap();
+            simulator.set_level("cin", cin).unwrap();
+            simulator.settle().unwrap();
+            assert_eq!(simulator.level("sum").unwrap(), expected_sum);
+            assert_eq!(simulator.level("cout").unwrap(), expected_cout);
+        }
+    }
+
+    #[test]
+    fn test_construction_order_does_not_matter() {
+        // (a AND b) OR c, with the OR gate added before the AND gate feeding it
+        let mut circuit = Circuit::new();
+        let a = circuit.add_net("a").unwrap();
+        let b = circuit.add_net("b").unwrap();
+        let c = circuit.add_net("c").unwrap();
+        let ab = circuit.add_net("ab").unwrap();
+        let out = circuit.add_net("out").unwrap();
+        circuit.add_component(Box::new(ORGate::new(ab, c, out)));
+        circuit.add_component(Box::new(ANDGate::new(a, b, ab)));
+
+        let mut simulator = Simulator::new(circuit);
+        simulator.set_level("a", Level::High).unwrap();
+        simulator.set_level("b", Level::High).unwrap();
+        simulator.set_level("c", Level::Low).unwrap();
+        simulator.settle().unwrap();
+        assert_eq!(simulator.level("out").unwrap(), Level::High);
+    }
+
+    #[test]
+    fn test_only_affected_components_are_evaluated() {
+        let mut circuit = Circuit::new();
+        let a = circuit.add_net("a").unwrap();
+        let b = circuit.add_net("b").unwrap();
+        let c = circuit.add_net("c").unwrap();
+        let d = circuit.add_net("d").unwrap();
+        let x = circuit.add_net("x").unwrap();
+        let y = circuit.add_net("y").unwrap();
+        circuit.add_component(Box::new(ANDGate::new(a, b, x)));
+        circuit.add_component(Box::new(ANDGate::new(c, d, y)));
+
+        let mut simulator = Simulator::new(circuit);
+        simulator.settle().unwrap();
+        assert_eq!(simulator.evaluations(), 2);
+        simulator.set_level("a", Level::High).unwrap();
+        simulator.settle().unwrap();
+        assert_eq!(simulator.evaluations(), 3);
+        // Setting a net to the level it already has changes nothing
+        simulator.set_level("a", Level::High).unwrap();
+        simulator.settle().unwrap();
+        assert_eq!(simulator.evaluations(), 3);
+    }
+
+    #[test]
+    fn test_oscillation_is_reported() {
+        // q = a XOR q toggles forever once a is High
+        let mut circuit = Circuit::new();
+        let a = circuit.add_net("a").unwrap();
+        let q = circuit.add_net("q").unwrap();
+        circuit.add_component(Box::new(XORGate::new(a, q, q)));
+
+        let mut simulator = Simulator::new(circuit).with_max_iterations(50);
+        simulator.set_level("q", Level::Low).unwrap();
+        simulator.set_level("a", Level::High).unwrap();
+        match simulator.settle() {
+            Err(SimulationError::Oscillation { iterations, nets }) => {
+                assert_eq!(iterations, 50);
+                assert_eq!(nets, vec!["q".to_string()]);
+            }
+            other => panic!("expected oscillation, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_gate_delay() {
+        let mut circuit = Circuit::new();
+        let a = circuit.add_net("a").unwrap();
+        let b = circuit.add_net("b").unwrap();
+        let ab = circuit.add_net("ab").unwrap();
+        let c = circuit.add_net("c").unwrap();
+        let out = circuit.add_net("out").unwrap();
+        circuit.add_component(Box::new(ANDGate::new(a, b, ab).with_delay(2)));
+        circuit.add_component(Box::new(ORGate::new(ab, c, out).with_delay(3)));
+
+        let mut simulator = Simulator::new(circuit);
+        simulator.set_level("a", Level::High).unwrap();
+        simulator.set_level("b", Level::High).unwrap();
+        simulator.set_level("c", Level::Low).unwrap();
+
+        simulator.run_until(4).unwrap();
+        assert_eq!(simulator.level("ab").unwrap(), Level::High);
+        assert_eq!(simulator.level("out").unwrap(), Level::Undefined);
+
+        assert_eq!(simulator.settle().unwrap(), 5);
+        assert_eq!(simulator.level("out").unwrap(), Level::High);
+        assert_eq!(simulator.last_change("ab").unwrap(), 2);
+        assert_eq!(simulator.last_change("out").unwrap(), 5);
+    }
+
+    #[test]
+    fn test_trace_records_level_changes() {
+        let mut circuit = Circuit::new().with_name("delay");
+        let a = circuit.add_net("a").unwrap();
+        let b = circuit.add_net("b").unwrap();
+        let out = circuit.add_net("out").unwrap();
+        circuit.add_component(Box::new(ANDGate::new(a, b, out).with_delay(2)));
+
+        let mut simulator = Simulator::new(circuit);
+        simulator.trace("out").unwrap();
+        simulator.trace("a").unwrap();
+        assert!(simulator.trace("missing").is_err());
+
+        simulator.set_level("a", Level::High).unwrap();
+        simulator.set_level("b", Level::High).unwrap();
+        simulator.settle().unwrap();
+        simulator.set_level("b", Level::Low).unwrap();
+        simulator.settle().unwrap();
+
+        assert_eq!(simulator.traced().signals(), vec!["out", "a"]);
+        assert_eq!(
+            simulator.traced().changes("out"),
+            vec![(0, Level::Undefined), (2, Level::High), (4, Level::Low)]
+        );
+        assert_eq!(simulator.traced().changes("a"), vec![(0, Level::Undefined), (0, Level::High)]);
+
+        let mut vcd = Vec::new();
+        simulator.write_vcd(&mut vcd).unwrap();
+        let vcd = String::from_utf8(vcd).unwrap();
+        assert!(vcd.contains("$scope module delay $end"));
+        assert!(vcd.ends_with("#2\n1!\n#4\n0!\n"));
+    }
+
+    #[test]
+    fn test_glitch_is_visible() {
+        // out = a XOR (a delayed): a change on a produces a short pulse on out
+        let mut circuit = Circuit::new();
+        let a = circuit.add_net("a").unwrap();
+        let low = circuit.add_net("low").unwrap();
+        let delayed = circuit.add_net("delayed").unwrap();
+        let out = circuit.add_net("out").unwrap();
+        circuit.add_component(Box::new(ORGate::new(a, low, delayed).with_delay(3)));
+        circuit.add_component(Box::new(XORGate::new(a, delayed, out).with_delay(1)));
+
+        let mut simulator = Simulator::new(circuit);
+        simulator.set_level("low", Level::Low).unwrap();
+        simulator.set_level("a", Level::Low).unwrap();
+        simulator.settle().unwrap();
+        assert_eq!(simulator.level("out").unwrap(), Level::Low);
+
+        let start = simulator.time();
+        simulator.set_level("a", Level::High).unwrap();
+        simulator.run_until(start + 1).unwrap();
+        assert_eq!(simulator.level("out").unwrap(), Level::High);
+        assert_eq!(simulator.settle().unwrap(), start + 4);
+        assert_eq!(simulator.level("out").unwrap(), Level::Low);
+    }
+
+    fn bus_circuit() -> Circuit {
+        let mut circuit = Circuit::new();
+        let a = circuit.add_net("a").unwrap();
+        let b = circuit.add_net("b").unwrap();
+        let enable_a = circuit.add_net("enable_a").unwrap();
+        let enable_b = circuit.add_net("enable_b").unwrap();
+        let bus = circuit.add_net("bus").unwrap();
+        let out = circuit.add_net("out").unwrap();
+        circuit.add_component(Box::new(TriStateBuffer::new(a, enable_a, bus)));
+        circuit.add_component(Box::new(TriStateBuffer::new(b, enable_b, bus)));
+        circuit.add_component(Box::new(NOTGate::new(bus, out)));
+        circuit
+    }
+
+    #[test]
+    fn test_tri_state_bus() {
+        let mut simulator = Simulator::new(bus_circuit());
+        simulator.set_level("a", Level::High).unwrap();
+        simulator.set_level("b", Level::Low).unwrap();
+        simulator.set_level("enable_a", Level::Low).unwrap();
+        simulator.set_level("enable_b", Level::Low).unwrap();
+        simulator.settle().unwrap();
+        assert_eq!(simulator.level("bus").unwrap(), Level::HighZ);
+        assert_eq!(simulator.level("out").unwrap(), Level::Undefined);
+
+        simulator.set_level("enable_a", Level::High).unwrap();
+        simulator.settle().unwrap();
+        assert_eq!(simulator.level("bus").unwrap(), Level::High);
+        assert_eq!(simulator.level("out").unwrap(), Level::Low);
+
+        simulator.set_level("enable_a", Level::Low).unwrap();
+        simulator.set_level("enable_b", Level::High).unwrap();
+        simulator.settle().unwrap();
+        assert_eq!(simulator.level("bus").unwrap(), Level::Low);
+        assert_eq!(simulator.level("out").unwrap(), Level::High);
+    }
+
+    #[test]
+    fn test_bus_contention_is_reported() {
+        let mut simulator = Simulator::new(bus_circuit());
+        simulator.set_level("a", Level::High).unwrap();
+        simulator.set_level("b", Level::Low).unwrap();
+        simulator.set_level("enable_a", Level::High).unwrap();
+        simulator.set_level("enable_b", Level::High).unwrap();
+        match simulator.settle() {
+            Err(SimulationError::Contention(contentions)) => {
+                assert_eq!(contentions.len(), 1);
+                assert_eq!(contentions[0].net, "bus");
+                assert!(contentions[0].drivers.contains(&Level::High));
+                assert!(contentions[0].drivers.contains(&Level::Low));
+            }
+            other => panic!("expected contention, got {:?}", other),
+        }
+        assert_eq!(simulator.level("bus").unwrap(), Level::Undefined);
+
+        // Releasing one driver resolves it
+        simulator.set_level("enable_b", Level::Low).unwrap();
+        simulator.settle().unwrap();
+        assert!(simulator.contentions().is_empty());
+        assert_eq!(simulator.level("bus").unwrap(), Level::High);
+    }
+
+    #[test]
+    fn test_wired_and_bus() {
+        let mut circuit = Circuit::new();
+        let a = circuit.add_net("a").unwrap();
+        let b = circuit.add_net("b").unwrap();
+        let bus = circuit.add_net("bus").unwrap();
+        circuit.nets_mut()[bus].set_resolution(Resolution::WiredAnd);
+        circuit.add_component(Box::new(NOTGate::new(a, bus)));
+        circuit.add_component(Box::new(NOTGate::new(b, bus)));
+
+        let mut simulator = Simulator::new(circuit);
+        simulator.set_level("a", Level::Low).unwrap();
+        simulator.set_level("b", Level::Low).unwrap();
+        simulator.settle().unwrap();
+        assert_eq!(simulator.level("bus").unwrap(), Level::High);
+
+        simulator.set_level("b", Level::High).unwrap();
+        simulator.settle().unwrap();
+        assert_eq!(simulator.level("bus").unwrap(), Level::Low);
+        assert!(simulator.contentions().is_empty());
+    }
+
+    #[test]
+    fn test_unknown_net() {
+        let mut simulator = Simulator::new(Circuit::new());
+        assert_eq!(
+            simulator.set_level("missing", Level::High),
+            Err(SimulationError::UnknownNet("missing".to_string()))
+        );
+    }
+}
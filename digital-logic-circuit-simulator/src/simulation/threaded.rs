@@ -0,0 +1,383 @@
+use std::collections::HashMap;
+use std::ops::Range;
+use std::sync::atomic::{AtomicU8, Ordering};
+use std::sync::{Arc, Barrier, Condvar, Mutex};
+use std::thread::{self, JoinHandle};
+
+use crate::analysis::gate_network::GateNetwork;
+use crate::components::gates::GateKind;
+use crate::components::Level;
+use crate::components::nets::net_id;
+use crate::components::NetId;
+use crate::simulation::Circuit;
+
+// A level is shared between threads only if every thread gets at least this many gates,
+// otherwise waiting for the others costs more than the gates themselves
+pub const MIN_GATES_PER_THREAD: usize = 256;
+
+struct Gate {
+    kind: GateKind,
+    // Range of the gate's inputs in `pins`
+    start: u32,
+    end: u32,
+    output: u32,
+}
+
+// A level wide enough to share, after the narrow levels before it
+struct Step {
+    // Gates the calling thread evaluates alone first
+    serial: Range<usize>,
+    // Gates of the wide level, split between all threads
+    parallel: Range<usize>,
+}
+
+// Everything the workers read, plus what they wait on
+struct Shared {
+    levels: Vec<AtomicU8>,
+    gates: Vec<Gate>,
+    pins: Vec<u32>,
+    steps: Vec<Step>,
+    threads: usize,
+    barrier: Barrier,
+    // Number of evaluations started, and whether the workers should stop
+    start: Mutex<(u64, bool)>,
+    started: Condvar,
+}
+
+// A levelised gate circuit evaluated by a pool of threads. Gates are sorted by their
+// depth, the longest path from an input or constant, so the gates of one level only read
+// nets of lower levels. The workers are started once in `new` and wait until `evaluate`
+// wakes them. Wide levels are split between the calling thread and the workers, which
+// meet at a barrier before the next level; runs of narrow levels are evaluated by the
+// calling thread alone. Every net has a single driver and levels run strictly in order,
+// so the result does not depend on the number of threads and equals what the other
+// engines settle to. Levels are stored in atomics, which makes the engine Send + Sync.
+//
+// Like GateNetwork this needs logic gates without feedback; nets nobody drives keep the
+// 0 or 1 they had in the circuit. Only wide, shallow circuits like multipliers have
+// levels worth sharing; a ripple carry adder runs on the calling thread only.
+pub struct ThreadedCircuit {
+    name: String,
+    name_lookup: HashMap<String, NetId>,
+    inputs: Vec<NetId>,
+    outputs: Vec<NetId>,
+    depth: usize,
+    shared: Arc<Shared>,
+    // Gates after the last wide level, evaluated by the calling thread
+    tail: Range<usize>,
+    workers: Vec<JoinHandle<()>>,
+}
+
+impl ThreadedCircuit {
+    pub fn new(circuit: &Circuit, threads: usize) -> Result<ThreadedCircuit, String> {
+        ThreadedCircuit::with_min_gates_per_thread(circuit, threads, MIN_GATES_PER_THREAD)
+    }
+
+    // Share levels that give every thread at least `min_gates` gates
+    pub fn with_min_gates_per_thread(circuit: &Circuit, threads: usize, min_gates: usize) -> Result<ThreadedCircuit, String> {
+        assert!(threads > 0, "At least one thread is needed");
+        let network = GateNetwork::new(circuit)?;
+
+        // The network is in topological order, so depths can be worked out in one pass
+        let mut net_depth = vec![0; circuit.net_count()];
+        let mut by_depth: Vec<Vec<usize>> = Vec::new();
+        for (index, gate) in network.gates.iter().enumerate() {
+            let depth = gate.inputs.iter().map(|&net| net_depth[net]).max().unwrap_or(0);
+            net_depth[gate.output] = depth + 1;
+            if by_depth.len() == depth {
+                by_depth.push(Vec::new());
+            }
+            by_depth[depth].push(index);
+        }
+
+        let mut gates = Vec::with_capacity(network.gates.len());
+        let mut pins = Vec::new();
+        let mut steps = Vec::new();
+        let mut serial_start = 0;
+        let depth = by_depth.len();
+        for level in by_depth {
+            let level_start = gates.len();
+            for index in level {
+                let gate = &network.gates[index];
+                let start = pins.len() as u32;
+                pins.extend(gate.inputs.iter().map(|&net| net as u32));
+                gates.push(Gate { kind: gate.kind, start, end: pins.len() as u32, output: gate.output as u32 });
+            }
+            if threads > 1 && gates.len() - level_start >= threads * min_gates.max(1) {
+                steps.push(Step { serial: serial_start..level_start, parallel: level_start..gates.len() });
+                serial_start = gates.len();
+            }
+        }
+        let tail = serial_start..gates.len();
+
+        let levels = circuit
+            .net_ids()
+            .map(|net| AtomicU8::new(encode(circuit.net_at(net).level())))
+            .collect();
+        // Without a wide level there is nothing to share, so no workers are started
+        let threads = if steps.is_empty() { 1 } else { threads };
+        let shared = Arc::new(Shared {
+            levels,
+            gates,
+            pins,
+            steps,
+            threads,
+            barrier: Barrier::new(threads),
+            start: Mutex::new((0, false)),
+            started: Condvar::new(),
+        });
+        let workers = (1..threads)
+            .map(|thread| {
+                let shared = Arc::clone(&shared);
+                thread::spawn(move || shared.work(thread))
+            })
+            .collect();
+
+        Ok(ThreadedCircuit {
+            name: circuit.name().to_string(),
+            name_lookup: circuit.net_lookup().map(|(name, net)| (name.to_string(), net)).collect(),
+            inputs: network.inputs.iter().map(|&net| net_id(net)).collect(),
+            outputs: network.outputs.iter().map(|&net| net_id(net)).collect(),
+            depth,
+            shared,
+            tail,
+            workers,
+        })
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn net(&self, name: &str) -> Option<NetId> {
+        self.name_lookup.get(name).copied()
+    }
+
+    // Nets of the input ports in declaration order
+    pub fn inputs(&self) -> &[NetId] {
+        &self.inputs
+    }
+
+    // Nets of the output ports in declaration order
+    pub fn outputs(&self) -> &[NetId] {
+        &self.outputs
+    }
+
+    // Threads evaluating the circuit, the calling one included. This is 1 when no level is
+    // wide enough to share.
+    pub fn threads(&self) -> usize {
+        self.shared.threads
+    }
+
+    // Number of levels split between the threads
+    pub fn parallel_levels(&self) -> usize {
+        self.shared.steps.len()
+    }
+
+    // Number of gates in the levels split between the threads
+    pub fn parallel_gates(&self) -> usize {
+        self.shared.steps.iter().map(|step| step.parallel.len()).sum()
+    }
+
+    pub fn component_count(&self) -> usize {
+        self.shared.gates.len()
+    }
+
+    // Number of levels, i.e. gates on the longest path
+    pub fn depth(&self) -> usize {
+        self.depth
+    }
+
+    pub fn level(&self, net: NetId) -> Level {
+        decode(self.shared.levels[net.index()].load(Ordering::Relaxed))
+    }
+
+    // Force a net to a level; gates reading it see it on the next evaluate
+    pub fn set_level(&mut self, net: NetId, level: Level) {
+        self.shared.levels[net.index()].store(encode(level), Ordering::Relaxed);
+    }
+
+    // Evaluate every gate once, level by level
+    pub fn evaluate(&mut self) {
+        let shared = &*self.shared;
+        let mut inputs = Vec::new();
+        if let Some(first) = shared.steps.first() {
+            // The workers wait for the gates before the first wide level anyway, so those
+            // are evaluated before waking them
+            shared.evaluate_gates(first.serial.clone(), &mut inputs);
+            shared.start.lock().unwrap().0 += 1;
+            shared.started.notify_all();
+            shared.run_steps(0, &mut inputs);
+        }
+        shared.evaluate_gates(self.tail.clone(), &mut inputs);
+    }
+}
+
+impl Drop for ThreadedCircuit {
+    fn drop(&mut self) {
+        self.shared.start.lock().unwrap().1 = true;
+        self.shared.started.notify_all();
+        for worker in self.workers.drain(..) {
+            worker.join().unwrap();
+        }
+    }
+}
+
+impl Shared {
+    // Body of a worker thread: take part in every evaluation until told to stop
+    fn work(&self, thread: usize) {
+        let mut seen = 0;
+        let mut inputs = Vec::new();
+        loop {
+            {
+                let mut start = self.start.lock().unwrap();
+                while start.0 == seen && !start.1 {
+                    start = self.started.wait(start).unwrap();
+                }
+                if start.1 {
+                    return;
+                }
+                seen = start.0;
+            }
+            self.run_steps(thread, &mut inputs);
+        }
+    }
+
+    // The wide levels as seen by one thread, 0 being the calling one. Narrow levels between
+    // them are evaluated by the calling thread while the others wait at the barrier.
+    fn run_steps(&self, thread: usize, inputs: &mut Vec<Level>) {
+        for (index, step) in self.steps.iter().enumerate() {
+            if index > 0 && !step.serial.is_empty() {
+                if thread == 0 {
+                    self.evaluate_gates(step.serial.clone(), inputs);
+                }
+                self.barrier.wait();
+            }
+            // Thread t takes the t-th of `threads` nearly equal slices
+            let width = step.parallel.len();
+            let start = step.parallel.start + width * thread / self.threads;
+            let end = step.parallel.start + width * (thread + 1) / self.threads;
+            self.evaluate_gates(start..end, inputs);
+            self.barrier.wait();
+        }
+    }
+
+    fn evaluate_gates(&self, gates: Range<usize>, inputs: &mut Vec<Level>) {
+        for gate in &self.gates[gates] {
+            inputs.clear();
+            let pins = &self.pins[gate.start as usize..gate.end as usize];
+            inputs.extend(pins.iter().map(|&net| decode(self.levels[net as usize].load(Ordering::Relaxed))));
+            self.levels[gate.output as usize].store(encode(gate.kind.evaluate(inputs)), Ordering::Relaxed);
+        }
+    }
+}
+
+fn encode(level: Level) -> u8 {
+    match level {
+        Level::Undefined => 0,
+        Level::High => 1,
+        Level::Low => 2,
+        Level::HighZ => 3,
+    }
+}
+
+fn decode(level: u8) -> Level {
+    match level {
+        1 => Level::High,
+        2 => Level::Low,
+        3 => Level::HighZ,
+        _ => Level::Undefined,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::digital_logic::arithmetic::{RippleCarryAdder, WallaceMultiplier};
+    use crate::analysis::RandomVectors;
+    use crate::netlist::load_builtin;
+    use crate::simulation::ArenaCircuit;
+
+    #[test]
+    fn test_levels() {
+        let circuit = ThreadedCircuit::new(&load_builtin("full_adder").unwrap(), 2).unwrap();
+        assert_eq!(circuit.component_count(), 5);
+        assert_eq!(circuit.depth(), 3);
+        // Levels of one or two gates are not worth sharing
+        assert_eq!(circuit.parallel_levels(), 0);
+        assert_eq!(circuit.threads(), 1);
+
+        let circuit = ThreadedCircuit::with_min_gates_per_thread(&load_builtin("full_adder").unwrap(), 2, 1).unwrap();
+        assert_eq!(circuit.parallel_levels(), 2);
+        assert_eq!(circuit.threads(), 2);
+    }
+
+    #[test]
+    fn test_wide_levels_only() {
+        // The carry chain of a ripple carry adder is never wide enough
+        let circuit = ThreadedCircuit::new(&RippleCarryAdder::new(256).into_circuit(), 4).unwrap();
+        assert_eq!(circuit.parallel_levels(), 0);
+        assert_eq!(circuit.threads(), 1);
+        // The partial products and the first reduction layers of a multiplier are
+        let circuit = ThreadedCircuit::new(&WallaceMultiplier::new(32, 32).into_circuit(), 2).unwrap();
+        assert!(circuit.parallel_levels() > 0);
+        assert!(circuit.parallel_levels() < circuit.depth());
+        assert!(circuit.parallel_gates() > circuit.component_count() / 3);
+        assert_eq!(circuit.threads(), 2);
+    }
+
+    #[test]
+    fn test_same_result_for_any_thread_count() {
+        let circuit = WallaceMultiplier::new(8, 8).into_circuit();
+        let mut arena = ArenaCircuit::new(&circuit).unwrap();
+        // Every level shared, only some, or none
+        let mut engines: Vec<ThreadedCircuit> = [(1, 1), (2, 1), (3, 4), (8, 1), (4, MIN_GATES_PER_THREAD)]
+            .iter()
+            .map(|&(threads, min_gates)| ThreadedCircuit::with_min_gates_per_thread(&circuit, threads, min_gates).unwrap())
+            .collect();
+        assert!(engines[2].parallel_levels() > 0 && engines[2].parallel_levels() < engines[1].parallel_levels());
+
+        let mut random = RandomVectors::new();
+        for vector in 0..20 {
+            let levels = random.vector(arena.inputs().len());
+            for (i, &net) in arena.inputs().to_vec().iter().enumerate() {
+                // Some inputs are left undefined in a few vectors
+                let level = if vector % 5 == 0 && i % 7 == 0 { Level::Undefined } else { levels[i] };
+                arena.set_level(net, level);
+                for engine in engines.iter_mut() {
+                    let net = engine.inputs()[i];
+                    engine.set_level(net, level);
+                }
+            }
+            arena.settle().unwrap();
+            for engine in engines.iter_mut() {
+                engine.evaluate();
+                let levels: Vec<Level> = engine.outputs().iter().map(|&net| engine.level(net)).collect();
+                let expected: Vec<Level> = arena.outputs().iter().map(|&net| arena.level(net)).collect();
+                assert_eq!(levels, expected, "{} threads, vector {}", engine.threads(), vector);
+            }
+        }
+    }
+
+    #[test]
+    fn test_shared_between_threads() {
+        fn shareable<T: Send + Sync>() {}
+        shareable::<ThreadedCircuit>();
+
+        let mut circuit = ThreadedCircuit::with_min_gates_per_thread(&load_builtin("and").unwrap(), 2, 1).unwrap();
+        for net in circuit.inputs().to_vec() {
+            circuit.set_level(net, Level::High);
+        }
+        circuit.evaluate();
+        let output = circuit.outputs()[0];
+        let circuit = &circuit;
+        thread::scope(|scope| {
+            assert_eq!(scope.spawn(|| circuit.level(output)).join().unwrap(), Level::High);
+        });
+    }
+
+    #[test]
+    fn test_rejects_feedback() {
+        assert!(ThreadedCircuit::new(&load_builtin("srlatch").unwrap(), 2).is_err());
+    }
+}
@@ -0,0 +1,187 @@
+use std::collections::HashMap;
+use std::io;
+
+use crate::components::{Level, NetId};
+
+// Level changes of selected nets over simulated time, written as a Value Change Dump
+pub struct Trace {
+    // Name and net of every traced signal
+    signals: Vec<(String, NetId)>,
+    lookup: HashMap<NetId, usize>,
+    // Time, signal and new level, in time order
+    changes: Vec<(u64, usize, Level)>,
+}
+
+impl Trace {
+    pub fn new() -> Trace {
+        Trace { signals: Vec::new(), lookup: HashMap::new(), changes: Vec::new() }
+    }
+
+    // Start tracing a net, recording its level at the given time
+    pub(crate) fn add(&mut self, name: &str, net: NetId, time: u64, level: Level) {
+        if self.lookup.contains_key(&net) {
+            return;
+        }
+        let signal = self.signals.len();
+        self.signals.push((name.to_string(), net));
+        self.lookup.insert(net, signal);
+        self.changes.push((time, signal, level));
+    }
+
+    pub(crate) fn record(&mut self, time: u64, net: NetId, level: Level) {
+        if let Some(&signal) = self.lookup.get(&net) {
+            self.changes.push((time, signal, level));
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.signals.is_empty()
+    }
+
+    pub fn signals(&self) -> Vec<&str> {
+        self.signals.iter().map(|(name, _)| name.as_str()).collect()
+    }
+
+    // Times and levels a signal took on, starting with its level when tracing began
+    pub fn changes(&self, name: &str) -> Vec<(u64, Level)> {
+        match self.signals.iter().position(|(signal, _)| signal == name) {
+            Some(signal) => self
+                .changes
+                .iter()
+                .filter(|&&(_, changed, _)| changed == signal)
+                .map(|&(time, _, level)| (time, level))
+                .collect(),
+            None => Vec::new(),
+        }
+    }
+
+    // Write the trace in VCD format, one tick per nanosecond.
+    // Hierarchical names like `ha1.partial` are placed in nested scopes below `module`.
+    pub fn write_vcd(&self, module: &str, writer: &mut impl io::Write) -> io::Result<()> {
+        writeln!(writer, "$version digital-logic-circuit-simulator $end")?;
+        writeln!(writer, "$timescale 1ns $end")?;
+        writeln!(writer, "$scope module {} $end", module)?;
+
+        let mut order: Vec<usize> = (0..self.signals.len()).collect();
+        order.sort_by_key(|&signal| scope_of(&self.signals[signal].0));
+        let mut open: Vec<&str> = Vec::new();
+        for signal in order {
+            let name = &self.signals[signal].0;
+            let path: Vec<&str> = name.split('.').collect();
+            let (scopes, reference) = path.split_at(path.len() - 1);
+            let common = open.iter().zip(scopes).take_while(|(a, b)| a == b).count();
+            for _ in common..open.len() {
+                writeln!(writer, "$upscope $end")?;
+            }
+            open.truncate(common);
+            for scope in &scopes[common..] {
+                writeln!(writer, "$scope module {} $end", scope)?;
+                open.push(scope);
+            }
+            writeln!(writer, "$var wire 1 {} {} $end", identifier(signal), reference[0])?;
+        }
+        for _ in 0..open.len() {
+            writeln!(writer, "$upscope $end")?;
+        }
+        writeln!(writer, "$upscope $end")?;
+        writeln!(writer, "$enddefinitions $end")?;
+
+        let mut time = None;
+        for &(changed_at, signal, level) in &self.changes {
+            if time != Some(changed_at) {
+                writeln!(writer, "#{}", changed_at)?;
+                time = Some(changed_at);
+            }
+            writeln!(writer, "{}{}", level.symbol(), identifier(signal))?;
+        }
+        Ok(())
+    }
+}
+
+impl Default for Trace {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// Scope part of a hierarchical name, signals in the same scope are written together
+fn scope_of(name: &str) -> Vec<&str> {
+    let mut path: Vec<&str> = name.split('.').collect();
+    path.pop();
+    path
+}
+
+// Short identifier code made of printable characters, as VCD uses to refer to variables
+fn identifier(signal: usize) -> String {
+    let mut code = String::new();
+    let mut rest = signal;
+    loop {
+        code.push((b'!' + (rest % 94) as u8) as char);
+        rest /= 94;
+        if rest == 0 {
+            return code;
+        }
+        rest -= 1;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::components::nets::net_id;
+
+    #[test]
+    fn test_changes() {
+        let mut trace = Trace::new();
+        trace.add("a", net_id(3), 0, Level::Undefined);
+        trace.record(2, net_id(3), Level::High);
+        trace.record(2, net_id(4), Level::Low);
+        assert_eq!(trace.signals(), vec!["a"]);
+        assert_eq!(trace.changes("a"), vec![(0, Level::Undefined), (2, Level::High)]);
+        assert!(trace.changes("b").is_empty());
+    }
+
+    #[test]
+    fn test_write_vcd() {
+        let mut trace = Trace::new();
+        trace.add("a", net_id(0), 0, Level::Undefined);
+        trace.add("ha1.partial", net_id(1), 0, Level::HighZ);
+        trace.add("b", net_id(2), 0, Level::Low);
+        trace.record(1, net_id(0), Level::High);
+        trace.record(1, net_id(1), Level::Low);
+        trace.record(3, net_id(2), Level::High);
+
+        let mut output = Vec::new();
+        trace.write_vcd("adder", &mut output).unwrap();
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            "$version digital-logic-circuit-simulator $end\n\
+             $timescale 1ns $end\n\
+             $scope module adder $end\n\
+             $var wire 1 ! a $end\n\
+             $var wire 1 # b $end\n\
+             $scope module ha1 $end\n\
+             $var wire 1 \" partial $end\n\
+             $upscope $end\n\
+             $upscope $end\n\
+             $enddefinitions $end\n\
+             #0\n\
+             x!\n\
+             z\"\n\
+             0#\n\
+             #1\n\
+             1!\n\
+             0\"\n\
+             #3\n\
+             1#\n"
+        );
+    }
+
+    #[test]
+    fn test_identifier() {
+        assert_eq!(identifier(0), "!");
+        assert_eq!(identifier(93), "~");
+        assert_eq!(identifier(94), "!!");
+        assert_eq!(identifier(95), "\"!");
+    }
+}
@@ -0,0 +1,154 @@
+// Structural (gate-level) Verilog.
+//
+//     module half_adder(a, b, sum, carry);
+//         input a, b;
+//         output sum, carry;
+//         xor g0(sum, a, b);
+//         and #2 g1(carry, a, b);
+//     endmodule
+//
+// Supported are single-bit ports and wires, supply0 and supply1 nets for constants, the gate
+// primitives and/or/xor/nand/nor/xnor/not/buf and bufif1 with an optional `#n` delay, and
+// instances of other modules in the same source with ordered or named (`.port(net)`) connections.
+// Undeclared nets are implicit wires. Names that are not plain identifiers, e.g. the hierarchical
+// `ha1.partial`, are written as escaped identifiers.
+
+pub mod lexer;
+pub mod reader;
+pub mod writer;
+
+pub use reader::import;
+pub use writer::export;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::circuits::{FullAdder, HalfAdder};
+    use crate::components::{Level, Nets};
+    use crate::digital_logic::arithmetic::RippleCarryAdder;
+    use crate::expression::{compile, compile_outputs, parse};
+    use crate::simulation::{Circuit, Simulator};
+
+    fn round_trip(circuit: &Circuit) -> Simulator {
+        let source = export(circuit).unwrap();
+        let imported = import(&source, None).unwrap();
+        assert_eq!(imported.inputs(), circuit.inputs());
+        assert_eq!(imported.outputs(), circuit.outputs());
+        Simulator::new(imported)
+    }
+
+    #[test]
+    fn test_round_trip_half_adder() {
+        let mut nets = Nets::new();
+        let [a, b, sum, carry] = [(); 4].map(|_| nets.add());
+        let half_adder = HalfAdder::new(a, b, sum, carry);
+        let circuit = Circuit::from_component("half_adder", nets, Box::new(half_adder), &["a", "b"], &["sum", "carry"]);
+        let mut simulator = round_trip(&circuit);
+
+        for (a, b, expected_sum, expected_carry) in [
+            (Level::Low, Level::Low, Level::Low, Level::Low),
+            (Level::Low, Level::High, Level::High, Level::Low),
+            (Level::High, Level::Low, Level::High, Level::Low),
+            (Level::High, Level::High, Level::Low, Level::High),
+        ] {
+            simulator.set_level("a", a).unwrap();
+            simulator.set_level("b", b).unwrap();
+            simulator.settle().unwrap();
+            assert_eq!(simulator.level("sum").unwrap(), expected_sum);
+            assert_eq!(simulator.level("carry").unwrap(), expected_carry);
+        }
+    }
+
+    #[test]
+    fn test_round_trip_full_adder() {
+        let mut nets = Nets::new();
+        let [a, b, cin, sum, cout] = [(); 5].map(|_| nets.add());
+        let full_adder = FullAdder::new(&mut nets, a, b, cin, sum, cout);
+        let circuit = Circuit::from_component("full_adder", nets, Box::new(full_adder), &["a", "b", "cin"], &["sum", "cout"]);
+        let mut simulator = round_trip(&circuit);
+
+        for (a, b, cin, expected_sum, expected_cout) in [
+            (Level::Low, Level::Low, Level::Low, Level::Low, Level::Low),
+            (Level::Low, Level::Low, Level::High, Level::High, Level::Low),
+            (Level::Low, Level::High, Level::Low, Level::High, Level::Low),
+            (Level::Low, Level::High, Level::High, Level::Low, Level::High),
+            (Level::High, Level::Low, Level::Low, Level::High, Level::Low),
+            (Level::High, Level::Low, Level::High, Level::Low, Level::High),
+            (Level::High, Level::High, Level::Low, Level::Low, Level::High),
+            (Level::High, Level::High, Level::High, Level::High, Level::High),
+        ] {
+            simulator.set_level("a", a).unwrap();
+            simulator.set_level("b", b).unwrap();
+            simulator.set_level("cin", cin).unwrap();
+            simulator.settle().unwrap();
+            assert_eq!(simulator.level("sum").unwrap(), expected_sum);
+            assert_eq!(simulator.level("cout").unwrap(), expected_cout);
+        }
+    }
+
+    #[test]
+    fn test_round_trip_ripple_carry_adder() {
+        let mut circuit = RippleCarryAdder::new(4).into_circuit();
+        circuit.set_delay(1);
+        let mut simulator = round_trip(&circuit);
+
+        for (a, b) in [(0b0000, 0b0000), (0b1111, 0b0001), (0b0101, 0b0011), (0b1001, 0b1100)] {
+            for i in 0..4 {
+                let bit = |value: u32| if value >> i & 1 == 1 { Level::High } else { Level::Low };
+                simulator.set_level(&format!("a{}", i), bit(a)).unwrap();
+                simulator.set_level(&format!("b{}", i), bit(b)).unwrap();
+            }
+            simulator.settle().unwrap();
+
+            let sum = a + b;
+            for i in 0..4 {
+                let expected = if sum >> i & 1 == 1 { Level::High } else { Level::Low };
+                assert_eq!(simulator.level(&format!("sum{}", i)).unwrap(), expected);
+            }
+            let expected_carry = if sum > 0b1111 { Level::High } else { Level::Low };
+            assert_eq!(simulator.level("carry3").unwrap(), expected_carry);
+        }
+    }
+
+    #[test]
+    fn test_round_trip_keeps_delays() {
+        let mut circuit = RippleCarryAdder::new(4).into_circuit();
+        circuit.set_delay(1);
+        let source = export(&circuit).unwrap();
+        let imported = import(&source, None).unwrap();
+        assert_eq!(export(&imported).unwrap(), source);
+    }
+
+    #[test]
+    fn test_round_trip_constants() {
+        let circuit = compile(&parse("1").unwrap());
+        let source = export(&circuit).unwrap();
+        assert_eq!(source, "module expr(y);\n    output y;\n    supply1 y;\nendmodule\n");
+        let mut simulator = round_trip(&circuit);
+        simulator.settle().unwrap();
+        assert_eq!(simulator.level("y").unwrap(), Level::High);
+
+        // A constant output next to one driven by a gate
+        let circuit = compile_outputs(
+            "c",
+            &["a".to_string()],
+            &[("y".to_string(), parse("a | 0").unwrap()), ("z".to_string(), parse("0").unwrap())],
+        )
+        .unwrap();
+        let source = export(&circuit).unwrap();
+        assert_eq!(export(&import(&source, None).unwrap()).unwrap(), source);
+        let mut simulator = round_trip(&circuit);
+        simulator.set_level("a", Level::High).unwrap();
+        simulator.settle().unwrap();
+        assert_eq!(simulator.level("y").unwrap(), Level::High);
+        assert_eq!(simulator.level("z").unwrap(), Level::Low);
+
+        // and a constant wire
+        let circuit = import("module m(a, y); input a; output y; supply0 zero; or g(y, a, zero); endmodule", None).unwrap();
+        assert!(export(&circuit).unwrap().contains("    supply0 zero;\n"));
+        let mut simulator = round_trip(&circuit);
+        simulator.set_level("a", Level::Low).unwrap();
+        simulator.settle().unwrap();
+        assert_eq!(simulator.level("y").unwrap(), Level::Low);
+    }
+}
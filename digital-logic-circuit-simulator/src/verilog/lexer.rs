@@ -0,0 +1,159 @@
+use crate::netlist::lexer::{Token, TokenKind};
+use crate::netlist::NetlistError;
+
+// Split Verilog source into the tokens the netlist format uses as well.
+// Escaped identifiers (`\name `) become plain identifiers without the backslash.
+pub fn tokenize(source: &str) -> Result<Vec<Token>, NetlistError> {
+    let mut tokens = Vec::new();
+    let mut chars = source.chars().peekable();
+    let mut line = 1;
+    let mut column = 1;
+
+    while let Some(&c) = chars.peek() {
+        let (start_line, start_column) = (line, column);
+        let mut push = |kind| tokens.push(Token { kind, line: start_line, column: start_column });
+
+        if c == '\n' {
+            chars.next();
+            line += 1;
+            column = 1;
+            continue;
+        }
+        if c.is_whitespace() {
+            chars.next();
+            column += 1;
+            continue;
+        }
+        if c == '/' {
+            chars.next();
+            column += 1;
+            match chars.peek() {
+                Some('/') => {
+                    while chars.peek().is_some_and(|&c| c != '\n') {
+                        chars.next();
+                    }
+                }
+                Some('*') => {
+                    chars.next();
+                    column += 1;
+                    let mut previous = ' ';
+                    loop {
+                        match chars.next() {
+                            Some('\n') => {
+                                line += 1;
+                                column = 1;
+                            }
+                            Some(c) => {
+                                column += 1;
+                                if previous == '*' && c == '/' {
+                                    break;
+                                }
+                                previous = c;
+                            }
+                            None => return Err(NetlistError::new(start_line, start_column, "unterminated comment")),
+                        }
+                    }
+                }
+                _ => return Err(NetlistError::new(start_line, start_column, "unexpected character '/'")),
+            }
+            continue;
+        }
+        if c == '\\' {
+            chars.next();
+            column += 1;
+            let mut name = String::new();
+            while let Some(&c) = chars.peek().filter(|c| !c.is_whitespace()) {
+                name.push(c);
+                chars.next();
+                column += 1;
+            }
+            if name.is_empty() {
+                return Err(NetlistError::new(start_line, start_column, "empty escaped identifier"));
+            }
+            push(TokenKind::Ident(name));
+            continue;
+        }
+        if c.is_ascii_alphabetic() || c == '_' {
+            let mut name = String::new();
+            while let Some(&c) = chars.peek().filter(|c| c.is_ascii_alphanumeric() || **c == '_' || **c == '$') {
+                name.push(c);
+                chars.next();
+                column += 1;
+            }
+            push(TokenKind::Ident(name));
+            continue;
+        }
+        if c.is_ascii_digit() {
+            let mut digits = String::new();
+            while let Some(&c) = chars.peek().filter(|c| c.is_ascii_digit()) {
+                digits.push(c);
+                chars.next();
+                column += 1;
+            }
+            let value = digits
+                .parse()
+                .map_err(|_| NetlistError::new(start_line, start_column, format!("number {} is too large", digits)))?;
+            push(TokenKind::Number(value));
+            continue;
+        }
+
+        chars.next();
+        column += 1;
+        let kind = match c {
+            '(' => TokenKind::LParen,
+            ')' => TokenKind::RParen,
+            ',' => TokenKind::Comma,
+            ';' => TokenKind::Semicolon,
+            '#' => TokenKind::Hash,
+            '.' => TokenKind::Dot,
+            '[' => return Err(NetlistError::new(start_line, start_column, "vectors are not supported")),
+            _ => return Err(NetlistError::new(start_line, start_column, format!("unexpected character '{}'", c))),
+        };
+        push(kind);
+    }
+
+    tokens.push(Token { kind: TokenKind::Eof, line, column });
+    Ok(tokens)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn kinds(source: &str) -> Vec<TokenKind> {
+        tokenize(source).unwrap().into_iter().map(|token| token.kind).collect()
+    }
+
+    #[test]
+    fn test_tokenize() {
+        assert_eq!(
+            kinds("and #2 g1(y, \\ha1.s , b); // comment\n/* block\n comment */ .a(x)"),
+            vec![
+                TokenKind::Ident("and".to_string()),
+                TokenKind::Hash,
+                TokenKind::Number(2),
+                TokenKind::Ident("g1".to_string()),
+                TokenKind::LParen,
+                TokenKind::Ident("y".to_string()),
+                TokenKind::Comma,
+                TokenKind::Ident("ha1.s".to_string()),
+                TokenKind::Comma,
+                TokenKind::Ident("b".to_string()),
+                TokenKind::RParen,
+                TokenKind::Semicolon,
+                TokenKind::Dot,
+                TokenKind::Ident("a".to_string()),
+                TokenKind::LParen,
+                TokenKind::Ident("x".to_string()),
+                TokenKind::RParen,
+                TokenKind::Eof,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_errors() {
+        assert_eq!(tokenize("input [3:0] a;"), Err(NetlistError::new(1, 7, "vectors are not supported")));
+        assert_eq!(tokenize("a\n /* open"), Err(NetlistError::new(2, 2, "unterminated comment")));
+    }
+}
@@ -0,0 +1,436 @@
+use std::collections::HashMap;
+
+use crate::netlist::lexer::TokenKind;
+use crate::netlist::loader::build;
+use crate::netlist::parser::{Parser, NetRef};
+use crate::netlist::{Definition, Instance, NetlistError};
+use crate::simulation::Circuit;
+use crate::verilog::lexer::tokenize;
+
+// Verilog gate primitives and the netlist built-ins they map to.
+// Primitives list their output first, followed by the inputs.
+pub const PRIMITIVES: &[(&str, &str)] = &[
+    ("and", "and"),
+    ("or", "or"),
+    ("xor", "xor"),
+    ("nand", "nand"),
+    ("nor", "nor"),
+    ("xnor", "xnor"),
+    ("not", "not"),
+    ("buf", "buf"),
+    ("bufif1", "tri"),
+];
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Direction {
+    Input,
+    Output,
+}
+
+// Nets of an instance, `None` for a connection that is left empty
+enum Connections {
+    Ordered(Vec<Option<NetRef>>),
+    Named(Vec<(NetRef, Option<NetRef>)>),
+}
+
+struct ModuleInstance {
+    kind: NetRef,
+    name: Option<NetRef>,
+    parameter: Option<u64>,
+    connections: Connections,
+    // Position of the opening parenthesis, for errors about missing connections
+    position: NetRef,
+}
+
+struct Module {
+    name: NetRef,
+    ports: Vec<NetRef>,
+    directions: HashMap<String, Direction>,
+    wires: Vec<NetRef>,
+    // Nets declared supply0 or supply1, with whether they are held at 1
+    supplies: Vec<(NetRef, bool)>,
+    instances: Vec<ModuleInstance>,
+}
+
+// Build a circuit from structural Verilog. Without a name the last module is the top level.
+pub fn import(source: &str, top: Option<&str>) -> Result<Circuit, NetlistError> {
+    let mut parser = Parser::new(tokenize(source)?);
+    let mut modules = Vec::new();
+    while parser.peek().kind != TokenKind::Eof {
+        modules.push(module(&mut parser)?);
+    }
+
+    let definitions = modules
+        .iter()
+        .map(|module| definition(module, &modules))
+        .collect::<Result<Vec<Definition>, NetlistError>>()?;
+    let mut builtins: Vec<&str> = PRIMITIVES.iter().map(|(_, builtin)| *builtin).collect();
+    builtins.extend(["tie0", "tie1"]);
+    build(&definitions, top, &builtins)
+}
+
+fn module(parser: &mut Parser) -> Result<Module, NetlistError> {
+    let keyword = parser.ident("'module'")?;
+    if keyword.name != "module" {
+        return Err(NetlistError::new(
+            keyword.line,
+            keyword.column,
+            format!("expected 'module', found '{}'", keyword.name),
+        ));
+    }
+    let name = parser.ident("a module name")?;
+    let mut module = Module {
+        name,
+        ports: Vec::new(),
+        directions: HashMap::new(),
+        wires: Vec::new(),
+        supplies: Vec::new(),
+        instances: Vec::new(),
+    };
+
+    // Port list, either plain names or with directions as in `(input a, b, output y)`
+    if parser.peek().kind == TokenKind::LParen {
+        parser.next();
+        let mut direction = None;
+        while parser.peek().kind != TokenKind::RParen {
+            let mut port = parser.ident("a port name")?;
+            if let Some(declared) = keyword_direction(&port.name) {
+                direction = Some(declared);
+                port = parser.ident("a port name")?;
+            }
+            if port.name == "wire" {
+                port = parser.ident("a port name")?;
+            }
+            if let Some(direction) = direction {
+                module.directions.insert(port.name.clone(), direction);
+            }
+            module.ports.push(port);
+            let token = parser.peek().clone();
+            match token.kind {
+                TokenKind::Comma => {
+                    parser.next();
+                }
+                TokenKind::RParen => {}
+                _ => return Err(Parser::error(&token, "',' or ')'")),
+            }
+        }
+        parser.next();
+    }
+    parser.expect(TokenKind::Semicolon)?;
+
+    loop {
+        let item = parser.ident("a declaration, an instance or 'endmodule'")?;
+        if item.name == "endmodule" {
+            return Ok(module);
+        }
+        if let Some(direction) = keyword_direction(&item.name) {
+            for port in names(parser)? {
+                if !module.ports.iter().any(|p| p.name == port.name) {
+                    return Err(NetlistError::new(
+                        port.line,
+                        port.column,
+                        format!("'{}' is not a port of module '{}'", port.name, module.name.name),
+                    ));
+                }
+                module.directions.insert(port.name, direction);
+            }
+        } else if item.name == "wire" {
+            module.wires.extend(names(parser)?);
+        } else if item.name == "supply0" || item.name == "supply1" {
+            let high = item.name == "supply1";
+            module.supplies.extend(names(parser)?.into_iter().map(|net| (net, high)));
+        } else {
+            module.instances.push(instance(parser, item)?);
+        }
+    }
+}
+
+fn keyword_direction(name: &str) -> Option<Direction> {
+    match name {
+        "input" => Some(Direction::Input),
+        "output" => Some(Direction::Output),
+        _ => None,
+    }
+}
+
+// `a, b, c;` with an optional leading `wire`
+fn names(parser: &mut Parser) -> Result<Vec<NetRef>, NetlistError> {
+    let mut names = Vec::new();
+    loop {
+        let mut name = parser.ident("a net name")?;
+        if name.name == "wire" && names.is_empty() {
+            name = parser.ident("a net name")?;
+        }
+        names.push(name);
+        let token = parser.next();
+        match token.kind {
+            TokenKind::Comma => continue,
+            TokenKind::Semicolon => return Ok(names),
+            _ => return Err(Parser::error(&token, "',' or ';'")),
+        }
+    }
+}
+
+fn instance(parser: &mut Parser, kind: NetRef) -> Result<ModuleInstance, NetlistError> {
+    let parameter = if parser.peek().kind == TokenKind::Hash {
+        parser.next();
+        let parenthesized = parser.peek().kind == TokenKind::LParen;
+        if parenthesized {
+            parser.next();
+        }
+        let token = parser.next();
+        let delay = match token.kind {
+            TokenKind::Number(value) => value,
+            _ => return Err(Parser::error(&token, "a delay")),
+        };
+        if parenthesized {
+            parser.expect(TokenKind::RParen)?;
+        }
+        Some(delay)
+    } else {
+        None
+    };
+    let name = match parser.peek().kind {
+        TokenKind::Ident(_) => Some(parser.ident("an instance name")?),
+        _ => None,
+    };
+
+    let open = parser.expect(TokenKind::LParen)?;
+    let position = NetRef { name: String::from("_"), line: open.line, column: open.column };
+    let connections = if parser.peek().kind == TokenKind::Dot {
+        let mut named = Vec::new();
+        loop {
+            parser.expect(TokenKind::Dot)?;
+            let port = parser.ident("a port name")?;
+            parser.expect(TokenKind::LParen)?;
+            let net = match parser.peek().kind {
+                TokenKind::RParen => None,
+                _ => Some(parser.ident("a net name")?),
+            };
+            parser.expect(TokenKind::RParen)?;
+            named.push((port, net));
+            let token = parser.next();
+            match token.kind {
+                TokenKind::Comma => continue,
+                TokenKind::RParen => break,
+                _ => return Err(Parser::error(&token, "',' or ')'")),
+            }
+        }
+        Connections::Named(named)
+    } else {
+        let mut ordered = Vec::new();
+        loop {
+            let net = match parser.peek().kind {
+                TokenKind::Comma | TokenKind::RParen => None,
+                _ => Some(parser.ident("a net name")?),
+            };
+            ordered.push(net);
+            let token = parser.next();
+            match token.kind {
+                TokenKind::Comma => continue,
+                TokenKind::RParen => break,
+                _ => return Err(Parser::error(&token, "',' or ')'")),
+            }
+        }
+        Connections::Ordered(ordered)
+    };
+    parser.expect(TokenKind::Semicolon)?;
+    Ok(ModuleInstance { kind, name, parameter, connections, position })
+}
+
+// Translate a module into a netlist definition, splitting connections into inputs and outputs
+fn definition(module: &Module, modules: &[Module]) -> Result<Definition, NetlistError> {
+    let mut inputs = Vec::new();
+    let mut outputs = Vec::new();
+    for port in &module.ports {
+        match module.directions.get(&port.name) {
+            Some(Direction::Input) => inputs.push(port.clone()),
+            Some(Direction::Output) => outputs.push(port.clone()),
+            None => {
+                return Err(NetlistError::new(
+                    port.line,
+                    port.column,
+                    format!("port '{}' has no direction", port.name),
+                ))
+            }
+        }
+    }
+
+    // Declared wires that are not ports, followed by implicit wires in order of use
+    let mut wires: Vec<NetRef> = Vec::new();
+    let mut declare = |net: &NetRef| {
+        if !module.ports.iter().chain(wires.iter()).any(|known| known.name == net.name) {
+            wires.push(net.clone());
+        }
+    };
+    for wire in module.wires.iter().chain(module.supplies.iter().map(|(net, _)| net)) {
+        declare(wire);
+    }
+    for instance in &module.instances {
+        let nets: Vec<&NetRef> = match &instance.connections {
+            Connections::Ordered(nets) => nets.iter().flatten().collect(),
+            Connections::Named(nets) => nets.iter().filter_map(|(_, net)| net.as_ref()).collect(),
+        };
+        for net in nets {
+            declare(net);
+        }
+    }
+
+    let mut instances = Vec::new();
+    for (index, instance) in module.instances.iter().enumerate() {
+        let unconnected = || instance.position.clone();
+        let primitive = PRIMITIVES.iter().find(|(primitive, _)| *primitive == instance.kind.name);
+        let (kind, inputs, outputs) = if let Some((_, builtin)) = primitive {
+            let nets = match &instance.connections {
+                Connections::Ordered(nets) => nets,
+                Connections::Named(_) => return Err(error(&instance.kind, "primitives take ordered connections")),
+            };
+            let mut nets = nets.iter().map(|net| net.clone().unwrap_or_else(unconnected));
+            let output = nets.next().into_iter().collect();
+            let kind = NetRef { name: builtin.to_string(), ..instance.kind.clone() };
+            (kind, nets.collect(), output)
+        } else {
+            let child = modules
+                .iter()
+                .find(|child| child.name.name == instance.kind.name)
+                .ok_or_else(|| error(&instance.kind, format!("unknown module '{}'", instance.kind.name)))?;
+            if instance.name.is_none() {
+                return Err(error(&instance.kind, "module instances need a name"));
+            }
+            let nets: Vec<NetRef> = match &instance.connections {
+                Connections::Ordered(nets) => {
+                    if nets.len() != child.ports.len() {
+                        return Err(error(
+                            &instance.kind,
+                            format!("'{}' has {} ports, found {}", child.name.name, child.ports.len(), nets.len()),
+                        ));
+                    }
+                    nets.iter().map(|net| net.clone().unwrap_or_else(unconnected)).collect()
+                }
+                Connections::Named(named) => {
+                    if let Some((port, _)) = named.iter().find(|(port, _)| !child.ports.iter().any(|p| p.name == port.name)) {
+                        return Err(error(port, format!("'{}' has no port '{}'", child.name.name, port.name)));
+                    }
+                    child
+                        .ports
+                        .iter()
+                        .map(|port| {
+                            named
+                                .iter()
+                                .find(|(name, _)| name.name == port.name)
+                                .and_then(|(_, net)| net.clone())
+                                .unwrap_or_else(unconnected)
+                        })
+                        .collect()
+                }
+            };
+            let (mut inputs, mut outputs) = (Vec::new(), Vec::new());
+            for (port, net) in child.ports.iter().zip(nets) {
+                match child.directions.get(&port.name) {
+                    Some(Direction::Output) => outputs.push(net),
+                    _ => inputs.push(net),
+                }
+            }
+            (instance.kind.clone(), inputs, outputs)
+        };
+
+        // Unnamed primitives get a name that cannot clash with a Verilog identifier
+        let name = instance
+            .name
+            .clone()
+            .unwrap_or_else(|| NetRef { name: format!("{}#{}", instance.kind.name, index), ..instance.kind.clone() });
+        instances.push(Instance { kind, name, parameter: instance.parameter, inputs, outputs });
+    }
+    // Supply nets are held by the netlist's tie-offs
+    for (index, (net, high)) in module.supplies.iter().enumerate() {
+        let kind = NetRef { name: String::from(if *high { "tie1" } else { "tie0" }), ..net.clone() };
+        let name = NetRef { name: format!("supply#{}", index), ..net.clone() };
+        instances.push(Instance { kind, name, parameter: None, inputs: Vec::new(), outputs: vec![net.clone()] });
+    }
+
+    Ok(Definition { name: module.name.clone(), inputs, outputs, wires, instances })
+}
+
+fn error(at: &NetRef, message: impl Into<String>) -> NetlistError {
+    NetlistError::new(at.line, at.column, message)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::components::Level;
+    use crate::simulation::Simulator;
+
+    const ADDER: &str = "
+        // Full adder from two half adders
+        module half_adder(a, b, sum, carry);
+            input a, b;
+            output sum, carry;
+            xor (sum, a, b);
+            and #1 g1(carry, a, b);
+        endmodule
+
+        module full_adder(input a, b, cin, output sum, cout);
+            wire s1, c1;
+            half_adder ha1(a, b, s1, c1);
+            half_adder ha2(.a(s1), .b(cin), .sum(sum), .carry(c2));
+            or #(2) g3(cout, c1, c2);
+        endmodule
+    ";
+
+    #[test]
+    fn test_import_full_adder() {
+        let circuit = import(ADDER, None).unwrap();
+        assert_eq!(circuit.name(), "full_adder");
+        assert_eq!(circuit.inputs(), &["a", "b", "cin"]);
+        assert_eq!(circuit.outputs(), &["sum", "cout"]);
+        // c2 is an implicit wire
+        assert!(circuit.net("c2").is_some());
+        assert_eq!(circuit.component_count(), 5);
+
+        let mut simulator = Simulator::new(circuit);
+        for (a, b, cin, expected_sum, expected_cout) in [
+            (Level::Low, Level::High, Level::High, Level::Low, Level::High),
+            (Level::High, Level::High, Level::High, Level::High, Level::High),
+            (Level::Low, Level::Low, Level::High, Level::High, Level::Low),
+        ] {
+            simulator.set_level("a", a).unwrap();
+            simulator.set_level("b", b).unwrap();
+            simulator.set_level("cin", cin).unwrap();
+            simulator.settle().unwrap();
+            assert_eq!(simulator.level("sum").unwrap(), expected_sum);
+            assert_eq!(simulator.level("cout").unwrap(), expected_cout);
+        }
+    }
+
+    #[test]
+    fn test_import_tri_state() {
+        let circuit = import("module bus(d, en, y); input d, en; output y; bufif1 b(y, d, en); endmodule", None).unwrap();
+        let mut simulator = Simulator::new(circuit);
+        simulator.set_level("d", Level::High).unwrap();
+        simulator.set_level("en", Level::Low).unwrap();
+        simulator.settle().unwrap();
+        assert_eq!(simulator.level("y").unwrap(), Level::HighZ);
+    }
+
+    #[test]
+    fn test_import_errors() {
+        for (source, expected) in [
+            ("module m(a); endmodule", NetlistError::new(1, 10, "port 'a' has no direction")),
+            (
+                "module m(a);\n input a;\n output y;\nendmodule",
+                NetlistError::new(3, 9, "'y' is not a port of module 'm'"),
+            ),
+            (
+                "module m(a);\n input a;\n adder u(a);\nendmodule",
+                NetlistError::new(3, 2, "unknown module 'adder'"),
+            ),
+            (
+                "module m(a, y);\n input a;\n output y;\n not g(y, a)\nendmodule",
+                NetlistError::new(5, 1, "expected ';', found 'endmodule'"),
+            ),
+        ] {
+            assert_eq!(import(source, None).err(), Some(expected));
+        }
+    }
+}
@@ -0,0 +1,162 @@
+use std::fmt::Write;
+
+use crate::components::gates::GateKind;
+use crate::components::NetId;
+use crate::netlist::writer::constant_nets;
+use crate::simulation::Circuit;
+
+// Words that cannot be used as plain identifiers
+const KEYWORDS: &[&str] = &[
+    "always", "and", "assign", "begin", "buf", "bufif0", "bufif1", "case", "default", "else", "end",
+    "endcase", "endmodule", "for", "function", "if", "initial", "inout", "input", "integer", "module",
+    "nand", "nor", "not", "notif0", "notif1", "or", "output", "parameter", "reg", "supply0", "supply1",
+    "tri", "wire", "xnor", "xor",
+];
+
+// Write a circuit as a structural Verilog module built from gate primitives.
+// Circuits without declared ports get the nets nothing drives as inputs and the nets nothing
+// reads as outputs. Components other than logic gates cannot be written. Nets nothing drives
+// that are held at 0 or 1 are declared as supply0 and supply1.
+pub fn export(circuit: &Circuit) -> Result<String, String> {
+    let (inputs, outputs) = ports(circuit);
+    let mut verilog = String::new();
+
+    let ports: Vec<String> = inputs.iter().chain(outputs.iter()).map(|&net| identifier(circuit.net_name(net))).collect();
+    writeln!(verilog, "module {}({});", identifier(circuit.name()), ports.join(", ")).unwrap();
+    for (keyword, nets) in [("input", &inputs), ("output", &outputs)] {
+        if !nets.is_empty() {
+            let names: Vec<String> = nets.iter().map(|&net| identifier(circuit.net_name(net))).collect();
+            writeln!(verilog, "    {} {};", keyword, names.join(", ")).unwrap();
+        }
+    }
+    let constants = constant_nets(circuit, &inputs);
+    let wires: Vec<String> = circuit
+        .net_ids()
+        .filter(|net| !inputs.contains(net) && !outputs.contains(net))
+        .filter(|net| !constants.iter().any(|(constant, _)| constant == net))
+        .map(|net| identifier(circuit.net_name(net)))
+        .collect();
+    if !wires.is_empty() {
+        writeln!(verilog, "    wire {};", wires.join(", ")).unwrap();
+    }
+    for (keyword, level) in [("supply0", false), ("supply1", true)] {
+        let names: Vec<String> = constants
+            .iter()
+            .filter(|&&(_, high)| high == level)
+            .map(|&(net, _)| identifier(circuit.net_name(net)))
+            .collect();
+        if !names.is_empty() {
+            writeln!(verilog, "    {} {};", keyword, names.join(", ")).unwrap();
+        }
+    }
+
+    for id in circuit.component_ids() {
+        let component = circuit.component(id);
+        let primitive = match component.gate_kind() {
+            Some(GateKind::And) => "and",
+            Some(GateKind::Or) => "or",
+            Some(GateKind::Xor) => "xor",
+            Some(GateKind::Nand) => "nand",
+            Some(GateKind::Nor) => "nor",
+            Some(GateKind::Xnor) => "xnor",
+            Some(GateKind::Not) => "not",
+            Some(GateKind::Buffer) => "buf",
+            Some(GateKind::TriState) => "bufif1",
+            None => return Err(format!("component {} is not a logic gate and has no Verilog primitive", id.index())),
+        };
+        let delay = match component.delay() {
+            0 => String::new(),
+            delay => format!(" #{}", delay),
+        };
+        let nets: Vec<String> = circuit
+            .component_outputs(id)
+            .iter()
+            .chain(circuit.component_inputs(id))
+            .map(|&net| identifier(circuit.net_name(net)))
+            .collect();
+        writeln!(verilog, "    {}{} g{}({});", primitive, delay, id.index(), nets.join(", ")).unwrap();
+    }
+
+    verilog.push_str("endmodule\n");
+    Ok(verilog)
+}
+
+// The input and output port nets
+fn ports(circuit: &Circuit) -> (Vec<NetId>, Vec<NetId>) {
+    if !circuit.inputs().is_empty() || !circuit.outputs().is_empty() {
+        let lookup = |names: &[String]| names.iter().filter_map(|name| circuit.net(name)).collect();
+        return (lookup(circuit.inputs()), lookup(circuit.outputs()));
+    }
+
+    let driven: Vec<NetId> = circuit.component_ids().flat_map(|c| circuit.component_outputs(c).to_vec()).collect();
+    let read: Vec<NetId> = circuit.component_ids().flat_map(|c| circuit.component_inputs(c).to_vec()).collect();
+    let inputs = circuit.net_ids().filter(|net| read.contains(net) && !driven.contains(net)).collect();
+    let outputs = circuit.net_ids().filter(|net| driven.contains(net) && !read.contains(net)).collect();
+    (inputs, outputs)
+}
+
+// A plain identifier if possible, otherwise an escaped identifier terminated by a space
+fn identifier(name: &str) -> String {
+    let plain = name.chars().next().is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '$')
+        && !KEYWORDS.contains(&name);
+    if plain {
+        name.to_string()
+    } else {
+        format!("\\{} ", name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::components::gates::{NOTGate, TriStateBuffer};
+    use crate::components::sequential::DLatch;
+
+    #[test]
+    fn test_export_gates() {
+        let mut circuit = Circuit::new().with_name("example");
+        let a = circuit.add_input("a").unwrap();
+        let en = circuit.add_input("en").unwrap();
+        let y = circuit.add_output("y").unwrap();
+        let inverted = circuit.add_net("a.inverted").unwrap();
+        circuit.add_component(Box::new(NOTGate::new(a, inverted).with_delay(2)));
+        circuit.add_component(Box::new(TriStateBuffer::new(inverted, en, y)));
+
+        assert_eq!(
+            export(&circuit).unwrap(),
+            "module example(a, en, y);\n\
+             \x20   input a, en;\n\
+             \x20   output y;\n\
+             \x20   wire \\a.inverted ;\n\
+             \x20   not #2 g0(\\a.inverted , a);\n\
+             \x20   bufif1 g1(y, \\a.inverted , en);\n\
+             endmodule\n"
+        );
+    }
+
+    #[test]
+    fn test_export_infers_ports() {
+        let mut circuit = Circuit::new();
+        let a = circuit.add_net("a").unwrap();
+        let y = circuit.add_net("y").unwrap();
+        circuit.add_component(Box::new(NOTGate::new(a, y)));
+        assert!(export(&circuit).unwrap().starts_with("module circuit(a, y);\n    input a;\n    output y;\n"));
+    }
+
+    #[test]
+    fn test_export_rejects_sequential_components() {
+        let mut circuit = Circuit::new();
+        let nets: Vec<_> = ["d", "en", "q", "q_bar"].iter().map(|name| circuit.add_net(name).unwrap()).collect();
+        circuit.add_component(Box::new(DLatch::new(nets[0], nets[1], nets[2], nets[3])));
+        assert!(export(&circuit).is_err());
+    }
+
+    #[test]
+    fn test_identifier() {
+        assert_eq!(identifier("sum0"), "sum0");
+        assert_eq!(identifier("ha1.partial"), "\\ha1.partial ");
+        assert_eq!(identifier("and"), "\\and ");
+        assert_eq!(identifier("0a"), "\\0a ");
+    }
+}